@@ -0,0 +1,40 @@
+#![no_main]
+
+use heaps::{
+    BinaryHeap, BinomialHeap, BucketQueue, DaryHeap, FibonacciHeap, FrontToBack, LazyBinomialHeap,
+    Multipass, PairingHeap, RankPairingHeap, StableLazyBinomialHeap, StablePairingHeap, TwoPass,
+    Type1, Type2,
+};
+use heaps_fuzz::{fuzz_addressable_heap, Limits};
+use libfuzzer_sys::fuzz_target;
+
+type Item = (u32, u32);
+
+// The first byte picks the heap
+fuzz_target!(|data: &[u8]| {
+    let Some((&heap, data)) = data.split_first() else {
+        return;
+    };
+    let limits = Limits::default();
+    match heap % 13 {
+        0 => fuzz_addressable_heap::<BinaryHeap<Item>>(data, limits),
+        1 => fuzz_addressable_heap::<DaryHeap<Item, 4>>(data, limits),
+        2 => fuzz_addressable_heap::<BinomialHeap<Item>>(data, limits),
+        3 => fuzz_addressable_heap::<LazyBinomialHeap<Item>>(data, limits),
+        4 => fuzz_addressable_heap::<FibonacciHeap<Item>>(data, limits),
+        5 => fuzz_addressable_heap::<PairingHeap<Item, TwoPass>>(data, limits),
+        6 => fuzz_addressable_heap::<PairingHeap<Item, FrontToBack>>(data, limits),
+        7 => fuzz_addressable_heap::<PairingHeap<Item, Multipass>>(data, limits),
+        8 => fuzz_addressable_heap::<RankPairingHeap<Item, Type1>>(data, limits),
+        9 => fuzz_addressable_heap::<RankPairingHeap<Item, Type2>>(data, limits),
+        10 => fuzz_addressable_heap::<StableLazyBinomialHeap<Item>>(data, limits),
+        11 => fuzz_addressable_heap::<StablePairingHeap<Item>>(data, limits),
+        _ => fuzz_addressable_heap::<BucketQueue<u32>>(
+            data,
+            Limits {
+                max_key: 256,
+                ..limits
+            },
+        ),
+    }
+});
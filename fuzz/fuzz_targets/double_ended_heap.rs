@@ -0,0 +1,9 @@
+#![no_main]
+
+use heaps::MinMaxHeap;
+use heaps_fuzz::{fuzz_double_ended_heap, Limits};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_double_ended_heap::<MinMaxHeap<(u32, u32)>>(data, Limits::default());
+});
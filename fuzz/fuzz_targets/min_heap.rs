@@ -0,0 +1,56 @@
+#![no_main]
+
+use heaps::{
+    ArcPointer, ArenaBinomialHeap, ArenaLazyBinomialHeap, BinaryHeap, BinomialHeap, BucketQueue,
+    DaryHeap, FibonacciHeap, FrontToBack, LazyBinomialHeap, LeftistHeap, MinMaxHeap, Multipass,
+    PairingHeap, PersistentLeftistHeap, RadixHeap, RankPairingHeap, RcPointer, SkewHeap,
+    StableBinaryHeap, StableLazyBinomialHeap, TwoPass, Type1, Type2,
+};
+use heaps_fuzz::{fuzz_min_heap, Limits};
+use libfuzzer_sys::fuzz_target;
+
+type Item = (u32, u32);
+
+// The first byte picks the heap
+fuzz_target!(|data: &[u8]| {
+    let Some((&heap, data)) = data.split_first() else {
+        return;
+    };
+    let limits = Limits::default();
+    match heap % 22 {
+        0 => fuzz_min_heap::<BinaryHeap<Item>>(data, limits),
+        1 => fuzz_min_heap::<DaryHeap<Item, 4>>(data, limits),
+        2 => fuzz_min_heap::<MinMaxHeap<Item>>(data, limits),
+        3 => fuzz_min_heap::<BinomialHeap<Item>>(data, limits),
+        4 => fuzz_min_heap::<LazyBinomialHeap<Item>>(data, limits),
+        5 => fuzz_min_heap::<ArenaBinomialHeap<Item>>(data, limits),
+        6 => fuzz_min_heap::<ArenaLazyBinomialHeap<Item>>(data, limits),
+        7 => fuzz_min_heap::<FibonacciHeap<Item>>(data, limits),
+        8 => fuzz_min_heap::<PairingHeap<Item, TwoPass>>(data, limits),
+        9 => fuzz_min_heap::<PairingHeap<Item, FrontToBack>>(data, limits),
+        10 => fuzz_min_heap::<PairingHeap<Item, Multipass>>(data, limits),
+        11 => fuzz_min_heap::<RankPairingHeap<Item, Type1>>(data, limits),
+        12 => fuzz_min_heap::<RankPairingHeap<Item, Type2>>(data, limits),
+        13 => fuzz_min_heap::<LeftistHeap<Item>>(data, limits),
+        14 => fuzz_min_heap::<SkewHeap<Item>>(data, limits),
+        15 => fuzz_min_heap::<PersistentLeftistHeap<Item, RcPointer>>(data, limits),
+        16 => fuzz_min_heap::<PersistentLeftistHeap<Item, ArcPointer>>(data, limits),
+        17 => fuzz_min_heap::<StableBinaryHeap<Item>>(data, limits),
+        18 => fuzz_min_heap::<StableLazyBinomialHeap<Item>>(data, limits),
+        19 => fuzz_min_heap::<RadixHeap<u32, u32>>(
+            data,
+            Limits {
+                monotone: true,
+                ..limits
+            },
+        ),
+        20 => fuzz_min_heap::<BucketQueue<u32>>(
+            data,
+            Limits {
+                max_key: 256,
+                ..limits
+            },
+        ),
+        _ => fuzz_min_heap::<DaryHeap<Item, 3>>(data, limits),
+    }
+});
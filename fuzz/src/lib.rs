@@ -0,0 +1,457 @@
+//! Decodes the fuzzer's bytes into a sequence of operations, runs it on a heap and on a sorted set
+//! as a reference model, and panics as soon as they disagree or the heap breaks an invariant
+
+use std::{collections::BTreeSet, fmt::Debug, mem};
+
+use heaps::{AddressableHeap, CheckInvariants, DoubleEndedHeap, Handle, HandleError, MinHeap};
+
+/// Most operations run on one input, since every operation walks the whole heap to check it
+const MAX_OPS: usize = 1000;
+/// Most operations run on a sub-heap before it is melded in
+const MAX_SUB_OPS: u8 = 16;
+/// Most keys heapified at once
+const MAX_HEAPIFY: u8 = 32;
+
+/// Items the fuzzer can build from a key and an id unique to the item, and compare with the
+/// model by both
+pub trait FuzzItem: Debug {
+    fn new(key: u32, id: u32) -> Self;
+    fn key(&self) -> u32;
+    fn id(&self) -> u32;
+}
+
+/// Ordered by key and then by id, and used as key and value by a [`RadixHeap`](heaps::RadixHeap)
+impl FuzzItem for (u32, u32) {
+    fn new(key: u32, id: u32) -> Self {
+        (key, id)
+    }
+    fn key(&self) -> u32 {
+        self.0
+    }
+    fn id(&self) -> u32 {
+        self.1
+    }
+}
+
+/// Items of a [`BucketQueue`](heaps::BucketQueue)
+impl FuzzItem for (usize, u32) {
+    fn new(key: u32, id: u32) -> Self {
+        (key as usize, id)
+    }
+    fn key(&self) -> u32 {
+        self.0 as u32
+    }
+    fn id(&self) -> u32 {
+        self.1
+    }
+}
+
+/// Keys the heap accepts
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// Keys are taken modulo this
+    pub max_key: u32,
+    /// Keys inserted are raised to the last key extracted, for monotone heaps
+    pub monotone: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_key: u32::MAX,
+            monotone: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Op {
+    Insert(u32),
+    ExtractMin,
+    /// Runs the operations on a new heap and melds it in, as the first heap passed to `meld` or
+    /// as the second
+    Meld {
+        ops: Vec<Op>,
+        sub_heap_first: bool,
+    },
+    /// Replaces the heap with a heapified one
+    Heapify(Vec<u32>),
+    Clear,
+    InsertWithHandle(u32),
+    /// Handles are picked by index among every handle given out, including the stale ones
+    Get(u8),
+    DecreaseKey {
+        handle: u8,
+        by: u32,
+        increase: bool,
+    },
+    Delete(u8),
+    ExtractMax,
+    PushPopMax(u32),
+}
+
+/// Operations a heap supports, past those of [`MinHeap`]
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Min,
+    Addressable,
+    DoubleEnded,
+}
+
+/// Rest of the fuzzer's bytes, reading as zeros once they run out
+struct Input<'a>(&'a [u8]);
+
+impl Input<'_> {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn byte(&mut self) -> u8 {
+        match self.0.split_first() {
+            Some((&byte, rest)) => {
+                self.0 = rest;
+                byte
+            }
+            None => 0,
+        }
+    }
+
+    /// Mostly small keys so there are many duplicates, and now and then any key
+    fn key(&mut self) -> u32 {
+        let byte = self.byte();
+        if byte < 0xC0 {
+            u32::from(byte % 32)
+        } else {
+            u32::from_le_bytes([self.byte(), self.byte(), self.byte(), self.byte()])
+        }
+    }
+
+    fn op(&mut self, kind: Kind, nested: bool) -> Op {
+        let choices = match kind {
+            Kind::Min => 10,
+            Kind::Addressable => 16,
+            Kind::DoubleEnded => 13,
+        };
+        match self.byte() % choices {
+            0..=3 => Op::Insert(self.key()),
+            4..=6 => Op::ExtractMin,
+            // Sub-heaps don't meld in sub-heaps of their own
+            7 if nested => Op::Insert(self.key()),
+            7 => {
+                let len = self.byte() % (MAX_SUB_OPS + 1);
+                Op::Meld {
+                    ops: (0..len).map(|_| self.op(kind, true)).collect(),
+                    sub_heap_first: self.byte().is_multiple_of(2),
+                }
+            }
+            8 => {
+                let len = self.byte() % (MAX_HEAPIFY + 1);
+                Op::Heapify((0..len).map(|_| self.key()).collect())
+            }
+            9 => Op::Clear,
+            10 | 11 if kind == Kind::DoubleEnded => Op::ExtractMax,
+            12 if kind == Kind::DoubleEnded => Op::PushPopMax(self.key()),
+            10 | 11 => Op::InsertWithHandle(self.key()),
+            12 => Op::Get(self.byte()),
+            13 | 14 => Op::DecreaseKey {
+                handle: self.byte(),
+                by: self.key(),
+                increase: self.byte().is_multiple_of(8),
+            },
+            _ => Op::Delete(self.byte()),
+        }
+    }
+
+    fn ops(mut self, kind: Kind) -> Vec<Op> {
+        let mut ops = Vec::new();
+        while !self.is_empty() && ops.len() < MAX_OPS {
+            ops.push(self.op(kind, false));
+        }
+        ops
+    }
+}
+
+/// Heap under test next to the model, which holds the key and id of every item in the heap
+struct Fuzzer<H> {
+    heap: H,
+    model: BTreeSet<(u32, u32)>,
+    limits: Limits,
+    /// Lowest key a monotone heap accepts
+    floor: u32,
+    next_id: u32,
+    /// Every handle given out, with the item it refers to if that item is still in the model
+    handles: Vec<(Handle, (u32, u32))>,
+}
+
+impl<H: MinHeap<Item: FuzzItem> + CheckInvariants + Default> Fuzzer<H> {
+    fn new(limits: Limits, floor: u32, next_id: u32) -> Self {
+        Self {
+            heap: H::default(),
+            model: BTreeSet::new(),
+            limits,
+            floor,
+            next_id,
+            handles: Vec::new(),
+        }
+    }
+
+    /// Fits a decoded key to the limits and pairs it with a new id
+    fn item(&mut self, key: u32) -> (u32, u32) {
+        let mut key = key % self.limits.max_key;
+        if self.limits.monotone {
+            key = key.max(self.floor);
+        }
+        self.next_id += 1;
+        (key, self.next_id)
+    }
+
+    fn check(&self) {
+        let min = self.heap.peek_min().map(|item| (item.key(), item.id()));
+        let expected = self.model.first();
+        assert_eq!(
+            min.map(|min| min.0),
+            expected.map(|expected| expected.0),
+            "peek_min gave {min:?} instead of {expected:?}"
+        );
+        if let Some(min) = min {
+            assert!(
+                self.model.contains(&min),
+                "peek_min gave {min:?} which isn't in the heap"
+            );
+        }
+        assert_eq!(self.heap.len(), self.model.len(), "wrong len");
+        if let Err(err) = self.heap.check_invariants() {
+            panic!("{err}");
+        }
+    }
+
+    /// Checks the item taken out of the heap is in the model with the expected key, and removes
+    /// it from the model
+    fn remove(
+        &mut self,
+        item: Option<H::Item>,
+        expected: Option<(u32, u32)>,
+    ) -> Option<(u32, u32)> {
+        let item = item.map(|item| (item.key(), item.id()));
+        assert_eq!(
+            item.map(|item| item.0),
+            expected.map(|expected| expected.0),
+            "took out {item:?} instead of {expected:?}"
+        );
+        if let Some(item) = item {
+            assert!(
+                self.model.remove(&item),
+                "took out {item:?} which isn't in the heap"
+            );
+        }
+        item
+    }
+
+    fn extract_min(&mut self) {
+        let item = self.heap.extract_min();
+        let expected = self.model.first().copied();
+        if let Some((key, _)) = self.remove(item, expected) {
+            self.floor = self.floor.max(key);
+        }
+    }
+
+    fn apply_min(&mut self, op: Op) {
+        self.apply(op, Self::apply_min);
+    }
+
+    /// Runs an operation of [`MinHeap`], running the operations of a sub-heap with `step`
+    fn apply(&mut self, op: Op, step: fn(&mut Self, Op)) {
+        match op {
+            Op::Insert(key) => {
+                let (key, id) = self.item(key);
+                self.heap.insert(H::Item::new(key, id));
+                self.model.insert((key, id));
+            }
+            Op::ExtractMin => self.extract_min(),
+            Op::Meld {
+                ops,
+                sub_heap_first,
+            } => {
+                let mut sub = Self::new(self.limits, self.floor, self.next_id);
+                for op in ops {
+                    step(&mut sub, op);
+                    sub.check();
+                }
+                self.next_id = sub.next_id;
+                self.floor = self.floor.max(sub.floor);
+                self.model.append(&mut sub.model);
+                self.handles.append(&mut sub.handles);
+
+                let heap = mem::take(&mut self.heap);
+                self.heap = if sub_heap_first {
+                    H::meld(sub.heap, heap)
+                } else {
+                    H::meld(heap, sub.heap)
+                };
+            }
+            Op::Heapify(keys) => {
+                let items: Vec<_> = keys.into_iter().map(|key| self.item(key)).collect();
+                self.heap = H::heapify(
+                    items
+                        .iter()
+                        .map(|&(key, id)| H::Item::new(key, id))
+                        .collect(),
+                );
+                self.model = items.into_iter().collect();
+            }
+            Op::Clear => {
+                self.heap.clear();
+                self.model.clear();
+            }
+            op => unreachable!("{op:?} isn't an operation of MinHeap"),
+        }
+    }
+
+    /// Everything left must come out in order
+    fn finish(mut self) {
+        while !self.model.is_empty() {
+            self.extract_min();
+            self.check();
+        }
+        assert!(
+            self.heap.extract_min().is_none(),
+            "extracted from an empty heap"
+        );
+    }
+}
+
+impl<H: AddressableHeap<Item: FuzzItem> + CheckInvariants + Default> Fuzzer<H> {
+    /// Handle picked by index among every handle given out, and whether its item is in the heap
+    fn handle(&self, index: u8) -> Option<(usize, Handle, (u32, u32), bool)> {
+        if self.handles.is_empty() {
+            return None;
+        }
+        let index = usize::from(index) % self.handles.len();
+        let (handle, item) = self.handles[index];
+        Some((index, handle, item, self.model.contains(&item)))
+    }
+
+    fn apply_addressable(&mut self, op: Op) {
+        match op {
+            Op::InsertWithHandle(key) => {
+                let (key, id) = self.item(key);
+                let handle = self.heap.insert_with_handle(H::Item::new(key, id));
+                self.model.insert((key, id));
+                self.handles.push((handle, (key, id)));
+            }
+            Op::Get(handle) => {
+                if let Some((_, handle, item, in_heap)) = self.handle(handle) {
+                    let got = self.heap.get(handle).map(|item| (item.key(), item.id()));
+                    assert_eq!(got, in_heap.then_some(item), "get of {handle:?}");
+                }
+            }
+            Op::DecreaseKey {
+                handle,
+                by,
+                increase,
+            } => {
+                if let Some((index, handle, (key, id), in_heap)) = self.handle(handle) {
+                    let new_key = if increase {
+                        key.saturating_add(by.max(1))
+                    } else {
+                        key.saturating_sub(by)
+                    };
+                    let result = self.heap.decrease_key(handle, H::Item::new(new_key, id));
+                    let expected = if !in_heap {
+                        Err(HandleError::InvalidHandle)
+                    } else if new_key > key {
+                        Err(HandleError::KeyIncreased)
+                    } else {
+                        Ok(())
+                    };
+                    assert_eq!(result, expected, "decrease_key of {handle:?} to {new_key}");
+                    if result.is_ok() {
+                        self.model.remove(&(key, id));
+                        self.model.insert((new_key, id));
+                        self.handles[index].1 = (new_key, id);
+                    }
+                }
+            }
+            Op::Delete(handle) => {
+                if let Some((_, handle, item, in_heap)) = self.handle(handle) {
+                    let result = self.heap.delete(handle);
+                    if in_heap {
+                        let deleted = result.map(|item| (item.key(), item.id()));
+                        assert_eq!(deleted, Ok(item), "delete of {handle:?}");
+                        self.model.remove(&item);
+                    } else {
+                        assert_eq!(result.err(), Some(HandleError::InvalidHandle));
+                    }
+                }
+            }
+            op => self.apply(op, Self::apply_addressable),
+        }
+    }
+}
+
+impl<H: DoubleEndedHeap<Item: FuzzItem> + CheckInvariants + Default> Fuzzer<H> {
+    fn check_max(&self) {
+        let max = self.heap.peek_max().map(FuzzItem::key);
+        let expected = self.model.last().map(|expected| expected.0);
+        assert_eq!(max, expected, "peek_max gave the wrong key");
+    }
+
+    fn apply_double_ended(&mut self, op: Op) {
+        match op {
+            Op::ExtractMax => {
+                let item = self.heap.extract_max();
+                let expected = self.model.last().copied();
+                self.remove(item, expected);
+            }
+            Op::PushPopMax(key) => {
+                let (key, id) = self.item(key);
+                let item = self.heap.push_pop_max(H::Item::new(key, id));
+                self.model.insert((key, id));
+                let expected = self.model.last().copied();
+                self.remove(Some(item), expected);
+            }
+            op => self.apply(op, Self::apply_double_ended),
+        }
+    }
+}
+
+/// Runs the operations of [`MinHeap`] decoded from the bytes
+pub fn fuzz_min_heap<H>(data: &[u8], limits: Limits)
+where
+    H: MinHeap<Item: FuzzItem> + CheckInvariants + Default,
+{
+    let mut fuzzer = Fuzzer::<H>::new(limits, 0, 0);
+    for op in Input(data).ops(Kind::Min) {
+        fuzzer.apply_min(op);
+        fuzzer.check();
+    }
+    fuzzer.finish();
+}
+
+/// Runs the operations of [`AddressableHeap`] decoded from the bytes, including ones on handles
+/// of items that already left the heap
+pub fn fuzz_addressable_heap<H>(data: &[u8], limits: Limits)
+where
+    H: AddressableHeap<Item: FuzzItem> + CheckInvariants + Default,
+{
+    let mut fuzzer = Fuzzer::<H>::new(limits, 0, 0);
+    for op in Input(data).ops(Kind::Addressable) {
+        fuzzer.apply_addressable(op);
+        fuzzer.check();
+    }
+    fuzzer.finish();
+}
+
+/// Runs the operations of [`DoubleEndedHeap`] decoded from the bytes
+pub fn fuzz_double_ended_heap<H>(data: &[u8], limits: Limits)
+where
+    H: DoubleEndedHeap<Item: FuzzItem> + CheckInvariants + Default,
+{
+    let mut fuzzer = Fuzzer::<H>::new(limits, 0, 0);
+    for op in Input(data).ops(Kind::DoubleEnded) {
+        fuzzer.apply_double_ended(op);
+        fuzzer.check();
+        fuzzer.check_max();
+    }
+    fuzzer.finish();
+}
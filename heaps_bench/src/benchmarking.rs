@@ -1,10 +1,113 @@
-use std::time::{Duration, Instant};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap as StdBinaryHeap,
+    thread,
+    time::{Duration, Instant},
+};
 
-use heaps::{BinaryHeap, BinomialHeap, Item, LazyBinomialHeap, MinHeap};
+use heaps::{
+    ArenaBinomialHeap, ArenaLazyBinomialHeap, BinaryHeap, BinomialHeap, BoundedHeap, BucketQueue,
+    Compare, CountingOrder, DaryHeap, FibonacciHeap, FrontToBack, Item, LazyBinomialHeap,
+    LeftistHeap, LockedHeap, MinHeap, MinMaxHeap, MinOrder, MultiQueue, Multipass, PairingHeap,
+    RadixHeap, RadixKey, RankPairingHeap, SkewHeap, Stats, TwoPass, Type2,
+};
 
 use crate::{ArrayMode, BenchElemType};
 
-fn heap_sort<I, T: MinHeap<Item = I>>(items: Vec<I>) -> Vec<I> {
+/// Numbers of children per node that d-ary heaps can be benchmarked with
+pub const DARY_ARITIES: [usize; 4] = [3, 4, 8, 16];
+
+/// Which heap implementations to benchmark
+#[derive(Clone)]
+pub struct HeapSelection {
+    pub binary: bool,
+    /// Arity of each d-ary heap to benchmark
+    pub dary: Vec<usize>,
+    pub binomial: bool,
+    pub lazy: bool,
+    pub arena_binomial: bool,
+    pub arena_lazy: bool,
+    pub fibonacci: bool,
+    pub pairing: bool,
+    pub pairing_front_to_back: bool,
+    pub pairing_multipass: bool,
+    pub leftist: bool,
+    pub skew: bool,
+    pub rank_pairing: bool,
+    pub min_max: bool,
+    /// Only benchmarked in the monotone workload
+    pub radix: bool,
+    /// Only benchmarked with element types that have a bucket priority
+    pub bucket: bool,
+    /// Only benchmarked in the top-k workload
+    pub bounded: bool,
+    /// Only benchmarked in the concurrent workload
+    pub locked: bool,
+    /// Only benchmarked in the concurrent workload
+    pub multi_queue: bool,
+}
+
+impl HeapSelection {
+    pub fn all() -> Self {
+        Self {
+            binary: true,
+            dary: DARY_ARITIES.to_vec(),
+            binomial: true,
+            lazy: true,
+            arena_binomial: true,
+            arena_lazy: true,
+            fibonacci: true,
+            pairing: true,
+            pairing_front_to_back: true,
+            pairing_multipass: true,
+            leftist: true,
+            skew: true,
+            rank_pairing: true,
+            min_max: true,
+            radix: true,
+            bucket: true,
+            bounded: true,
+            locked: true,
+            multi_queue: true,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        !(self.binary
+            || !self.dary.is_empty()
+            || self.binomial
+            || self.lazy
+            || self.arena_binomial
+            || self.arena_lazy
+            || self.fibonacci
+            || self.pairing
+            || self.pairing_front_to_back
+            || self.pairing_multipass
+            || self.leftist
+            || self.skew
+            || self.rank_pairing
+            || self.min_max
+            || self.radix
+            || self.bucket
+            || self.bounded
+            || self.locked
+            || self.multi_queue)
+    }
+}
+
+/// Something to time each selected heap on
+trait Workload<T> {
+    /// Runs the workload on an `H`, checking its results
+    fn run<H: MinHeap<Item = T> + Default>(&self) -> Duration;
+    /// Number of heap operations a run does, counting each item heapified as one
+    fn operations(&self) -> usize;
+}
+
+struct SortWorkload<'a, T> {
+    array: &'a [T],
+    sorted: &'a [T],
+}
+
+fn heap_sort<I, T: MinHeap<Item = I> + Default>(items: Vec<I>) -> Vec<I> {
     let length = items.len();
     let mut heap = T::heapify(items);
     let mut sorted = Vec::with_capacity(length);
@@ -14,21 +117,402 @@ fn heap_sort<I, T: MinHeap<Item = I>>(items: Vec<I>) -> Vec<I> {
     sorted
 }
 
-fn benchmark_sort<T: Item + Clone, H: MinHeap<Item = T>>(array: &[T], sorted: &[T]) -> Duration {
-    let array = array.to_vec();
+impl<T: Item + Clone> Workload<T> for SortWorkload<'_, T> {
+    fn run<H: MinHeap<Item = T> + Default>(&self) -> Duration {
+        let array = self.array.to_vec();
+        let before = Instant::now();
+        let sorted_by_heap = heap_sort::<T, H>(array);
+        let duration = before.elapsed();
+        assert!(sorted_by_heap == self.sorted);
+        duration
+    }
+    fn operations(&self) -> usize {
+        2 * self.array.len()
+    }
+}
+
+struct TopKWorkload<'a, T> {
+    array: &'a [T],
+    /// Smallest k items of the array in order
+    top: &'a [T],
+}
+
+impl<T: Item + Clone> Workload<T> for TopKWorkload<'_, T> {
+    fn run<H: MinHeap<Item = T> + Default>(&self) -> Duration {
+        let before = Instant::now();
+        let mut heap = H::default();
+        for item in self.array {
+            heap.insert(item.clone());
+        }
+        let top: Vec<_> = heap.drain_sorted().take(self.top.len()).collect();
+        let duration = before.elapsed();
+        assert!(top == self.top);
+        duration
+    }
+    fn operations(&self) -> usize {
+        self.array.len() + self.top.len()
+    }
+}
+
+impl<T: Item + Clone> TopKWorkload<'_, T> {
+    fn run_bounded<C: Compare<T> + Default>(&self) -> Duration {
+        let before = Instant::now();
+        let mut heap = BoundedHeap::with_comparator(self.top.len(), C::default());
+        heap.extend(self.array.iter().cloned());
+        let top = heap.into_sorted_vec();
+        let duration = before.elapsed();
+        assert!(top == self.top);
+        duration
+    }
+}
+
+/// Priority queue shared by the threads of the concurrent workload
+trait ConcurrentQueue<T>: Sync {
+    fn with_threads(threads: usize) -> Self;
+    fn insert(&self, item: T);
+    fn extract_min(&self) -> Option<T>;
+    fn into_sorted_vec(self) -> Vec<T>;
+}
+
+impl<T: Item + Send> ConcurrentQueue<T> for LockedHeap<BinaryHeap<T>> {
+    fn with_threads(_threads: usize) -> Self {
+        Self::default()
+    }
+    fn insert(&self, item: T) {
+        LockedHeap::insert(self, item);
+    }
+    fn extract_min(&self) -> Option<T> {
+        LockedHeap::extract_min(self)
+    }
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_inner().into_sorted_vec()
+    }
+}
+
+impl<T: Item + Send> ConcurrentQueue<T> for MultiQueue<T> {
+    /// Two heaps per thread, so threads rarely pick a locked one
+    fn with_threads(threads: usize) -> Self {
+        Self::new(2 * threads)
+    }
+    fn insert(&self, item: T) {
+        MultiQueue::insert(self, item);
+    }
+    fn extract_min(&self) -> Option<T> {
+        MultiQueue::extract_min(self)
+    }
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_heap().into_sorted_vec()
+    }
+}
+
+/// Fills the queue with the array, then splits the array between the threads, which insert their
+/// items again and extract an item after each insertion. Only the threads are timed, and the
+/// items extracted plus the ones left must be the array twice
+fn concurrent_run<T: Item + Clone + Send + Sync, Q: ConcurrentQueue<T>>(
+    array: &[T],
+    threads: usize,
+    sorted_twice: &[T],
+) -> Duration {
+    let queue = Q::with_threads(threads);
+    for item in array {
+        queue.insert(item.clone());
+    }
+    let chunk_len = array.len().div_ceil(threads).max(1);
+
     let before = Instant::now();
-    let sorted_by_heap = heap_sort::<T, H>(array);
+    let extracted: Vec<Vec<T>> = thread::scope(|scope| {
+        let workers: Vec<_> = array
+            .chunks(chunk_len)
+            .map(|chunk| {
+                let queue = &queue;
+                scope.spawn(move || {
+                    let mut extracted = Vec::with_capacity(chunk.len());
+                    for item in chunk {
+                        queue.insert(item.clone());
+                        extracted.extend(queue.extract_min());
+                    }
+                    extracted
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect()
+    });
     let duration = before.elapsed();
-    assert!(sorted_by_heap == sorted);
+
+    let mut items: Vec<T> = extracted.into_iter().flatten().collect();
+    items.extend(queue.into_sorted_vec());
+    items.sort_unstable();
+    assert!(items == sorted_twice);
     duration
 }
 
+/// Keys that can go through the monotone workload
+pub trait MonotoneKey: BenchElemType + RadixKey {
+    /// Adds a non-negative weight taken from the high half of the bits of `seed`, saturating at
+    /// the max key so the result is never smaller
+    fn add_weight(self, seed: Self) -> Self;
+}
+
+struct MonotoneWorkload<'a, T> {
+    array: &'a [T],
+    /// Keys in the order they must be extracted
+    extracted: &'a [T],
+}
+
+/// Inserts the array, then extracts the min and reinserts it with a weight added once per array
+/// item, like Dijkstra's algorithm relaxing edges, and extracts what is left. Returns the
+/// extracted keys in order
+fn monotone_run<T: MonotoneKey, H: Default>(
+    array: &[T],
+    insert: impl Fn(&mut H, T),
+    extract_min: impl Fn(&mut H) -> Option<T>,
+) -> Vec<T> {
+    let mut heap = H::default();
+    let mut extracted = Vec::with_capacity(2 * array.len());
+    for &key in array {
+        insert(&mut heap, key);
+    }
+    for &seed in array {
+        let key = extract_min(&mut heap).unwrap();
+        extracted.push(key);
+        insert(&mut heap, key.add_weight(seed));
+    }
+    while let Some(key) = extract_min(&mut heap) {
+        extracted.push(key);
+    }
+    extracted
+}
+
+impl<T: MonotoneKey> MonotoneWorkload<'_, T> {
+    fn run_with<H: Default>(
+        &self,
+        insert: impl Fn(&mut H, T),
+        extract_min: impl Fn(&mut H) -> Option<T>,
+    ) -> Duration {
+        let before = Instant::now();
+        let extracted = monotone_run(self.array, insert, extract_min);
+        let duration = before.elapsed();
+        assert!(extracted == self.extracted);
+        duration
+    }
+}
+
+impl<T: MonotoneKey> Workload<T> for MonotoneWorkload<'_, T> {
+    fn run<H: MinHeap<Item = T> + Default>(&self) -> Duration {
+        self.run_with(H::insert, H::extract_min)
+    }
+    /// Every item is inserted and extracted twice
+    fn operations(&self) -> usize {
+        4 * self.array.len()
+    }
+}
+
+/// [`BucketQueue`] holding each element with its bucket priority, so it can run the workloads
+/// like the other heaps
+struct BucketQueueHeap<T>(BucketQueue<T>);
+
+impl<T> Default for BucketQueueHeap<T> {
+    fn default() -> Self {
+        Self(BucketQueue::new())
+    }
+}
+
+impl<T: BenchElemType> MinHeap for BucketQueueHeap<T> {
+    type Item = T;
+
+    fn peek_min(&self) -> Option<&T> {
+        self.0.peek_min().map(|(_, item)| item)
+    }
+    fn extract_min(&mut self) -> Option<T> {
+        self.0.extract_min().map(|(_, item)| item)
+    }
+    fn insert(&mut self, item: T) {
+        let priority = item
+            .bucket_priority()
+            .expect("element has no bucket priority");
+        self.0.insert((priority, item));
+    }
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+    fn heapify(items: Vec<T>) -> Self {
+        let mut heap = Self::default();
+        for item in items {
+            heap.insert(item);
+        }
+        heap
+    }
+    fn meld(heap_a: Self, heap_b: Self) -> Self {
+        Self(BucketQueue::meld(heap_a.0, heap_b.0))
+    }
+}
+
+/// Runs the bucket queue on the workload if it is selected and the elements have bucket
+/// priorities
+fn benchmark_bucket_queue<T: BenchElemType>(
+    heaps: &HeapSelection,
+    array: &[T],
+    workload: &impl Workload<T>,
+) {
+    if heaps.bucket && array.iter().all(|item| item.bucket_priority().is_some()) {
+        let duration = workload.run::<BucketQueueHeap<T>>();
+        println!("  Bucket Queue: duration={}s", duration.as_secs_f64());
+    }
+}
+
+/// Formats the work counted on a run as averages per operation of the workload. The heaps only
+/// count links, swaps and allocations with the `stats` feature, so without it only the
+/// comparisons are shown
+fn per_operation(stats: Stats, operations: usize) -> String {
+    let per_operation = |count: u64| count as f64 / operations.max(1) as f64;
+    let mut work = format!(" comparisons/op={:.2}", per_operation(stats.comparisons));
+    if cfg!(feature = "stats") {
+        work += &format!(
+            " links/op={:.2} swaps/op={:.2} allocations/op={:.2}",
+            per_operation(stats.links),
+            per_operation(stats.swaps),
+            per_operation(stats.allocations),
+        );
+    }
+    work
+}
+
+/// Runs the workload on `H` and prints how long it took. With `count`, also runs it untimed on
+/// `Counted`, the same heap with a [`CountingOrder`], and prints the work it did per operation
+fn benchmark_heap<T, H, Counted>(name: &str, workload: &impl Workload<T>, count: bool)
+where
+    H: MinHeap<Item = T> + Default,
+    Counted: MinHeap<Item = T> + Default,
+{
+    let duration = workload.run::<H>();
+    let work = if count {
+        Stats::take();
+        workload.run::<Counted>();
+        per_operation(Stats::take(), workload.operations())
+    } else {
+        String::new()
+    };
+    println!("  {name}: duration={}s{work}", duration.as_secs_f64());
+}
+
+/// Runs the workload on every selected heap, printing how long each took and, with `count`, the
+/// work each did per operation
+fn benchmark_heaps<T: Item>(heaps: &HeapSelection, workload: &impl Workload<T>, count: bool) {
+    type Counting = CountingOrder;
+    if heaps.binary {
+        benchmark_heap::<T, BinaryHeap<T>, BinaryHeap<T, Counting>>("Binary Heap", workload, count);
+    }
+    for &arity in &heaps.dary {
+        let name = format!("{arity}-ary Heap");
+        match arity {
+            3 => benchmark_heap::<T, DaryHeap<T, 3>, DaryHeap<T, 3, Counting>>(
+                &name, workload, count,
+            ),
+            4 => benchmark_heap::<T, DaryHeap<T, 4>, DaryHeap<T, 4, Counting>>(
+                &name, workload, count,
+            ),
+            8 => benchmark_heap::<T, DaryHeap<T, 8>, DaryHeap<T, 8, Counting>>(
+                &name, workload, count,
+            ),
+            16 => benchmark_heap::<T, DaryHeap<T, 16>, DaryHeap<T, 16, Counting>>(
+                &name, workload, count,
+            ),
+            _ => unreachable!("arity {arity} is not in DARY_ARITIES"),
+        }
+    }
+    if heaps.binomial {
+        benchmark_heap::<T, BinomialHeap<T>, BinomialHeap<T, Counting>>(
+            "Binomial Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.lazy {
+        benchmark_heap::<T, LazyBinomialHeap<T>, LazyBinomialHeap<T, Counting>>(
+            "Lazy One-Pass Binomial Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.arena_binomial {
+        benchmark_heap::<T, ArenaBinomialHeap<T>, ArenaBinomialHeap<T, Counting>>(
+            "Arena Binomial Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.arena_lazy {
+        benchmark_heap::<T, ArenaLazyBinomialHeap<T>, ArenaLazyBinomialHeap<T, Counting>>(
+            "Arena Lazy One-Pass Binomial Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.fibonacci {
+        benchmark_heap::<T, FibonacciHeap<T>, FibonacciHeap<T, Counting>>(
+            "Fibonacci Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.pairing {
+        benchmark_heap::<T, PairingHeap<T, TwoPass>, PairingHeap<T, TwoPass, Counting>>(
+            "Two-Pass Pairing Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.pairing_front_to_back {
+        benchmark_heap::<T, PairingHeap<T, FrontToBack>, PairingHeap<T, FrontToBack, Counting>>(
+            "Front-to-Back Pairing Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.pairing_multipass {
+        benchmark_heap::<T, PairingHeap<T, Multipass>, PairingHeap<T, Multipass, Counting>>(
+            "Multipass Pairing Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.leftist {
+        benchmark_heap::<T, LeftistHeap<T>, LeftistHeap<T, Counting>>(
+            "Leftist Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.skew {
+        benchmark_heap::<T, SkewHeap<T>, SkewHeap<T, Counting>>("Skew Heap", workload, count);
+    }
+    if heaps.rank_pairing {
+        benchmark_heap::<T, RankPairingHeap<T>, RankPairingHeap<T, Type2, Counting>>(
+            "Rank-Pairing Heap",
+            workload,
+            count,
+        );
+    }
+    if heaps.min_max {
+        benchmark_heap::<T, MinMaxHeap<T>, MinMaxHeap<T, Counting>>(
+            "Min-Max Heap",
+            workload,
+            count,
+        );
+    }
+}
+
 pub fn benchmark_heaps_sort<T: Item + Clone + BenchElemType>(
     mode: ArrayMode,
     len: usize,
-    binary: bool,
-    binomial: bool,
-    lazy: bool,
+    heaps: &HeapSelection,
+    count: bool,
 ) {
     eprintln!("Generating {:?} array length {}", mode, len);
     let before = Instant::now();
@@ -52,19 +536,162 @@ pub fn benchmark_heaps_sort<T: Item + Clone + BenchElemType>(
         size_of::<T>(),
     );
 
-    if binary {
-        let duration = benchmark_sort::<T, BinaryHeap<T>>(&array, &sorted);
-        println!("  Binary Heap: duration={}s", duration.as_secs_f64());
+    let workload = SortWorkload {
+        array: &array,
+        sorted: &sorted,
+    };
+    benchmark_heaps(heaps, &workload, count);
+    benchmark_bucket_queue(heaps, &array, &workload);
+}
+
+pub fn benchmark_heaps_monotone<T: MonotoneKey>(
+    mode: ArrayMode,
+    len: usize,
+    heaps: &HeapSelection,
+    count: bool,
+) {
+    eprintln!("Generating {:?} array length {}", mode, len);
+    let before = Instant::now();
+    let array = T::build_array_with_mode(len, mode);
+    let duration = before.elapsed();
+    eprintln!("Generated array in {:?}", duration);
+
+    eprintln!(
+        "Running monotone workload on {:?} array length {} with std",
+        mode, len
+    );
+    let before = Instant::now();
+    let extracted = monotone_run(
+        &array,
+        |heap: &mut StdBinaryHeap<Reverse<T>>, key| heap.push(Reverse(key)),
+        |heap| heap.pop().map(|Reverse(key)| key),
+    );
+    let duration = before.elapsed();
+    eprintln!(
+        "Ran monotone workload on {:?} array length {} in {:?}",
+        mode, len, duration
+    );
+    eprintln!();
+
+    println!(
+        "Monotone {:?} array: n={} elem_size={}",
+        mode,
+        len,
+        size_of::<T>(),
+    );
+
+    let workload = MonotoneWorkload {
+        array: &array,
+        extracted: &extracted,
+    };
+    benchmark_heaps(heaps, &workload, count);
+    if heaps.radix {
+        let duration = workload.run_with(
+            |heap: &mut RadixHeap<T>, key| heap.insert((key, ())),
+            |heap| heap.extract_min().map(|(key, ())| key),
+        );
+        println!("  Radix Heap: duration={}s", duration.as_secs_f64());
     }
-    if binomial {
-        let duration = benchmark_sort::<T, BinomialHeap<T>>(&array, &sorted);
-        println!("  Binomial Heap: duration={}s", duration.as_secs_f64());
+    benchmark_bucket_queue(heaps, &array, &workload);
+}
+
+pub fn benchmark_heaps_top_k<T: Item + Clone + BenchElemType>(
+    mode: ArrayMode,
+    len: usize,
+    k: usize,
+    heaps: &HeapSelection,
+    count: bool,
+) {
+    eprintln!("Generating {:?} array length {}", mode, len);
+    let before = Instant::now();
+    let array = T::build_array_with_mode(len, mode);
+    let duration = before.elapsed();
+    eprintln!("Generated array in {:?}", duration);
+
+    let mut top = array.clone();
+
+    eprintln!("Sorting {:?} array length {} with std", mode, len);
+    let before = Instant::now();
+    top.sort_unstable();
+    top.truncate(k);
+    let duration = before.elapsed();
+    eprintln!("Sorted {:?} array length {} in {:?}", mode, len, duration);
+    eprintln!();
+
+    println!(
+        "Top {} of {:?} array: n={} elem_size={}",
+        k,
+        mode,
+        len,
+        size_of::<T>(),
+    );
+
+    let workload = TopKWorkload {
+        array: &array,
+        top: &top,
+    };
+    benchmark_heaps(heaps, &workload, count);
+    benchmark_bucket_queue(heaps, &array, &workload);
+    if heaps.bounded {
+        let duration = workload.run_bounded::<MinOrder>();
+        let work = if count {
+            Stats::take();
+            workload.run_bounded::<CountingOrder>();
+            per_operation(Stats::take(), workload.operations())
+        } else {
+            String::new()
+        };
+        println!("  Bounded Heap: duration={}s{work}", duration.as_secs_f64());
     }
-    if lazy {
-        let duration = benchmark_sort::<T, LazyBinomialHeap<T>>(&array, &sorted);
+}
+
+pub fn benchmark_heaps_concurrent<T: Item + Clone + Send + Sync + BenchElemType>(
+    mode: ArrayMode,
+    len: usize,
+    threads: &[usize],
+    heaps: &HeapSelection,
+) {
+    eprintln!("Generating {:?} array length {}", mode, len);
+    let before = Instant::now();
+    let array = T::build_array_with_mode(len, mode);
+    let duration = before.elapsed();
+    eprintln!("Generated array in {:?}", duration);
+
+    let mut sorted_twice = array.clone();
+    sorted_twice.extend_from_slice(&array);
+
+    eprintln!("Sorting {:?} array length {} twice with std", mode, len);
+    let before = Instant::now();
+    sorted_twice.sort_unstable();
+    let duration = before.elapsed();
+    eprintln!("Sorted {:?} array length {} in {:?}", mode, len, duration);
+    eprintln!();
+
+    println!(
+        "Concurrent {:?} array: n={} elem_size={}",
+        mode,
+        len,
+        size_of::<T>(),
+    );
+
+    // Each item of the array is inserted and an item extracted once by the threads
+    let operations = 2 * len;
+    let print = |name: &str, threads: usize, duration: Duration| {
         println!(
-            "  Lazy One-Pass Binomial Heap: duration={}s",
-            duration.as_secs_f64()
+            "  {name}: threads={threads} duration={}s throughput={:.0}ops/s",
+            duration.as_secs_f64(),
+            operations as f64 / duration.as_secs_f64()
         );
+    };
+    for &threads in threads {
+        if heaps.locked {
+            let duration =
+                concurrent_run::<T, LockedHeap<BinaryHeap<T>>>(&array, threads, &sorted_twice);
+            print("Locked Binary Heap", threads, duration);
+        }
+        if heaps.multi_queue {
+            let duration = concurrent_run::<T, MultiQueue<T>>(&array, threads, &sorted_twice);
+            print("MultiQueue", threads, duration);
+        }
     }
 }
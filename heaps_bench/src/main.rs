@@ -1,9 +1,12 @@
 mod benchmarking;
 mod byte_array;
 
-use benchmarking::benchmark_heaps_sort;
+use benchmarking::{
+    benchmark_heaps_concurrent, benchmark_heaps_monotone, benchmark_heaps_sort,
+    benchmark_heaps_top_k, HeapSelection, MonotoneKey, DARY_ARITIES,
+};
 use byte_array::ByteArray;
-use clap::{Parser, ValueEnum};
+use clap::{error::ErrorKind, CommandFactory, Parser, ValueEnum};
 use rand::{thread_rng, Rng};
 
 #[derive(Parser)]
@@ -20,18 +23,85 @@ struct Cli {
     #[arg(short, long)]
     mode: ArrayMode,
 
+    /// What the heaps are timed on
+    #[arg(short, long, default_value = "sort")]
+    workload: Workload,
+
+    /// Number of smallest elements to keep in the top-k workload
+    #[arg(short, default_value_t = 100)]
+    k: usize,
+
+    /// Numbers of threads to run the concurrent workload with
+    #[arg(long, num_args = 1.., default_values_t = [1, 2, 4, 8])]
+    threads: Vec<usize>,
+
+    /// Run each heap once more with a comparator that counts comparisons, and report the
+    /// comparisons it did per operation, plus the links, swaps and node allocations when built
+    /// with the `stats` feature. Not supported by the radix heap, bucket queue and concurrent
+    /// workload
+    #[arg(long)]
+    count: bool,
+
     /// Benchmark all heap implementations
     #[arg(short, long)]
     all: bool,
     /// Benchmark binary heap implementation
     #[arg(long)]
     binary: bool,
+    /// Benchmark d-ary heap implementation with each of these numbers of children per node
+    #[arg(long, num_args = 1.., value_parser = parse_arity)]
+    dary: Vec<usize>,
     /// Benchmark binomial heap implementation
     #[arg(long)]
     binomial: bool,
     /// Benchmark lazy one-pass binomial heap implementation
     #[arg(long)]
     lazy: bool,
+    /// Benchmark arena-backed binomial heap implementation
+    #[arg(long)]
+    arena_binomial: bool,
+    /// Benchmark arena-backed lazy one-pass binomial heap implementation
+    #[arg(long)]
+    arena_lazy: bool,
+    /// Benchmark Fibonacci heap implementation
+    #[arg(long)]
+    fibonacci: bool,
+    /// Benchmark two-pass pairing heap implementation
+    #[arg(long)]
+    pairing: bool,
+    /// Benchmark front-to-back pairing heap implementation
+    #[arg(long)]
+    pairing_front_to_back: bool,
+    /// Benchmark multipass pairing heap implementation
+    #[arg(long)]
+    pairing_multipass: bool,
+    /// Benchmark leftist heap implementation
+    #[arg(long)]
+    leftist: bool,
+    /// Benchmark skew heap implementation
+    #[arg(long)]
+    skew: bool,
+    /// Benchmark type-2 rank-pairing heap implementation
+    #[arg(long)]
+    rank_pairing: bool,
+    /// Benchmark min-max heap implementation
+    #[arg(long)]
+    min_max: bool,
+    /// Benchmark radix heap implementation, only with the monotone workload
+    #[arg(long)]
+    radix: bool,
+    /// Benchmark bucket queue implementation, only with u8 elements
+    #[arg(long)]
+    bucket: bool,
+    /// Benchmark bounded heap implementation, only with the top-k workload
+    #[arg(long)]
+    bounded: bool,
+    /// Benchmark binary heap behind a lock, only with the concurrent workload
+    #[arg(long)]
+    locked: bool,
+    /// Benchmark multiqueue implementation, only with the concurrent workload
+    #[arg(long)]
+    multi_queue: bool,
 }
 
 #[derive(Clone, Copy, ValueEnum)]
@@ -47,6 +117,21 @@ enum ArrayElemType {
     Bigger,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum Workload {
+    /// Heapsort the array
+    Sort,
+    /// Insert the array, then keep extracting the min and reinserting it with a non-negative
+    /// weight added, like Dijkstra's algorithm. Needs unsigned integer elements
+    Monotone,
+    /// Find the k smallest elements of the array, by inserting them all and extracting k, or by
+    /// streaming them through a bounded heap
+    TopK,
+    /// Fill a queue shared by all threads with the array, then have the threads insert the
+    /// array again between them, extracting an item after each insertion
+    Concurrent,
+}
+
 #[derive(Clone, Copy, ValueEnum, Debug)]
 enum ArrayMode {
     Random,
@@ -60,6 +145,11 @@ trait BenchElemType: heaps::Item + Sized {
     fn increasing_iter(len: usize) -> Vec<Self>;
     fn decreasing_iter(len: usize) -> Vec<Self>;
     fn random_iter(len: usize) -> Vec<Self>;
+    /// Priority of the element in a bucket queue with one bucket per value, for types with few
+    /// enough values
+    fn bucket_priority(&self) -> Option<usize> {
+        None
+    }
     fn build_array_with_mode(len: usize, mode: ArrayMode) -> Vec<Self> {
         match mode {
             ArrayMode::Random => Self::random_iter(len),
@@ -70,42 +160,208 @@ trait BenchElemType: heaps::Item + Sized {
     }
 }
 
+fn parse_arity(arg: &str) -> Result<usize, String> {
+    let arity = arg.parse().map_err(|err| format!("{err}"))?;
+    if DARY_ARITIES.contains(&arity) {
+        Ok(arity)
+    } else {
+        Err(format!("supported arities are {DARY_ARITIES:?}"))
+    }
+}
+
 fn main() {
     let options = Cli::parse();
-    let mut binary = options.binary;
-    let mut binomial = options.binomial;
-    let mut lazy = options.lazy;
-    if options.all || (!binary && !binomial && !lazy) {
-        binary = true;
-        binomial = true;
-        lazy = true;
+    let mut heaps = HeapSelection {
+        binary: options.binary,
+        dary: options.dary,
+        binomial: options.binomial,
+        lazy: options.lazy,
+        arena_binomial: options.arena_binomial,
+        arena_lazy: options.arena_lazy,
+        fibonacci: options.fibonacci,
+        pairing: options.pairing,
+        pairing_front_to_back: options.pairing_front_to_back,
+        pairing_multipass: options.pairing_multipass,
+        leftist: options.leftist,
+        skew: options.skew,
+        rank_pairing: options.rank_pairing,
+        min_max: options.min_max,
+        radix: options.radix,
+        bucket: options.bucket,
+        bounded: options.bounded,
+        locked: options.locked,
+        multi_queue: options.multi_queue,
+    };
+    if options.all || heaps.is_empty() {
+        heaps = HeapSelection::all();
+    }
+
+    if let Workload::Monotone = options.workload {
+        if let ArrayElemType::Big | ArrayElemType::Bigger = options.size {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "the monotone workload needs unsigned integer elements",
+                )
+                .exit();
+        }
     }
 
     for len in options.n {
-        match options.size {
-            ArrayElemType::U8 => {
-                benchmark_heaps_sort::<u8>(options.mode, len, binary, binomial, lazy);
+        match options.workload {
+            Workload::Sort => {
+                benchmark_sort_with_size(options.size, options.mode, len, &heaps, options.count)
             }
-            ArrayElemType::U16 => {
-                benchmark_heaps_sort::<u16>(options.mode, len, binary, binomial, lazy);
-            }
-            ArrayElemType::U32 => {
-                benchmark_heaps_sort::<u32>(options.mode, len, binary, binomial, lazy);
-            }
-            ArrayElemType::U64 => {
-                benchmark_heaps_sort::<u64>(options.mode, len, binary, binomial, lazy);
-            }
-            ArrayElemType::U128 => {
-                benchmark_heaps_sort::<u128>(options.mode, len, binary, binomial, lazy);
-            }
-            ArrayElemType::Big => {
-                type T = ByteArray<1024>;
-                benchmark_heaps_sort::<T>(options.mode, len, binary, binomial, lazy);
-            }
-            ArrayElemType::Bigger => {
-                type T = ByteArray<10240>;
-                benchmark_heaps_sort::<T>(options.mode, len, binary, binomial, lazy);
+            Workload::Monotone => {
+                benchmark_monotone_with_size(options.size, options.mode, len, &heaps, options.count)
             }
+            Workload::TopK => benchmark_top_k_with_size(
+                options.size,
+                options.mode,
+                len,
+                options.k,
+                &heaps,
+                options.count,
+            ),
+            Workload::Concurrent => benchmark_concurrent_with_size(
+                options.size,
+                options.mode,
+                len,
+                &options.threads,
+                &heaps,
+            ),
+        }
+    }
+}
+
+fn benchmark_sort_with_size(
+    size: ArrayElemType,
+    mode: ArrayMode,
+    len: usize,
+    heaps: &HeapSelection,
+    count: bool,
+) {
+    match size {
+        ArrayElemType::U8 => {
+            benchmark_heaps_sort::<u8>(mode, len, heaps, count);
+        }
+        ArrayElemType::U16 => {
+            benchmark_heaps_sort::<u16>(mode, len, heaps, count);
+        }
+        ArrayElemType::U32 => {
+            benchmark_heaps_sort::<u32>(mode, len, heaps, count);
+        }
+        ArrayElemType::U64 => {
+            benchmark_heaps_sort::<u64>(mode, len, heaps, count);
+        }
+        ArrayElemType::U128 => {
+            benchmark_heaps_sort::<u128>(mode, len, heaps, count);
+        }
+        ArrayElemType::Big => {
+            type T = ByteArray<1024>;
+            benchmark_heaps_sort::<T>(mode, len, heaps, count);
+        }
+        ArrayElemType::Bigger => {
+            type T = ByteArray<10240>;
+            benchmark_heaps_sort::<T>(mode, len, heaps, count);
+        }
+    }
+}
+
+fn benchmark_top_k_with_size(
+    size: ArrayElemType,
+    mode: ArrayMode,
+    len: usize,
+    k: usize,
+    heaps: &HeapSelection,
+    count: bool,
+) {
+    match size {
+        ArrayElemType::U8 => {
+            benchmark_heaps_top_k::<u8>(mode, len, k, heaps, count);
+        }
+        ArrayElemType::U16 => {
+            benchmark_heaps_top_k::<u16>(mode, len, k, heaps, count);
+        }
+        ArrayElemType::U32 => {
+            benchmark_heaps_top_k::<u32>(mode, len, k, heaps, count);
+        }
+        ArrayElemType::U64 => {
+            benchmark_heaps_top_k::<u64>(mode, len, k, heaps, count);
+        }
+        ArrayElemType::U128 => {
+            benchmark_heaps_top_k::<u128>(mode, len, k, heaps, count);
+        }
+        ArrayElemType::Big => {
+            type T = ByteArray<1024>;
+            benchmark_heaps_top_k::<T>(mode, len, k, heaps, count);
+        }
+        ArrayElemType::Bigger => {
+            type T = ByteArray<10240>;
+            benchmark_heaps_top_k::<T>(mode, len, k, heaps, count);
+        }
+    }
+}
+
+fn benchmark_concurrent_with_size(
+    size: ArrayElemType,
+    mode: ArrayMode,
+    len: usize,
+    threads: &[usize],
+    heaps: &HeapSelection,
+) {
+    match size {
+        ArrayElemType::U8 => {
+            benchmark_heaps_concurrent::<u8>(mode, len, threads, heaps);
+        }
+        ArrayElemType::U16 => {
+            benchmark_heaps_concurrent::<u16>(mode, len, threads, heaps);
+        }
+        ArrayElemType::U32 => {
+            benchmark_heaps_concurrent::<u32>(mode, len, threads, heaps);
+        }
+        ArrayElemType::U64 => {
+            benchmark_heaps_concurrent::<u64>(mode, len, threads, heaps);
+        }
+        ArrayElemType::U128 => {
+            benchmark_heaps_concurrent::<u128>(mode, len, threads, heaps);
+        }
+        ArrayElemType::Big => {
+            type T = ByteArray<1024>;
+            benchmark_heaps_concurrent::<T>(mode, len, threads, heaps);
+        }
+        ArrayElemType::Bigger => {
+            type T = ByteArray<10240>;
+            benchmark_heaps_concurrent::<T>(mode, len, threads, heaps);
+        }
+    }
+}
+
+fn benchmark_monotone_with_size(
+    size: ArrayElemType,
+    mode: ArrayMode,
+    len: usize,
+    heaps: &HeapSelection,
+    count: bool,
+) {
+    match size {
+        ArrayElemType::U8 => {
+            benchmark_heaps_monotone::<u8>(mode, len, heaps, count);
+        }
+        ArrayElemType::U16 => {
+            benchmark_heaps_monotone::<u16>(mode, len, heaps, count);
+        }
+        ArrayElemType::U32 => {
+            benchmark_heaps_monotone::<u32>(mode, len, heaps, count);
+        }
+        ArrayElemType::U64 => {
+            benchmark_heaps_monotone::<u64>(mode, len, heaps, count);
+        }
+        ArrayElemType::U128 => {
+            benchmark_heaps_monotone::<u128>(mode, len, heaps, count);
+        }
+        ArrayElemType::Big | ArrayElemType::Bigger => {
+            unreachable!("checked before running")
         }
     }
 }
@@ -125,6 +381,9 @@ impl BenchElemType for u8 {
         thread_rng().fill(&mut array[..]);
         array
     }
+    fn bucket_priority(&self) -> Option<usize> {
+        Some(*self as usize)
+    }
 }
 impl BenchElemType for u16 {
     fn zero_array(len: usize) -> Vec<Self> {
@@ -190,3 +449,28 @@ impl BenchElemType for u128 {
         array
     }
 }
+impl MonotoneKey for u8 {
+    fn add_weight(self, seed: Self) -> Self {
+        self.saturating_add(seed >> (Self::BITS / 2))
+    }
+}
+impl MonotoneKey for u16 {
+    fn add_weight(self, seed: Self) -> Self {
+        self.saturating_add(seed >> (Self::BITS / 2))
+    }
+}
+impl MonotoneKey for u32 {
+    fn add_weight(self, seed: Self) -> Self {
+        self.saturating_add(seed >> (Self::BITS / 2))
+    }
+}
+impl MonotoneKey for u64 {
+    fn add_weight(self, seed: Self) -> Self {
+        self.saturating_add(seed >> (Self::BITS / 2))
+    }
+}
+impl MonotoneKey for u128 {
+    fn add_weight(self, seed: Self) -> Self {
+        self.saturating_add(seed >> (Self::BITS / 2))
+    }
+}
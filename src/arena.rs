@@ -0,0 +1,240 @@
+use std::{
+    iter::FusedIterator,
+    mem,
+    ops::{Index, IndexMut},
+    slice, vec,
+};
+
+/// Index of a node in a [`Slab`]
+pub(crate) type NodeId = u32;
+
+#[derive(Clone)]
+enum Slot<T> {
+    Occupied(T),
+    /// Next slot in the free list
+    Vacant(Option<NodeId>),
+}
+
+/// Nodes stored contiguously in a `Vec` and addressed by their index. Removed slots are kept in a
+/// free list and reused by later insertions, so indices of the other nodes never change
+#[derive(Clone)]
+pub(crate) struct Slab<T> {
+    slots: Vec<Slot<T>>,
+    /// First vacant slot
+    free: Option<NodeId>,
+    len: usize,
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl<T> Slab<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Number of occupied slots
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of occupied and vacant slots
+    pub fn slots_len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn insert(&mut self, value: T) -> NodeId {
+        self.len += 1;
+        match self.free {
+            Some(id) => {
+                let slot = mem::replace(&mut self.slots[id as usize], Slot::Occupied(value));
+                let Slot::Vacant(next_free) = slot else {
+                    unreachable!("free list points to an occupied slot")
+                };
+                self.free = next_free;
+                id
+            }
+            None => {
+                let id = NodeId::try_from(self.slots.len()).expect("slab is full");
+                self.slots.push(Slot::Occupied(value));
+                id
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> T {
+        let slot = mem::replace(&mut self.slots[id as usize], Slot::Vacant(self.free));
+        let Slot::Occupied(value) = slot else {
+            panic!("node {id} was already removed")
+        };
+        self.free = Some(id);
+        self.len -= 1;
+        value
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.free = None;
+        self.len = 0;
+    }
+
+    /// Moves all the slots of `other` after the slots of `self`, calling `shift` on each moved
+    /// value so it can add the returned offset to the indices it holds
+    pub fn append(&mut self, other: Self, mut shift: impl FnMut(&mut T, NodeId)) -> NodeId {
+        let offset = NodeId::try_from(self.slots.len()).expect("slab is full");
+        assert!(
+            NodeId::try_from(self.slots.len() + other.slots.len()).is_ok(),
+            "slab is full"
+        );
+
+        self.slots.reserve(other.slots.len());
+        for slot in other.slots {
+            match slot {
+                Slot::Occupied(mut value) => {
+                    shift(&mut value, offset);
+                    self.slots.push(Slot::Occupied(value));
+                }
+                Slot::Vacant(_) => {
+                    // Push it on the free list of self instead
+                    let id = self.slots.len() as NodeId;
+                    self.slots.push(Slot::Vacant(self.free));
+                    self.free = Some(id);
+                }
+            }
+        }
+        self.len += other.len;
+        offset
+    }
+
+    /// Iterates over the occupied slots in index order
+    pub fn values(&self) -> Values<'_, T> {
+        Values {
+            slots: self.slots.iter(),
+            remaining: self.len,
+        }
+    }
+
+    pub fn into_values(self) -> IntoValues<T> {
+        IntoValues {
+            slots: self.slots.into_iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<T> Index<NodeId> for Slab<T> {
+    type Output = T;
+
+    fn index(&self, id: NodeId) -> &T {
+        match &self.slots[id as usize] {
+            Slot::Occupied(value) => value,
+            Slot::Vacant(_) => panic!("node {id} was removed"),
+        }
+    }
+}
+
+impl<T> IndexMut<NodeId> for Slab<T> {
+    fn index_mut(&mut self, id: NodeId) -> &mut T {
+        match &mut self.slots[id as usize] {
+            Slot::Occupied(value) => value,
+            Slot::Vacant(_) => panic!("node {id} was removed"),
+        }
+    }
+}
+
+pub(crate) struct Values<'a, T> {
+    slots: slice::Iter<'a, Slot<T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Values<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.slots.find_map(|slot| match slot {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        })?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Values<'_, T> {}
+impl<T> FusedIterator for Values<'_, T> {}
+
+pub(crate) struct IntoValues<T> {
+    slots: vec::IntoIter<Slot<T>>,
+    remaining: usize,
+}
+
+impl<T> Iterator for IntoValues<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.slots.find_map(|slot| match slot {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        })?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoValues<T> {}
+impl<T> FusedIterator for IntoValues<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::Slab;
+
+    #[test]
+    fn reuses_removed_slots() {
+        let mut slab = Slab::default();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        let c = slab.insert('c');
+        assert_eq!(slab.remove(b), 'b');
+        assert_eq!(slab.remove(a), 'a');
+        assert_eq!(slab.len(), 1);
+
+        assert_eq!(slab.insert('d'), a);
+        assert_eq!(slab.insert('e'), b);
+        assert_eq!(slab.insert('f'), 3);
+        assert_eq!(slab[c], 'c');
+        assert_eq!(slab.values().collect::<String>(), "decf");
+    }
+
+    #[test]
+    fn append_shifts_indices() {
+        let mut slab_a = Slab::default();
+        slab_a.insert(0);
+        let mut slab_b = Slab::default();
+        let x = slab_b.insert(10);
+        let y = slab_b.insert(11);
+        slab_b.remove(x);
+
+        let offset = slab_a.append(slab_b, |value, offset| *value += offset);
+        assert_eq!(offset, 1);
+        assert_eq!(slab_a.len(), 2);
+        assert_eq!(slab_a[y + offset], 12);
+        // The vacant slot of slab_b is reused
+        assert_eq!(slab_a.insert(5), x + offset);
+        assert_eq!(slab_a.into_values().collect::<Vec<_>>(), vec![0, 5, 12]);
+    }
+}
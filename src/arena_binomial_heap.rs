@@ -0,0 +1,557 @@
+use std::{
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{check_len, ensure, CheckInvariants, InvariantError};
+use crate::{
+    arena::{IntoValues, NodeId, Slab, Values},
+    dot::{Dot, ToDot},
+    iter::same_items,
+    stats::count,
+    Compare, MinHeap, MinOrder,
+};
+
+#[derive(Clone)]
+struct ArenaBinomialNode<I> {
+    item: I,
+    /// Number of children node has
+    degree: u32,
+    /// Right sibling of the node
+    next_sibling: Option<NodeId>,
+    /// Left child of the node
+    left_child: Option<NodeId>,
+}
+
+/// Binomial heap that keeps its nodes in a single slab instead of one `Box` per item. Melding
+/// moves the nodes of the heap with fewer slots into the other slab, so it takes linear time
+#[derive(Clone)]
+pub struct ArenaBinomialHeap<I, C = MinOrder> {
+    nodes: Slab<ArenaBinomialNode<I>>,
+    /// List of binomial trees
+    head: Option<NodeId>,
+    compare: C,
+}
+
+impl<I, C: Compare<I> + Default> Default for ArenaBinomialHeap<I, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, C: Compare<I>> ArenaBinomialHeap<I, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            nodes: Slab::default(),
+            head: None,
+            compare,
+        }
+    }
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        let mut heap = Self {
+            nodes: Slab::with_capacity(items.len()),
+            head: None,
+            compare,
+        };
+        heap.extend(items);
+        heap
+    }
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I> {
+        Iter {
+            nodes: self.nodes.values(),
+        }
+    }
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<I> {
+        self.head = None;
+        IntoIter {
+            nodes: mem::take(&mut self.nodes).into_values(),
+        }
+    }
+    /// Makes the tree with the larger root a child of the other root, returning the new root
+    fn link(&mut self, tree_a: NodeId, tree_b: NodeId) -> NodeId {
+        count!(links);
+        let (root, child) = if self
+            .compare
+            .gt(&self.nodes[tree_a].item, &self.nodes[tree_b].item)
+        {
+            (tree_b, tree_a)
+        } else {
+            (tree_a, tree_b)
+        };
+        debug_assert_eq!(self.nodes[root].degree, self.nodes[child].degree);
+        debug_assert!(self.nodes[child].next_sibling.is_none());
+
+        self.nodes[child].next_sibling = self.nodes[root].left_child;
+        let root_node = &mut self.nodes[root];
+        root_node.left_child = Some(child);
+        root_node.degree += 1;
+        root
+    }
+    fn merge_by_degree(
+        &mut self,
+        mut list_a: Option<NodeId>,
+        mut list_b: Option<NodeId>,
+    ) -> Option<NodeId> {
+        let mut new_list = None;
+        let mut list_tail: Option<NodeId> = None;
+        loop {
+            let next = match (list_a, list_b) {
+                (None, None) => break,
+                (Some(a), Some(b)) if self.nodes[a].degree > self.nodes[b].degree => {
+                    list_b = self.nodes[b].next_sibling;
+                    b
+                }
+                (Some(a), _) => {
+                    list_a = self.nodes[a].next_sibling;
+                    a
+                }
+                (None, Some(b)) => {
+                    list_b = self.nodes[b].next_sibling;
+                    b
+                }
+            };
+            match list_tail {
+                Some(tail) => self.nodes[tail].next_sibling = Some(next),
+                None => new_list = Some(next),
+            }
+            list_tail = Some(next);
+        }
+
+        new_list
+    }
+    fn meld_lists(&mut self, list_a: Option<NodeId>, list_b: Option<NodeId>) -> Option<NodeId> {
+        let mut x = self.merge_by_degree(list_a, list_b)?;
+        let mut next_x = self.nodes[x].next_sibling.take();
+        let mut list_head = None;
+        let mut list_tail: Option<NodeId> = None;
+
+        while let Some(next) = next_x {
+            let x_degree = self.nodes[x].degree;
+            if x_degree != self.nodes[next].degree
+                || self.nodes[next]
+                    .next_sibling
+                    .is_some_and(|nn_x| self.nodes[nn_x].degree == x_degree)
+            {
+                match list_tail {
+                    Some(tail) => self.nodes[tail].next_sibling = Some(x),
+                    None => list_head = Some(x),
+                }
+                list_tail = Some(x);
+                x = next;
+                next_x = self.nodes[x].next_sibling.take();
+            } else {
+                next_x = self.nodes[next].next_sibling.take();
+                x = self.link(x, next);
+            }
+        }
+        match list_tail {
+            Some(tail) => self.nodes[tail].next_sibling = Some(x),
+            None => list_head = Some(x),
+        }
+
+        list_head
+    }
+    /// Returns the root with the min item and the root before it in the list
+    fn find_min(&self) -> Option<(Option<NodeId>, NodeId)> {
+        let mut min = (None, self.head?);
+        let mut prev = min.1;
+
+        while let Some(tree) = self.nodes[prev].next_sibling {
+            if self
+                .compare
+                .gt(&self.nodes[min.1].item, &self.nodes[tree].item)
+            {
+                min = (Some(prev), tree);
+            }
+            prev = tree;
+        }
+
+        Some(min)
+    }
+}
+
+impl<I, C: Compare<I>> MinHeap for ArenaBinomialHeap<I, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        self.find_min().map(|(_, min)| &self.nodes[min].item)
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        let (prev, min) = self.find_min()?;
+        let next = self.nodes[min].next_sibling;
+        match prev {
+            Some(prev) => self.nodes[prev].next_sibling = next,
+            None => self.head = next,
+        }
+        let min_node = self.nodes.remove(min);
+
+        // Reverse the list of children so their degrees are increasing like the list of trees
+        let mut children = None;
+        let mut cursor = min_node.left_child;
+        while let Some(child) = cursor {
+            cursor = mem::replace(&mut self.nodes[child].next_sibling, children);
+            children = Some(child);
+        }
+        self.head = self.meld_lists(self.head, children);
+
+        Some(min_node.item)
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        let node = self.nodes.insert(ArenaBinomialNode {
+            item,
+            degree: 0,
+            next_sibling: None,
+            left_child: None,
+        });
+        self.head = self.meld_lists(self.head, Some(node));
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.head = None;
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        let mut heap = Self {
+            nodes: Slab::with_capacity(items.len()),
+            ..Self::default()
+        };
+        heap.extend(items);
+        heap
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        // Move the nodes of the smaller slab into the bigger one
+        if heap_a.nodes.slots_len() < heap_b.nodes.slots_len() {
+            mem::swap(&mut heap_a.compare, &mut heap_b.compare);
+            mem::swap(&mut heap_a, &mut heap_b);
+        }
+        let offset = heap_a.nodes.append(heap_b.nodes, |node, offset| {
+            node.next_sibling = node.next_sibling.map(|id| id + offset);
+            node.left_child = node.left_child.map(|id| id + offset);
+        });
+        let head_b = heap_b.head.map(|id| id + offset);
+        heap_a.head = heap_a.meld_lists(heap_a.head, head_b);
+        heap_a
+    }
+}
+
+/// Iterator over the items of an [`ArenaBinomialHeap`] in arbitrary order
+pub struct Iter<'a, I> {
+    nodes: Values<'a, ArenaBinomialNode<I>>,
+}
+
+impl<'a, I> Iterator for Iter<'a, I> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Iter<'_, I> {}
+impl<I> FusedIterator for Iter<'_, I> {}
+
+/// Owning iterator over the items of an [`ArenaBinomialHeap`] in arbitrary order
+pub struct IntoIter<I> {
+    nodes: IntoValues<ArenaBinomialNode<I>>,
+}
+
+impl<I> Iterator for IntoIter<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for IntoIter<I> {}
+impl<I> FusedIterator for IntoIter<I> {}
+
+impl<I, C> IntoIterator for ArenaBinomialHeap<I, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            nodes: self.nodes.into_values(),
+        }
+    }
+}
+
+impl<'a, I, C> IntoIterator for &'a ArenaBinomialHeap<I, C> {
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter {
+            nodes: self.nodes.values(),
+        }
+    }
+}
+
+impl<I, C: Compare<I> + Default> FromIterator<I> for ArenaBinomialHeap<I, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default> From<Vec<I>> for ArenaBinomialHeap<I, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default, const N: usize> From<[I; N]> for ArenaBinomialHeap<I, C> {
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, C: Compare<I>> Extend<I> for ArenaBinomialHeap<I, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Inserting is amortized O(1) when there are no extractions in between, like incrementing
+        // a binary counter
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<I: Debug, C> Debug for ArenaBinomialHeap<I, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, C: Compare<I>> PartialEq for ArenaBinomialHeap<I, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, C: Compare<I>> Eq for ArenaBinomialHeap<I, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> ArenaBinomialHeap<I, C> {
+    /// Checks the binomial tree below `node`, returning its number of nodes
+    fn check_tree(&self, node: NodeId) -> Result<usize, InvariantError> {
+        let degree = self.nodes[node].degree;
+        // Children go from degree `degree - 1` down to 0
+        let mut size = 1;
+        let mut children = 0;
+        let mut cursor = self.nodes[node].left_child;
+        while let Some(child) = cursor {
+            children += 1;
+            ensure!(
+                children <= degree && self.nodes[child].degree == degree - children,
+                "child degrees",
+                "child {children} of a node of degree {degree} has degree {}",
+                self.nodes[child].degree
+            );
+            ensure!(
+                self.compare
+                    .le(&self.nodes[node].item, &self.nodes[child].item),
+                "heap order",
+                "child {children} of a node of degree {degree} is smaller than it"
+            );
+            size += self.check_tree(child)?;
+            cursor = self.nodes[child].next_sibling;
+        }
+        ensure!(
+            children == degree,
+            "child degrees",
+            "node of degree {degree} has {children} children"
+        );
+        ensure!(
+            size == 1 << degree,
+            "binomial tree size",
+            "tree of degree {degree} has {size} nodes instead of {}",
+            1usize << degree
+        );
+        Ok(size)
+    }
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> CheckInvariants for ArenaBinomialHeap<I, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut len = 0;
+        let mut prev_degree = None;
+        let mut cursor = self.head;
+        while let Some(root) = cursor {
+            let degree = self.nodes[root].degree;
+            if let Some(prev_degree) = prev_degree {
+                ensure!(
+                    prev_degree < degree,
+                    "root list degrees",
+                    "root of degree {degree} follows a root of degree {prev_degree}"
+                );
+            }
+            len += self.check_tree(root)?;
+            prev_degree = Some(degree);
+            cursor = self.nodes[root].next_sibling;
+        }
+        check_len(self.nodes.len(), len)
+    }
+}
+
+/// Draws the list of roots side by side, with an edge from each node to its children
+impl<I: Debug, C> ToDot for ArenaBinomialHeap<I, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut roots = Vec::new();
+        let mut stack = Vec::new();
+        let mut cursor = self.head;
+        while let Some(root) = cursor {
+            let node = &self.nodes[root];
+            let id = dot.node(&node.item, &format!("degree {}", node.degree), "");
+            roots.push(id);
+            stack.push((node, id));
+            cursor = node.next_sibling;
+        }
+        dot.list(&roots);
+        while let Some((node, id)) = stack.pop() {
+            let mut cursor = node.left_child;
+            while let Some(child) = cursor {
+                let child = &self.nodes[child];
+                let child_id = dot.node(&child.item, &format!("degree {}", child.degree), "");
+                dot.edge(id, child_id, "");
+                stack.push((child, child_id));
+                cursor = child.next_sibling;
+            }
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArenaBinomialHeap;
+    use crate::{tests, ByKey, MaxOrder, MinHeap};
+
+    type HeapU32 = ArenaBinomialHeap<u32>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<ArenaBinomialHeap<u32, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| ArenaBinomialHeap::with_comparator(ByKey(|item: &(u32, char)| item.0)));
+    }
+
+    #[test]
+    fn reuses_extracted_slots() {
+        let mut heap = HeapU32::heapify((0..100).collect());
+        for _ in 0..50 {
+            heap.extract_min();
+        }
+        heap.extend(200..250);
+        assert_eq!(heap.nodes.slots_len(), 100);
+
+        let other = HeapU32::heapify((100..110).collect());
+        let mut heap = HeapU32::meld(other, heap);
+        assert_eq!(heap.nodes.slots_len(), 110);
+        for i in (50..110).chain(200..250) {
+            assert_eq!(heap.extract_min(), Some(i));
+        }
+        assert_eq!(heap.extract_min(), None);
+    }
+}
@@ -0,0 +1,626 @@
+use std::{
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{check_len, ensure, CheckInvariants, InvariantError};
+use crate::{
+    arena::{IntoValues, NodeId, Slab, Values},
+    dot::{Dot, ToDot},
+    iter::same_items,
+    stats::count,
+    Compare, MinHeap, MinOrder,
+};
+
+#[derive(Clone)]
+struct ArenaLeftTreeNode<I> {
+    item: I,
+    degree: u32,
+    /// Left child
+    left: Option<NodeId>,
+    /// Right child, unless node is a root then it is the next tree
+    right: Option<NodeId>,
+}
+
+/// Lazy one-pass binomial heap that keeps its nodes in a single slab instead of one `Box` per
+/// item. Melding moves the nodes of the heap with fewer slots into the other slab, so it takes
+/// linear time
+#[derive(Clone)]
+pub struct ArenaLazyBinomialHeap<I, C = MinOrder> {
+    nodes: Slab<ArenaLeftTreeNode<I>>,
+    // Head of the list of trees
+    head: Option<NodeId>,
+    // Last tree in the list
+    tail: Option<NodeId>,
+    // Tree immediately before the min tree, or None if min tree is at head
+    prev_min: Option<NodeId>,
+    // Tree with min value in the list
+    min: Option<NodeId>,
+    // Order of the items
+    compare: C,
+}
+
+impl<I, C: Compare<I> + Default> Default for ArenaLazyBinomialHeap<I, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, C: Compare<I>> ArenaLazyBinomialHeap<I, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            nodes: Slab::default(),
+            head: None,
+            tail: None,
+            prev_min: None,
+            min: None,
+            compare,
+        }
+    }
+
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        let mut heap = Self::with_comparator(compare);
+        heap.nodes = Slab::with_capacity(items.len());
+        heap.extend(items);
+        heap
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I> {
+        Iter {
+            nodes: self.nodes.values(),
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<I> {
+        let nodes = mem::take(&mut self.nodes);
+        self.set_list(None);
+        IntoIter {
+            nodes: nodes.into_values(),
+        }
+    }
+
+    /// Appends a tree to the end of the list of trees, updating the min if needed
+    fn push_root(&mut self, tree: NodeId) {
+        debug_assert!(self.nodes[tree].right.is_none());
+        if self.min.is_none_or(|min| {
+            self.compare
+                .gt(&self.nodes[min].item, &self.nodes[tree].item)
+        }) {
+            self.prev_min = self.tail;
+            self.min = Some(tree);
+        }
+        match self.tail {
+            Some(tail) => self.nodes[tail].right = Some(tree),
+            None => self.head = Some(tree),
+        }
+        self.tail = Some(tree);
+    }
+
+    /// Replaces the list of trees, finding its tail and min
+    fn set_list(&mut self, list: Option<NodeId>) {
+        self.head = list;
+        self.tail = None;
+        self.prev_min = None;
+        self.min = None;
+
+        let mut cursor = list;
+        while let Some(tree) = cursor {
+            if self.min.is_none_or(|min| {
+                self.compare
+                    .gt(&self.nodes[min].item, &self.nodes[tree].item)
+            }) {
+                self.prev_min = self.tail;
+                self.min = Some(tree);
+            }
+            self.tail = Some(tree);
+            cursor = self.nodes[tree].right;
+        }
+    }
+
+    /// Makes the tree with the larger root a child of the other root, returning the new root
+    fn link(&mut self, tree_a: NodeId, tree_b: NodeId) -> NodeId {
+        count!(links);
+        let (root, child) = if self
+            .compare
+            .gt(&self.nodes[tree_a].item, &self.nodes[tree_b].item)
+        {
+            (tree_b, tree_a)
+        } else {
+            (tree_a, tree_b)
+        };
+        debug_assert_eq!(self.nodes[root].degree, self.nodes[child].degree);
+        debug_assert!(self.nodes[child].right.is_none());
+
+        self.nodes[child].right = self.nodes[root].left;
+        let root_node = &mut self.nodes[root];
+        root_node.left = Some(child);
+        root_node.degree += 1;
+        root
+    }
+
+    /// Links each tree with the first unmatched tree of the same degree found before it, going
+    /// through `list_a` and then `list_b`. Returns the list of linked and leftover trees
+    fn merge_matches_one_pass(
+        &mut self,
+        list_a: Option<NodeId>,
+        mut list_b: Option<NodeId>,
+    ) -> Option<NodeId> {
+        // Unmatched tree of each degree
+        let mut matchings: Vec<Option<NodeId>> = Vec::new();
+
+        let mut full_list_head = None;
+        let mut list_tail: Option<NodeId> = None;
+        let mut push = |nodes: &mut Slab<ArenaLeftTreeNode<I>>, tree: NodeId| {
+            match list_tail {
+                Some(tail) => nodes[tail].right = Some(tree),
+                None => full_list_head = Some(tree),
+            }
+            list_tail = Some(tree);
+        };
+
+        let mut cursor = list_a;
+        while let Some(tree) = cursor.or_else(|| list_b.take()) {
+            cursor = self.nodes[tree].right.take();
+
+            let degree = self.nodes[tree].degree as usize;
+            if matchings.len() <= degree {
+                matchings.resize(degree + 1, None);
+            }
+            if let Some(matching_tree) = matchings[degree].take() {
+                let linked = self.link(tree, matching_tree);
+                push(&mut self.nodes, linked);
+            } else {
+                matchings[degree] = Some(tree);
+            }
+        }
+        // Add leftovers
+        for tree in matchings.into_iter().flatten() {
+            push(&mut self.nodes, tree);
+        }
+
+        full_list_head
+    }
+}
+
+impl<I, C: Compare<I>> MinHeap for ArenaLazyBinomialHeap<I, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        self.min.map(|min| &self.nodes[min].item)
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        let min = self.min?;
+        let next = self.nodes[min].right;
+        match self.prev_min {
+            Some(prev) => self.nodes[prev].right = next,
+            None => self.head = next,
+        }
+        let min_node = self.nodes.remove(min);
+
+        // Children of a root can be thought of as list of trees
+        let merged = self.merge_matches_one_pass(self.head, min_node.left);
+        self.set_list(merged);
+
+        Some(min_node.item)
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        let tree = self.nodes.insert(ArenaLeftTreeNode {
+            item,
+            degree: 0,
+            left: None,
+            right: None,
+        });
+        self.push_root(tree);
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.set_list(None);
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        let mut heap = Self {
+            nodes: Slab::with_capacity(items.len()),
+            ..Self::default()
+        };
+        heap.extend(items);
+        heap
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        // Move the nodes of the smaller slab into the bigger one, keeping the list of heap_a first
+        let a_first = heap_a.nodes.slots_len() >= heap_b.nodes.slots_len();
+        if !a_first {
+            mem::swap(&mut heap_a.compare, &mut heap_b.compare);
+            mem::swap(&mut heap_a, &mut heap_b);
+        }
+        let offset = heap_a.nodes.append(heap_b.nodes, |node, offset| {
+            node.left = node.left.map(|id| id + offset);
+            node.right = node.right.map(|id| id + offset);
+        });
+        let shift = |id: Option<NodeId>| id.map(|id| id + offset);
+        let (list_a, tail_a, list_b) = if a_first {
+            (heap_a.head, heap_a.tail, shift(heap_b.head))
+        } else {
+            (shift(heap_b.head), shift(heap_b.tail), heap_a.head)
+        };
+
+        // Append list b to the tail of list a, then find the new min
+        let list = match tail_a {
+            Some(tail_a) => {
+                heap_a.nodes[tail_a].right = list_b;
+                list_a
+            }
+            None => list_b,
+        };
+        heap_a.set_list(list);
+        heap_a
+    }
+}
+
+/// Iterator over the items of an [`ArenaLazyBinomialHeap`] in arbitrary order
+pub struct Iter<'a, I> {
+    nodes: Values<'a, ArenaLeftTreeNode<I>>,
+}
+
+impl<'a, I> Iterator for Iter<'a, I> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Iter<'_, I> {}
+impl<I> FusedIterator for Iter<'_, I> {}
+
+/// Owning iterator over the items of an [`ArenaLazyBinomialHeap`] in arbitrary order
+pub struct IntoIter<I> {
+    nodes: IntoValues<ArenaLeftTreeNode<I>>,
+}
+
+impl<I> Iterator for IntoIter<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for IntoIter<I> {}
+impl<I> FusedIterator for IntoIter<I> {}
+
+impl<I, C> IntoIterator for ArenaLazyBinomialHeap<I, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            nodes: self.nodes.into_values(),
+        }
+    }
+}
+
+impl<'a, I, C> IntoIterator for &'a ArenaLazyBinomialHeap<I, C> {
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter {
+            nodes: self.nodes.values(),
+        }
+    }
+}
+
+impl<I, C: Compare<I> + Default> FromIterator<I> for ArenaLazyBinomialHeap<I, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default> From<Vec<I>> for ArenaLazyBinomialHeap<I, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default, const N: usize> From<[I; N]> for ArenaLazyBinomialHeap<I, C> {
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, C: Compare<I>> Extend<I> for ArenaLazyBinomialHeap<I, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Inserting is O(1), which is also how heapify builds the heap
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<I: Debug, C> Debug for ArenaLazyBinomialHeap<I, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, C: Compare<I>> PartialEq for ArenaLazyBinomialHeap<I, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, C: Compare<I>> Eq for ArenaLazyBinomialHeap<I, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> ArenaLazyBinomialHeap<I, C> {
+    /// Checks the binomial tree below `node`, returning its number of nodes
+    fn check_tree(&self, node: NodeId) -> Result<usize, InvariantError> {
+        let degree = self.nodes[node].degree;
+        // Children go from degree `degree - 1` down to 0
+        let mut size = 1;
+        let mut children = 0;
+        let mut cursor = self.nodes[node].left;
+        while let Some(child) = cursor {
+            children += 1;
+            ensure!(
+                children <= degree && self.nodes[child].degree == degree - children,
+                "child degrees",
+                "child {children} of a node of degree {degree} has degree {}",
+                self.nodes[child].degree
+            );
+            ensure!(
+                self.compare
+                    .le(&self.nodes[node].item, &self.nodes[child].item),
+                "heap order",
+                "child {children} of a node of degree {degree} is smaller than it"
+            );
+            size += self.check_tree(child)?;
+            cursor = self.nodes[child].right;
+        }
+        ensure!(
+            children == degree,
+            "child degrees",
+            "node of degree {degree} has {children} children"
+        );
+        ensure!(
+            size == 1 << degree,
+            "binomial tree size",
+            "tree of degree {degree} has {size} nodes instead of {}",
+            1usize << degree
+        );
+        Ok(size)
+    }
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> CheckInvariants for ArenaLazyBinomialHeap<I, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut len = 0;
+        let mut prev = None;
+        let mut min_found = false;
+        let mut smallest: Option<NodeId> = None;
+        let mut cursor = self.head;
+        for i in 0.. {
+            let Some(root) = cursor else {
+                break;
+            };
+            if self.min == Some(root) {
+                ensure!(
+                    self.prev_min == prev,
+                    "prev_min pointer",
+                    "the min is root {i} but prev_min isn't the root before it"
+                );
+                min_found = true;
+            }
+            if smallest.is_none_or(|smallest| {
+                self.compare
+                    .lt(&self.nodes[root].item, &self.nodes[smallest].item)
+            }) {
+                smallest = Some(root);
+            }
+            len += self.check_tree(root)?;
+            prev = Some(root);
+            cursor = self.nodes[root].right;
+        }
+        ensure!(
+            self.tail == prev,
+            "tail pointer",
+            "tail isn't the last root"
+        );
+        match smallest {
+            Some(smallest) => {
+                ensure!(min_found, "min pointer", "min isn't a root");
+                ensure!(
+                    self.compare.le(
+                        &self.nodes[self.min.unwrap()].item,
+                        &self.nodes[smallest].item
+                    ),
+                    "min pointer",
+                    "a root is smaller than min"
+                );
+            }
+            None => ensure!(
+                self.min.is_none() && self.prev_min.is_none(),
+                "min pointer",
+                "min or prev_min is set in an empty heap"
+            ),
+        }
+        check_len(self.nodes.len(), len)
+    }
+}
+
+/// Draws the list of roots side by side, marking the ones `min`, `prev_min` and `tail` point to,
+/// with the half trees drawn as the binomial trees they stand for
+impl<I: Debug, C> ToDot for ArenaLazyBinomialHeap<I, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut roots = Vec::new();
+        let mut stack = Vec::new();
+        let mut cursor = self.head;
+        while let Some(root) = cursor {
+            let node = &self.nodes[root];
+            let id = dot.node(&node.item, &format!("degree {}", node.degree), "");
+            for (name, marked) in [
+                ("min", self.min),
+                ("prev_min", self.prev_min),
+                ("tail", self.tail),
+            ] {
+                if marked == Some(root) {
+                    dot.marker(name, id);
+                }
+            }
+            roots.push(id);
+            stack.push((node, id));
+            cursor = node.right;
+        }
+        dot.list(&roots);
+        // The left link of a node is its first child and the right links of the children are
+        // their next siblings
+        while let Some((node, id)) = stack.pop() {
+            let mut cursor = node.left;
+            while let Some(child) = cursor {
+                let child = &self.nodes[child];
+                let child_id = dot.node(&child.item, &format!("degree {}", child.degree), "");
+                dot.edge(id, child_id, "");
+                stack.push((child, child_id));
+                cursor = child.right;
+            }
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArenaLazyBinomialHeap;
+    use crate::{tests, ByKey, MaxOrder, MinHeap};
+
+    type HeapU32 = ArenaLazyBinomialHeap<u32>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<ArenaLazyBinomialHeap<u32, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| {
+            ArenaLazyBinomialHeap::with_comparator(ByKey(|item: &(u32, char)| item.0))
+        });
+    }
+
+    #[test]
+    fn reuses_extracted_slots() {
+        let mut heap = HeapU32::heapify((0..100).collect());
+        for _ in 0..50 {
+            heap.extract_min();
+        }
+        heap.extend(200..250);
+        assert_eq!(heap.nodes.slots_len(), 100);
+
+        let other = HeapU32::heapify((100..110).collect());
+        let mut heap = HeapU32::meld(other, heap);
+        assert_eq!(heap.nodes.slots_len(), 110);
+        for i in (50..110).chain(200..250) {
+            assert_eq!(heap.extract_min(), Some(i));
+        }
+        assert_eq!(heap.extract_min(), None);
+    }
+}
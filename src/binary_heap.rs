@@ -1,99 +1,13 @@
-use crate::{Item, MinHeap};
+use crate::{DaryHeap, MinOrder};
 
-pub struct BinaryHeap<I: Item> {
-    array: Vec<I>,
-}
-
-#[inline(always)]
-const fn parent(i: usize) -> usize {
-    (i - 1) / 2
-}
-#[inline(always)]
-const fn left(i: usize) -> usize {
-    2 * i + 1
-}
-#[inline(always)]
-const fn right(i: usize) -> usize {
-    2 * i + 2
-}
-#[inline(always)]
-fn min_child<I: Item>(items: &[I], i: usize) -> usize {
-    if left(i) >= items.len() {
-        i
-    } else if right(i) >= items.len() || items[left(i)] < items[right(i)] {
-        left(i)
-    } else {
-        right(i)
-    }
-}
-
-impl<I: Item> BinaryHeap<I> {
-    fn sift_up(&mut self, mut i: usize) {
-        debug_assert!(i < self.array.len());
-        while i != 0 && self.array[parent(i)] > self.array[i] {
-            self.array.swap(parent(i), i);
-            i = parent(i);
-        }
-    }
-    fn sift_down(&mut self, mut i: usize) {
-        debug_assert!(i < self.array.len());
-        let mut child = min_child(&self.array, i);
-        while self.array[i] > self.array[child] {
-            self.array.swap(i, child);
-            i = child;
-            child = min_child(&self.array, i);
-        }
-    }
-}
-
-impl<I: Item> Default for BinaryHeap<I> {
-    fn default() -> Self {
-        Self { array: Vec::new() }
-    }
-}
-
-impl<I: Item> MinHeap for BinaryHeap<I> {
-    type Item = I;
-
-    fn peek_min(&self) -> Option<&Self::Item> {
-        self.array.first()
-    }
-
-    fn extract_min(&mut self) -> Option<Self::Item> {
-        if self.array.is_empty() {
-            return None;
-        }
-        let min = self.array.swap_remove(0);
-        if !self.array.is_empty() {
-            self.sift_down(0);
-        }
-        Some(min)
-    }
-
-    fn insert(&mut self, item: Self::Item) {
-        self.array.push(item);
-        self.sift_up(self.array.len() - 1);
-    }
-
-    fn heapify(items: Vec<Self::Item>) -> Self {
-        let mut heap = Self { array: items };
-        for i in (0..heap.array.len() / 2).rev() {
-            heap.sift_down(i);
-        }
-        heap
-    }
-
-    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
-        let mut items = std::mem::take(&mut heap_a.array);
-        items.append(&mut heap_b.array);
-        Self::heapify(items)
-    }
-}
+/// Heap stored as a complete binary tree in an array, the [`DaryHeap`] where each node has two
+/// children
+pub type BinaryHeap<I, C = MinOrder> = DaryHeap<I, 2, C>;
 
 #[cfg(test)]
 mod tests {
     use super::BinaryHeap;
-    use crate::tests;
+    use crate::{tests, ByKey, FnCompare, MaxOrder};
 
     type HeapU32 = BinaryHeap<u32>;
 
@@ -102,6 +16,10 @@ mod tests {
         tests::simple::<HeapU32>();
     }
     #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
     fn empty_heap() {
         tests::empty_heap::<HeapU32>();
     }
@@ -141,4 +59,95 @@ mod tests {
     fn meld_two_empty_heaps() {
         tests::meld_two_empty_heaps::<HeapU32>();
     }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key() {
+        tests::decrease_key::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key_many() {
+        tests::decrease_key_many::<HeapU32>();
+    }
+    #[test]
+    fn delete() {
+        tests::delete::<HeapU32>();
+    }
+    #[test]
+    fn handles_after_meld() {
+        tests::handles_after_meld::<HeapU32>();
+    }
+    #[test]
+    fn stale_handles_rejected() {
+        tests::stale_handles_rejected::<HeapU32>();
+    }
+    #[test]
+    fn clone_handles() {
+        tests::clone_handles::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<BinaryHeap<u32, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| BinaryHeap::with_comparator(ByKey(|item: &(u32, char)| item.0)));
+    }
+    #[test]
+    fn fn_compare_decrease_key() {
+        tests::fn_compare_decrease_key(BinaryHeap::with_comparator(FnCompare(
+            |a: &u32, b: &u32| b.cmp(a),
+        )));
+    }
+    #[test]
+    fn meld_different_comparators() {
+        // Melding heapifies all the items again with the first heap's comparator
+        assert_eq!(
+            tests::meld_different_comparators(BinaryHeap::with_comparator),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn capacity() {
+        use crate::MinHeap;
+
+        let mut heap = HeapU32::with_capacity(100);
+        assert!(heap.capacity() >= 100);
+        for i in 0..50 {
+            heap.insert(i);
+        }
+        heap.reserve(200);
+        assert!(heap.capacity() >= 250);
+        heap.shrink_to_fit();
+        assert!(heap.capacity() >= 50 && heap.capacity() < 250);
+        assert_eq!(heap.len(), 50);
+        assert_eq!(heap.extract_min(), Some(0));
+    }
 }
@@ -1,8 +1,25 @@
-use std::{mem, ptr};
-
-use crate::{Item, MinHeap};
-
-struct BinomialTreeNode<I: Item> {
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem,
+    ptr::{self, NonNull},
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{
+    check_handle, check_handle_count, check_len, ensure, CheckInvariants, InvariantError,
+};
+use crate::{
+    dot::{Dot, ToDot},
+    handle::meld_handle_maps,
+    iter::same_items,
+    node_box::{NodeBox, TreeNode},
+    stats::count,
+    AddressableHeap, Compare, Handle, HandleError, MinHeap, MinOrder,
+};
+
+struct BinomialTreeNode<I> {
     /// Item the node holds
     item: I,
     /// Number of children node has
@@ -11,51 +28,76 @@ struct BinomialTreeNode<I: Item> {
     next_sibling: BinomialTreeLink<I>,
     /// Left child of the node
     left_child: BinomialTreeLink<I>,
+    /// Parent of the node, or None if the node is a root
+    parent: Option<NonNull<BinomialTreeNode<I>>>,
+    /// Handle of the item, if it was inserted with one
+    handle: Option<Handle>,
 }
 
-type BinomialTreeLink<I> = Option<Box<BinomialTreeNode<I>>>;
+type BinomialTreeLink<I> = Option<NodeBox<BinomialTreeNode<I>>>;
 
-impl<I: Item> BinomialTreeNode<I> {
-    fn with_item(item: I) -> Box<Self> {
-        Box::new(Self {
+impl<I> TreeNode for BinomialTreeNode<I> {
+    fn take_links(&mut self) -> [Option<NodeBox<Self>>; 2] {
+        [self.next_sibling.take(), self.left_child.take()]
+    }
+}
+
+impl<I> BinomialTreeNode<I> {
+    fn with_item(item: I) -> NodeBox<Self> {
+        count!(allocations);
+        NodeBox::new(Self {
             item,
             degree: 0,
             next_sibling: None,
             left_child: None,
+            parent: None,
+            handle: None,
         })
     }
 
-    fn split_remove_root(mut self) -> (I, BinomialHeap<I>) {
+    fn split_remove_root(mut self) -> (I, BinomialTreeLink<I>) {
         debug_assert!(self.next_sibling.is_none());
 
         let mut left_child = self.left_child.take();
 
         // Reverse singly linked list from https://stackoverflow.com/a/65854843
         let rev = &mut None;
-        while left_child.is_some() {
-            mem::swap(&mut left_child.as_mut().unwrap().next_sibling, rev);
+        while let Some(child) = left_child.as_mut() {
+            child.parent = None;
+            mem::swap(&mut child.next_sibling, rev);
             mem::swap(&mut left_child, rev);
         }
         mem::swap(&mut left_child, rev);
 
-        (self.item, BinomialHeap { head: left_child })
+        (self.item, left_child)
     }
 
-    fn link(&mut self, mut other: Box<Self>) {
-        if self.item > other.item {
-            mem::swap(self, &mut other);
+    fn link(
+        mut tree: NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) -> NodeBox<Self> {
+        if compare.gt(&tree.item, &other.item) {
+            mem::swap(&mut tree, &mut other);
         }
-        self.link_as_child(other);
+        Self::link_as_child(&mut tree, other, compare);
+        tree
     }
 
-    fn link_as_child(&mut self, mut other: Box<Self>) {
-        debug_assert_eq!(self.degree, other.degree);
+    fn link_as_child(
+        tree: &mut NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) {
+        count!(links);
+        debug_assert_eq!(tree.degree, other.degree);
         debug_assert!(other.next_sibling.is_none());
-        debug_assert!(self.item <= other.item);
+        debug_assert!(compare.le(&tree.item, &other.item));
 
-        other.next_sibling = self.left_child.take();
-        self.left_child = Some(other);
-        self.degree += 1;
+        other.parent = Some(tree.as_ptr());
+        other.next_sibling = tree.left_child.take();
+        tree.left_child = Some(other);
+        tree.degree += 1;
     }
 
     fn merge_by_degree(
@@ -99,55 +141,194 @@ impl<I: Item> BinomialTreeNode<I> {
     }
 }
 
-pub struct BinomialHeap<I: Item> {
+pub struct BinomialHeap<I, C = MinOrder> {
     /// List of binomial trees
     head: BinomialTreeLink<I>,
+    /// Node holding the item of each handle in the heap
+    handles: HashMap<Handle, NonNull<BinomialTreeNode<I>>>,
+    len: usize,
+    compare: C,
 }
 
-impl<I: Item> Default for BinomialHeap<I> {
+// SAFETY: The raw pointers only point to nodes owned by the heap, so sending or sharing the heap
+// is the same as sending or sharing the items in it
+unsafe impl<I: Send, C: Send> Send for BinomialHeap<I, C> {}
+unsafe impl<I: Sync, C: Sync> Sync for BinomialHeap<I, C> {}
+
+impl<I, C: Compare<I> + Default> Default for BinomialHeap<I, C> {
     fn default() -> Self {
-        Self { head: None }
+        Self::with_comparator(C::default())
     }
 }
-impl<I: Item> BinomialHeap<I> {
-    fn with_item(item: I) -> Self {
+impl<I, C: Compare<I>> BinomialHeap<I, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
         Self {
-            head: Some(BinomialTreeNode::with_item(item)),
+            head: None,
+            handles: HashMap::new(),
+            len: 0,
+            compare,
         }
     }
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        Self {
+            len: items.len(),
+            head: Self::heapify_list(items, &compare),
+            handles: HashMap::new(),
+            compare,
+        }
+    }
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I> {
+        Iter {
+            stack: self.head.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<I> {
+        let iter = IntoIter {
+            stack: self.head.take().into_iter().collect(),
+            remaining: self.len,
+        };
+        self.clear();
+        iter
+    }
+    fn heapify_list(items: Vec<I>, compare: &C) -> BinomialTreeLink<I> {
+        match items.len() {
+            0 => None,
+            1 => Some(BinomialTreeNode::with_item(
+                items.into_iter().next().unwrap(),
+            )),
+            _ => {
+                // Recursively heapify by splitting vector in half and melding results
+                let mut left = items;
+                let right = left.split_off(left.len() / 2);
+
+                Self::meld_lists(
+                    Self::heapify_list(left, compare),
+                    Self::heapify_list(right, compare),
+                    compare,
+                )
+            }
+        }
+    }
+    fn meld_lists(
+        list_a: BinomialTreeLink<I>,
+        list_b: BinomialTreeLink<I>,
+        compare: &C,
+    ) -> BinomialTreeLink<I> {
+        let mut list_head = BinomialTreeNode::merge_by_degree(list_a, list_b);
+        let mut x = list_head.take()?;
+        let mut next_x = x.next_sibling.take();
+        let mut list_tail = &mut list_head;
+
+        while let Some(mut next) = next_x {
+            if x.degree != next.degree
+                || next
+                    .next_sibling
+                    .as_ref()
+                    .is_some_and(|nn_x| nn_x.degree == x.degree)
+            {
+                *list_tail = Some(x);
+                list_tail = &mut list_tail.as_mut().unwrap().next_sibling;
+                x = next;
+                next_x = x.next_sibling.take();
+            } else {
+                next_x = next.next_sibling.take();
+                x = BinomialTreeNode::link(x, next, compare);
+            }
+        }
+        debug_assert!(list_tail.is_none());
+        *list_tail = Some(x);
+
+        list_head
+    }
+    /// Melds a single new node into the heap
+    fn insert_node(&mut self, node: NodeBox<BinomialTreeNode<I>>) {
+        self.head = Self::meld_lists(self.head.take(), Some(node), &self.compare);
+        self.len += 1;
+    }
     fn find_min(&self) -> Option<&BinomialTreeNode<I>> {
         let mut min: Option<&BinomialTreeNode<I>> = None;
         let mut curr_tree = &self.head;
 
         while let Some(tree) = curr_tree {
-            if min.is_none_or(|min| min.item > tree.item) {
-                min = Some(tree.as_ref());
+            if min.is_none_or(|min| self.compare.gt(&min.item, &tree.item)) {
+                min = Some(tree);
             }
             curr_tree = &tree.next_sibling;
         }
 
         min
     }
-    fn find_link_to_min(&mut self) -> Option<&mut BinomialTreeLink<I>> {
-        let min: *const BinomialTreeNode<I> = self.find_min()?;
-
+    fn find_link_to(&mut self, root: *const BinomialTreeNode<I>) -> &mut BinomialTreeLink<I> {
         let mut curr_link = &mut self.head;
         loop {
             if curr_link
                 .as_ref()
-                .is_some_and(|tree| ptr::eq(tree.as_ref(), min))
+                .is_some_and(|tree| ptr::eq(&**tree, root))
             {
-                return Some(curr_link);
+                return curr_link;
             } else if let Some(tree) = curr_link {
                 curr_link = &mut tree.next_sibling;
             } else {
-                // We return in the first line if no min exists, so we expect to reach in some iteration
+                // Callers only pass roots in the list, so we expect to reach it in some iteration
                 unreachable!()
             }
         }
     }
+    /// Removes a root from the list of trees and returns its item
+    fn extract_root(&mut self, root: *const BinomialTreeNode<I>) -> I {
+        // Extract tree with given root from middle of linked list
+        let link_to_root = self.find_link_to(root);
+        let mut root_tree = link_to_root.take().unwrap();
+        *link_to_root = root_tree.next_sibling.take();
+
+        if let Some(handle) = root_tree.handle {
+            self.handles.remove(&handle);
+        }
+
+        // Build a new heap with the elements from the tree except the root
+        let (item, rest) = root_tree.into_inner().split_remove_root();
+
+        // Push all those elements back into the heap
+        self.head = Self::meld_lists(self.head.take(), rest, &self.compare);
+        self.len -= 1;
+
+        item
+    }
+    /// Moves the item in `node` up its tree while it is smaller than its parent's item, or all the
+    /// way to the root if `to_root` is true. Returns the node that ends up holding the item.
+    fn sift_up(
+        &mut self,
+        mut node: NonNull<BinomialTreeNode<I>>,
+        to_root: bool,
+    ) -> NonNull<BinomialTreeNode<I>> {
+        // SAFETY: We only store pointers to nodes owned by the heap, and a node is never its own
+        // parent, so the two mutable references don't alias.
+        // We hold a mutable reference to self, so no one can have any other reference
+        while let Some(mut parent) = unsafe { node.as_ref() }.parent {
+            let (child_node, parent_node) = unsafe { (node.as_mut(), parent.as_mut()) };
+            if !to_root && self.compare.le(&parent_node.item, &child_node.item) {
+                break;
+            }
+            mem::swap(&mut child_node.item, &mut parent_node.item);
+            count!(swaps);
+            mem::swap(&mut child_node.handle, &mut parent_node.handle);
+            if let Some(handle) = child_node.handle {
+                self.handles.insert(handle, node);
+            }
+            if let Some(handle) = parent_node.handle {
+                self.handles.insert(handle, parent);
+            }
+            node = parent;
+        }
+        node
+    }
 }
-impl<I: Item> MinHeap for BinomialHeap<I> {
+impl<I, C: Compare<I>> MinHeap for BinomialHeap<I, C> {
     type Item = I;
 
     fn peek_min(&self) -> Option<&Self::Item> {
@@ -155,82 +336,334 @@ impl<I: Item> MinHeap for BinomialHeap<I> {
     }
 
     fn extract_min(&mut self) -> Option<Self::Item> {
-        // Extract tree with min root from middle of linked list
-        let link_to_min = self.find_link_to_min()?;
-        let mut min_tree = link_to_min.take()?;
-        *link_to_min = min_tree.next_sibling.take();
+        let min: *const BinomialTreeNode<I> = self.find_min()?;
+        Some(self.extract_root(min))
+    }
 
-        // Build a new heap with the elements from the min tree except the root
-        let (min, rest) = min_tree.split_remove_root();
+    fn insert(&mut self, item: Self::Item) {
+        self.insert_node(BinomialTreeNode::with_item(item));
+    }
 
-        // Push all those elements back into the heap
-        let original_heap = mem::take(self);
-        *self = Self::meld(original_heap, rest);
+    fn len(&self) -> usize {
+        self.len
+    }
 
-        Some(min)
+    fn clear(&mut self) {
+        self.head = None;
+        self.handles.clear();
+        self.len = 0;
     }
 
-    fn insert(&mut self, item: Self::Item) {
-        let current = mem::take(self);
-        let new_item = Self::with_item(item);
-        *self = Self::meld(current, new_item);
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        Self::heapify_with_comparator(items, Self::default().compare)
     }
 
-    fn heapify(items: Vec<Self::Item>) -> Self {
-        match items.len() {
-            0 => Self::make_heap(),
-            1 => Self::with_item(items.into_iter().next().unwrap()),
-            _ => {
-                // Recursively heapify by splitting vector in half and melding results
-                let mut left = items;
-                let right = left.split_off(left.len() / 2);
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        heap_a.head = Self::meld_lists(heap_a.head.take(), heap_b.head.take(), &heap_a.compare);
+        heap_a.handles = meld_handle_maps(
+            mem::take(&mut heap_a.handles),
+            mem::take(&mut heap_b.handles),
+        );
+        heap_a.len += heap_b.len;
+        heap_a
+    }
+}
+
+impl<I, C: Compare<I>> AddressableHeap for BinomialHeap<I, C> {
+    fn insert_with_handle(&mut self, item: Self::Item) -> Handle {
+        let handle = Handle::new();
+        let mut node = BinomialTreeNode::with_item(item);
+        node.handle = Some(handle);
+        self.handles.insert(handle, node.as_ptr());
+        self.insert_node(node);
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<&Self::Item> {
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // Since we have &self borrowed, we know there are not mutable references
+        self.handles
+            .get(&handle)
+            .map(|node| unsafe { &node.as_ref().item })
+    }
 
-                Self::meld(Self::heapify(left), Self::heapify(right))
+    fn decrease_key(&mut self, handle: Handle, item: Self::Item) -> Result<(), HandleError> {
+        let mut node = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        {
+            // SAFETY: We only store pointers to nodes owned by the heap
+            // We hold a mutable reference to self, so no one can have any other reference
+            let node = unsafe { node.as_mut() };
+            if self.compare.gt(&item, &node.item) {
+                return Err(HandleError::KeyIncreased);
             }
+            node.item = item;
         }
+        self.sift_up(node, false);
+        Ok(())
     }
 
-    fn meld(heap_a: Self, heap_b: Self) -> Self {
-        let mut list_head = BinomialTreeNode::merge_by_degree(heap_a.head, heap_b.head);
-        if list_head.is_none() {
-            return Self::make_heap();
-        }
+    fn delete(&mut self, handle: Handle) -> Result<Self::Item, HandleError> {
+        let node = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        let root = self.sift_up(node, true);
+        Ok(self.extract_root(root.as_ptr()))
+    }
+}
 
-        let mut x = list_head.take().unwrap();
-        let mut next_x = x.next_sibling.take();
-        let mut list_tail = &mut list_head;
+/// Iterator over the items of a [`BinomialHeap`] in arbitrary order
+pub struct Iter<'a, I> {
+    /// Nodes whose item, children and next siblings haven't been visited yet
+    stack: Vec<&'a BinomialTreeNode<I>>,
+    remaining: usize,
+}
 
-        while let Some(mut next) = next_x {
-            if x.degree != next.degree
-                || next
-                    .next_sibling
-                    .as_ref()
-                    .is_some_and(|nn_x| nn_x.degree == x.degree)
-            {
-                *list_tail = Some(x);
-                list_tail = &mut list_tail.as_mut().unwrap().next_sibling;
-                x = next;
-                next_x = x.next_sibling.take();
-            } else {
-                next_x = next.next_sibling.take();
-                x.link(next);
+impl<'a, I> Iterator for Iter<'a, I> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.next_sibling.as_deref());
+        self.stack.extend(node.left_child.as_deref());
+        self.remaining -= 1;
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for Iter<'_, I> {}
+impl<I> FusedIterator for Iter<'_, I> {}
+
+/// Owning iterator over the items of a [`BinomialHeap`] in arbitrary order
+pub struct IntoIter<I> {
+    /// Trees whose items haven't been returned yet
+    stack: Vec<NodeBox<BinomialTreeNode<I>>>,
+    remaining: usize,
+}
+
+impl<I> Iterator for IntoIter<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?.into_inner();
+        self.stack.extend(node.next_sibling.take());
+        self.stack.extend(node.left_child.take());
+        self.remaining -= 1;
+        Some(node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for IntoIter<I> {}
+impl<I> FusedIterator for IntoIter<I> {}
+
+impl<I, C: Compare<I>> IntoIterator for BinomialHeap<I, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl<'a, I, C: Compare<I>> IntoIterator for &'a BinomialHeap<I, C> {
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<I, C: Compare<I> + Default> FromIterator<I> for BinomialHeap<I, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default> From<Vec<I>> for BinomialHeap<I, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default, const N: usize> From<[I; N]> for BinomialHeap<I, C> {
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, C: Compare<I>> Extend<I> for BinomialHeap<I, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Heapify the new items on their own and meld them in, like meld would with a second heap
+        let items: Vec<I> = iter.into_iter().collect();
+        self.len += items.len();
+        let list = Self::heapify_list(items, &self.compare);
+        self.head = Self::meld_lists(self.head.take(), list, &self.compare);
+    }
+}
+
+impl<I: Clone, C: Compare<I> + Clone> Clone for BinomialHeap<I, C> {
+    /// The clone has the same items but none of the handles, which keep referring to the items in
+    /// the original heap
+    fn clone(&self) -> Self {
+        Self::heapify_with_comparator(self.iter().cloned().collect(), self.compare.clone())
+    }
+}
+
+impl<I: Debug, C: Compare<I>> Debug for BinomialHeap<I, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, C: Compare<I>> PartialEq for BinomialHeap<I, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, C: Compare<I>> Eq for BinomialHeap<I, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> BinomialHeap<I, C> {
+    /// Checks the binomial tree below `node`, returning its number of nodes
+    fn check_tree(
+        &self,
+        node: &BinomialTreeNode<I>,
+        handles_found: &mut usize,
+    ) -> Result<usize, InvariantError> {
+        check_handle(
+            &self.handles,
+            node.handle,
+            NonNull::from(node),
+            handles_found,
+        )?;
+        // Children go from degree `node.degree - 1` down to 0
+        let mut size = 1;
+        let mut children = 0;
+        let mut cursor = node.left_child.as_deref();
+        while let Some(child) = cursor {
+            children += 1;
+            ensure!(
+                children <= node.degree && child.degree == node.degree - children,
+                "child degrees",
+                "child {children} of a node of degree {} has degree {}",
+                node.degree,
+                child.degree
+            );
+            ensure!(
+                child.parent == Some(NonNull::from(node)),
+                "parent pointers",
+                "child {children} of a node of degree {} doesn't point to it",
+                node.degree
+            );
+            ensure!(
+                self.compare.le(&node.item, &child.item),
+                "heap order",
+                "child {children} of a node of degree {} is smaller than it",
+                node.degree
+            );
+            size += self.check_tree(child, handles_found)?;
+            cursor = child.next_sibling.as_deref();
+        }
+        ensure!(
+            children == node.degree,
+            "child degrees",
+            "node of degree {} has {children} children",
+            node.degree
+        );
+        ensure!(
+            size == 1 << node.degree,
+            "binomial tree size",
+            "tree of degree {} has {size} nodes instead of {}",
+            node.degree,
+            1usize << node.degree
+        );
+        Ok(size)
+    }
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> CheckInvariants for BinomialHeap<I, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut len = 0;
+        let mut handles_found = 0;
+        let mut prev_degree = None;
+        let mut cursor = self.head.as_deref();
+        while let Some(root) = cursor {
+            if let Some(prev_degree) = prev_degree {
+                ensure!(
+                    prev_degree < root.degree,
+                    "root list degrees",
+                    "root of degree {} follows a root of degree {prev_degree}",
+                    root.degree
+                );
             }
+            ensure!(
+                root.parent.is_none(),
+                "parent pointers",
+                "root of degree {} has a parent",
+                root.degree
+            );
+            len += self.check_tree(root, &mut handles_found)?;
+            prev_degree = Some(root.degree);
+            cursor = root.next_sibling.as_deref();
         }
-        debug_assert!(list_tail.is_none());
-        *list_tail = Some(x);
+        check_len(self.len, len)?;
+        check_handle_count(&self.handles, handles_found)
+    }
+}
 
-        Self { head: list_head }
+/// Draws the list of roots side by side, with an edge from each node to its children
+impl<I: Debug, C> ToDot for BinomialHeap<I, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut roots = Vec::new();
+        let mut stack = Vec::new();
+        let mut cursor = self.head.as_deref();
+        while let Some(root) = cursor {
+            let id = dot.node(&root.item, &format!("degree {}", root.degree), "");
+            roots.push(id);
+            stack.push((root, id));
+            cursor = root.next_sibling.as_deref();
+        }
+        dot.list(&roots);
+        while let Some((node, id)) = stack.pop() {
+            let mut cursor = node.left_child.as_deref();
+            while let Some(child) = cursor {
+                let child_id = dot.node(&child.item, &format!("degree {}", child.degree), "");
+                dot.edge(id, child_id, "");
+                stack.push((child, child_id));
+                cursor = child.next_sibling.as_deref();
+            }
+        }
+        dot.finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tests;
+    use crate::{tests, ByKey, FnCompare, MaxOrder};
 
     #[test]
     fn binomial_insertion() {
-        let mut heap = BinomialHeap::make_heap();
+        let mut heap: BinomialHeap<i32> = BinomialHeap::make_heap();
         heap.insert(10);
         let head = heap.head.as_ref().unwrap();
         assert_eq!(head.degree, 0);
@@ -253,32 +686,40 @@ mod tests {
 
     #[test]
     fn binomial_heap_split() {
-        let x = Box::new(BinomialTreeNode {
+        let x = NodeBox::new(BinomialTreeNode {
             item: 1,
             degree: 0,
             next_sibling: None,
             left_child: None,
+            parent: None,
+            handle: None,
         });
-        let y = Box::new(BinomialTreeNode {
+        let y = NodeBox::new(BinomialTreeNode {
             item: 2,
             degree: 0,
             next_sibling: Some(x),
             left_child: None,
+            parent: None,
+            handle: None,
         });
-        let z = Box::new(BinomialTreeNode {
+        let z = NodeBox::new(BinomialTreeNode {
             item: 3,
             degree: 0,
             next_sibling: Some(y),
             left_child: None,
+            parent: None,
+            handle: None,
         });
         let parent = BinomialTreeNode {
             item: 0,
             degree: 3,
             next_sibling: None,
             left_child: Some(z),
+            parent: None,
+            handle: None,
         };
         let (num, rest) = parent.split_remove_root();
-        let first = rest.head.as_ref().unwrap();
+        let first = rest.as_ref().unwrap();
         let second = first.next_sibling.as_ref().unwrap();
         let third = second.next_sibling.as_ref().unwrap();
         assert_eq!(0, num);
@@ -294,6 +735,10 @@ mod tests {
         tests::simple::<HeapU32>();
     }
     #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
     fn empty_heap() {
         tests::empty_heap::<HeapU32>();
     }
@@ -333,4 +778,101 @@ mod tests {
     fn meld_two_empty_heaps() {
         tests::meld_two_empty_heaps::<HeapU32>();
     }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key() {
+        tests::decrease_key::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key_many() {
+        tests::decrease_key_many::<HeapU32>();
+    }
+    #[test]
+    fn delete() {
+        tests::delete::<HeapU32>();
+    }
+    #[test]
+    fn handles_after_meld() {
+        tests::handles_after_meld::<HeapU32>();
+    }
+    #[test]
+    fn stale_handles_rejected() {
+        tests::stale_handles_rejected::<HeapU32>();
+    }
+    #[test]
+    fn clone_handles() {
+        tests::clone_handles::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<BinomialHeap<u32, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| BinomialHeap::with_comparator(ByKey(|item: &(u32, char)| item.0)));
+    }
+    #[test]
+    fn fn_compare_decrease_key() {
+        tests::fn_compare_decrease_key(BinomialHeap::with_comparator(FnCompare(
+            |a: &u32, b: &u32| b.cmp(a),
+        )));
+    }
+
+    #[test]
+    fn broken_invariants_named() {
+        let heap = HeapU32::heapify(vec![0, 1, 2, 3, 4]);
+        assert_eq!(heap.check_invariants(), Ok(()));
+
+        // The roots have degrees 0 and 2
+        let mut broken = heap.clone();
+        let large = broken.head.as_mut().unwrap().next_sibling.as_mut().unwrap();
+        large.item = 10;
+        let err = broken.check_invariants().unwrap_err();
+        assert_eq!(err.invariant, "heap order");
+
+        let mut broken = heap.clone();
+        let mut small = broken.head.take().unwrap();
+        let mut large = small.next_sibling.take().unwrap();
+        small.next_sibling = None;
+        large.next_sibling = Some(small);
+        broken.head = Some(large);
+        let err = broken.check_invariants().unwrap_err();
+        assert_eq!(err.invariant, "root list degrees");
+        assert_eq!(
+            err.to_string(),
+            "root list degrees invariant broken: root of degree 0 follows a root of degree 2"
+        );
+
+        let mut broken = heap;
+        broken.len += 1;
+        let err = broken.check_invariants().unwrap_err();
+        assert_eq!(err.invariant, "length");
+    }
 }
@@ -0,0 +1,248 @@
+use std::{
+    fmt::{self, Debug},
+    slice,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{ensure, CheckInvariants, InvariantError};
+use crate::{dot::ToDot, Compare, DoubleEndedHeap, MinHeap, MinMaxHeap, MinOrder};
+
+/// Heap that keeps at most `capacity` items, the first ones in the order of its comparator. Once
+/// it is full, pushing an item evicts the last one, so streaming items through it keeps the
+/// top k of them in O(log k) per item and O(k) memory
+#[derive(Clone)]
+pub struct BoundedHeap<I, C = MinOrder> {
+    heap: MinMaxHeap<I, C>,
+    capacity: usize,
+}
+
+impl<I: Ord> BoundedHeap<I> {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_comparator(capacity, MinOrder)
+    }
+}
+
+impl<I, C: Compare<I>> BoundedHeap<I, C> {
+    pub fn with_comparator(capacity: usize, compare: C) -> Self {
+        Self {
+            heap: MinMaxHeap::with_comparator(compare),
+            capacity,
+        }
+    }
+
+    /// Most items the heap keeps
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.heap.len() >= self.capacity
+    }
+
+    /// Best item kept
+    pub fn peek_min(&self) -> Option<&I> {
+        self.heap.peek_min()
+    }
+
+    /// Worst item kept, the next one to be evicted
+    pub fn peek_max(&self) -> Option<&I> {
+        self.heap.peek_max()
+    }
+
+    /// Adds the item if the heap isn't full yet or the item comes before its max, which is then
+    /// evicted. Returns the item that doesn't fit, if any, which is the new item itself if it
+    /// doesn't come before the max, so of equal items the earlier ones are kept
+    pub fn push(&mut self, item: I) -> Option<I> {
+        if self.heap.len() < self.capacity {
+            self.heap.insert(item);
+            None
+        } else if self.capacity == 0 {
+            Some(item)
+        } else {
+            Some(self.heap.push_pop_max(item))
+        }
+    }
+
+    pub fn extract_min(&mut self) -> Option<I> {
+        self.heap.extract_min()
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> slice::Iter<'_, I> {
+        self.heap.iter()
+    }
+
+    /// Kept items, from best to worst
+    pub fn into_sorted_vec(self) -> Vec<I> {
+        self.heap.into_sorted_vec()
+    }
+
+    /// Keeps the best items of both heaps, up to the capacity and with the comparator of `heap_a`
+    pub fn merge(mut heap_a: Self, heap_b: Self) -> Self {
+        heap_a.extend(heap_b.heap);
+        heap_a
+    }
+}
+
+impl<I, C: Compare<I>> Extend<I> for BoundedHeap<I, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<I, C> IntoIterator for BoundedHeap<I, C> {
+    type Item = I;
+    type IntoIter = <MinMaxHeap<I, C> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.heap.into_iter()
+    }
+}
+
+impl<'a, I, C> IntoIterator for &'a BoundedHeap<I, C> {
+    type Item = &'a I;
+    type IntoIter = <&'a MinMaxHeap<I, C> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.heap).into_iter()
+    }
+}
+
+impl<I: Debug, C> Debug for BoundedHeap<I, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.heap).finish()
+    }
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> CheckInvariants for BoundedHeap<I, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        ensure!(
+            self.heap.len() <= self.capacity,
+            "capacity",
+            "{} items in a heap of capacity {}",
+            self.heap.len(),
+            self.capacity
+        );
+        self.heap.check_invariants()
+    }
+}
+
+/// Draws the min-max heap holding the items, whose max is the next item to be evicted
+impl<I: Debug, C> ToDot for BoundedHeap<I, C> {
+    fn to_dot(&self) -> String {
+        self.heap.to_dot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests, ByKey, MaxOrder};
+
+    #[test]
+    fn keeps_smallest() {
+        let mut heap = BoundedHeap::new(3);
+        assert_eq!(heap.capacity(), 3);
+        assert_eq!(heap.push(5), None);
+        assert_eq!(heap.push(1), None);
+        assert!(!heap.is_full());
+        assert_eq!(heap.push(9), None);
+        assert!(heap.is_full());
+        assert_eq!(heap.push(3), Some(9));
+        assert_eq!(heap.push(7), Some(7));
+        assert_eq!(heap.push(0), Some(5));
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.peek_min(), Some(&0));
+        assert_eq!(heap.peek_max(), Some(&3));
+        assert_eq!(heap.into_sorted_vec(), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn stream() {
+        let mut heap = BoundedHeap::new(100);
+        let len = tests::sized(100_000);
+        heap.extend((0..len).map(|i| (i * 7919) % len));
+        assert_eq!(heap.len(), 100);
+        assert_eq!(heap.into_sorted_vec(), (0..100).collect::<Vec<_>>());
+
+        let mut heap = BoundedHeap::with_comparator(10, MaxOrder);
+        heap.extend((0..1000u32).rev());
+        assert_eq!(heap.extract_min(), Some(999));
+        assert_eq!(heap.into_sorted_vec(), (990..999).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn keeps_earlier_equal_items() {
+        let mut heap = BoundedHeap::with_comparator(2, ByKey(|item: &(u32, char)| item.0));
+        heap.extend([(1, 'a'), (2, 'b'), (2, 'c'), (1, 'd')]);
+        assert_eq!(heap.into_sorted_vec(), vec![(1, 'a'), (1, 'd')]);
+
+        let mut heap = BoundedHeap::with_comparator(2, ByKey(|item: &(u32, char)| item.0));
+        heap.extend([(1, 'a'), (2, 'b')]);
+        assert_eq!(heap.push((2, 'c')), Some((2, 'c')));
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut heap = BoundedHeap::new(0);
+        assert!(heap.is_full());
+        assert_eq!(heap.push(1), Some(1));
+        assert!(heap.is_empty());
+        assert_eq!(heap.peek_min(), None);
+        assert_eq!(heap.peek_max(), None);
+        assert_eq!(heap.extract_min(), None);
+    }
+
+    #[test]
+    fn merge() {
+        let heap_a: BoundedHeap<u32> = {
+            let mut heap = BoundedHeap::new(4);
+            heap.extend([8, 2, 6, 4, 10]);
+            heap
+        };
+        let mut heap_b = BoundedHeap::new(10);
+        heap_b.extend([7, 1, 5, 3]);
+
+        let heap = BoundedHeap::merge(heap_a.clone(), heap_b.clone());
+        assert_eq!(heap.capacity(), 4);
+        assert_eq!(heap.into_sorted_vec(), vec![1, 2, 3, 4]);
+        let heap = BoundedHeap::merge(heap_b, heap_a);
+        assert_eq!(heap.capacity(), 10);
+        assert_eq!(heap.into_sorted_vec(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let heap = BoundedHeap::merge(BoundedHeap::<u32>::new(2), BoundedHeap::new(2));
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn iter_and_clear() {
+        let mut heap = BoundedHeap::new(3);
+        heap.extend([4, 2, 8, 6]);
+        let mut items: Vec<_> = heap.iter().copied().collect();
+        items.sort_unstable();
+        assert_eq!(items, vec![2, 4, 6]);
+        assert_eq!((&heap).into_iter().count(), 3);
+        assert_eq!(format!("{:?}", BoundedHeap::<u32>::new(2)), "[]");
+        let mut items: Vec<_> = heap.clone().into_iter().collect();
+        items.sort_unstable();
+        assert_eq!(items, vec![2, 4, 6]);
+        heap.clear();
+        assert!(heap.is_empty());
+        assert_eq!(heap.push(1), None);
+    }
+}
@@ -0,0 +1,667 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{
+    check_handle, check_handle_count, check_len, ensure, CheckInvariants, InvariantError,
+};
+use crate::{
+    arena::{IntoValues, NodeId, Slab, Values},
+    dot::{Dot, ToDot},
+    AddressableHeap, Handle, HandleError, MinHeap,
+};
+
+struct BucketNode<V> {
+    item: (usize, V),
+    /// Node before this one in its bucket
+    prev: Option<NodeId>,
+    /// Node after this one in its bucket
+    next: Option<NodeId>,
+    /// Handle of the item, if it was inserted with one
+    handle: Option<Handle>,
+}
+
+/// List of the nodes with one priority, from the first inserted to the last
+#[derive(Clone, Copy, Default)]
+struct Bucket {
+    head: Option<NodeId>,
+    tail: Option<NodeId>,
+}
+
+/// Queue of `(priority, value)` items with priorities in `0..P`, keeping one list of items per
+/// priority. Inserting is O(1) and extracting takes O(1) plus the number of empty buckets
+/// skipped to find the next min. Items with the same priority come out in the order they were
+/// inserted
+pub struct BucketQueue<V, const P: usize = 256> {
+    nodes: Slab<BucketNode<V>>,
+    buckets: Vec<Bucket>,
+    /// Lowest priority whose bucket isn't empty, or P if the queue is empty
+    min: usize,
+    /// Node holding the item of each handle in the queue
+    handles: HashMap<Handle, NodeId>,
+}
+
+impl<V, const P: usize> Default for BucketQueue<V, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, const P: usize> BucketQueue<V, P> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Slab::default(),
+            buckets: vec![Bucket::default(); P],
+            min: P,
+            handles: HashMap::new(),
+        }
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            nodes: self.nodes.values(),
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<V> {
+        let nodes = mem::take(&mut self.nodes);
+        self.clear();
+        IntoIter {
+            nodes: nodes.into_values(),
+        }
+    }
+
+    /// Adds a node for the item to the back of the bucket of its priority
+    fn push(&mut self, item: (usize, V), handle: Option<Handle>) -> NodeId {
+        let priority = item.0;
+        assert!(
+            priority < P,
+            "priority {priority} is out of the queue's range 0..{P}"
+        );
+        let id = self.nodes.insert(BucketNode {
+            item,
+            prev: None,
+            next: None,
+            handle,
+        });
+        self.push_back(id);
+        id
+    }
+
+    /// Adds the node of the bucket of its priority
+    fn push_back(&mut self, id: NodeId) {
+        let priority = self.nodes[id].item.0;
+        let bucket = &mut self.buckets[priority];
+        self.nodes[id].prev = bucket.tail;
+        self.nodes[id].next = None;
+        match bucket.tail {
+            Some(tail) => self.nodes[tail].next = Some(id),
+            None => bucket.head = Some(id),
+        }
+        bucket.tail = Some(id);
+        self.min = self.min.min(priority);
+    }
+
+    /// Takes the node out of its bucket, moving `min` up if the bucket was the min one and is
+    /// now empty
+    fn unlink(&mut self, id: NodeId) {
+        let BucketNode {
+            item: (priority, _),
+            prev,
+            next,
+            ..
+        } = self.nodes[id];
+        let bucket = &mut self.buckets[priority];
+        match prev {
+            Some(prev) => self.nodes[prev].next = next,
+            None => bucket.head = next,
+        }
+        match next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => bucket.tail = prev,
+        }
+        if bucket.head.is_none() && priority == self.min {
+            self.min = (priority + 1..P)
+                .find(|&priority| self.buckets[priority].head.is_some())
+                .unwrap_or(P);
+        }
+    }
+
+    fn remove(&mut self, id: NodeId) -> (usize, V) {
+        self.unlink(id);
+        let node = self.nodes.remove(id);
+        if let Some(handle) = node.handle {
+            self.handles.remove(&handle);
+        }
+        node.item
+    }
+}
+
+impl<V, const P: usize> MinHeap for BucketQueue<V, P> {
+    type Item = (usize, V);
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        let head = self.buckets.get(self.min)?.head?;
+        Some(&self.nodes[head].item)
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        let head = self.buckets.get(self.min)?.head?;
+        Some(self.remove(head))
+    }
+
+    /// Panics if the priority isn't in `0..P`
+    fn insert(&mut self, item: Self::Item) {
+        self.push(item, None);
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.buckets.fill(Bucket::default());
+        self.min = P;
+        self.handles.clear();
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        let mut queue = Self::default();
+        queue.extend(items);
+        queue
+    }
+
+    /// Items of `heap_a` come out before the items of `heap_b` with the same priority. Takes
+    /// O(P) plus the time to move the nodes of the smaller queue into the other one
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        // Move the nodes of the smaller slab into the bigger one
+        let swapped = heap_a.nodes.slots_len() < heap_b.nodes.slots_len();
+        if swapped {
+            mem::swap(&mut heap_a, &mut heap_b);
+        }
+        let offset = heap_a.nodes.append(heap_b.nodes, |node, offset| {
+            node.prev = node.prev.map(|id| id + offset);
+            node.next = node.next.map(|id| id + offset);
+        });
+        heap_a.handles.extend(
+            heap_b
+                .handles
+                .into_iter()
+                .map(|(handle, id)| (handle, id + offset)),
+        );
+
+        for (bucket_a, bucket_b) in heap_a.buckets.iter_mut().zip(heap_b.buckets) {
+            let bucket_b = Bucket {
+                head: bucket_b.head.map(|id| id + offset),
+                tail: bucket_b.tail.map(|id| id + offset),
+            };
+            // The lists of the queue that was heap_a go first
+            let (front, back) = if swapped {
+                (bucket_b, *bucket_a)
+            } else {
+                (*bucket_a, bucket_b)
+            };
+            *bucket_a = match (front.tail, back.head) {
+                (Some(tail), Some(head)) => {
+                    heap_a.nodes[tail].next = Some(head);
+                    heap_a.nodes[head].prev = Some(tail);
+                    Bucket {
+                        head: front.head,
+                        tail: back.tail,
+                    }
+                }
+                (Some(_), None) => front,
+                (None, _) => back,
+            };
+        }
+        heap_a.min = heap_a.min.min(heap_b.min);
+        heap_a
+    }
+}
+
+impl<V, const P: usize> AddressableHeap for BucketQueue<V, P> {
+    /// Panics if the priority isn't in `0..P`
+    fn insert_with_handle(&mut self, item: Self::Item) -> Handle {
+        let handle = Handle::new();
+        let id = self.push(item, Some(handle));
+        self.handles.insert(handle, id);
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<&Self::Item> {
+        self.handles.get(&handle).map(|&id| &self.nodes[id].item)
+    }
+
+    /// The item moves to the back of the bucket of its new priority, unless the priority stays
+    /// the same
+    fn decrease_key(&mut self, handle: Handle, item: Self::Item) -> Result<(), HandleError> {
+        let id = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        let priority = self.nodes[id].item.0;
+        if item.0 > priority {
+            return Err(HandleError::KeyIncreased);
+        }
+        if item.0 == priority {
+            self.nodes[id].item = item;
+        } else {
+            self.unlink(id);
+            self.nodes[id].item = item;
+            self.push_back(id);
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, handle: Handle) -> Result<Self::Item, HandleError> {
+        let id = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        Ok(self.remove(id))
+    }
+}
+
+/// Iterator over the items of a [`BucketQueue`] in arbitrary order
+pub struct Iter<'a, V> {
+    nodes: Values<'a, BucketNode<V>>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = &'a (usize, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<V> ExactSizeIterator for Iter<'_, V> {}
+impl<V> FusedIterator for Iter<'_, V> {}
+
+/// Owning iterator over the items of a [`BucketQueue`] in arbitrary order
+pub struct IntoIter<V> {
+    nodes: IntoValues<BucketNode<V>>,
+}
+
+impl<V> Iterator for IntoIter<V> {
+    type Item = (usize, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<V> ExactSizeIterator for IntoIter<V> {}
+impl<V> FusedIterator for IntoIter<V> {}
+
+impl<V, const P: usize> IntoIterator for BucketQueue<V, P> {
+    type Item = (usize, V);
+    type IntoIter = IntoIter<V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            nodes: self.nodes.into_values(),
+        }
+    }
+}
+
+impl<'a, V, const P: usize> IntoIterator for &'a BucketQueue<V, P> {
+    type Item = &'a (usize, V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V, const P: usize> FromIterator<(usize, V)> for BucketQueue<V, P> {
+    fn from_iter<T: IntoIterator<Item = (usize, V)>>(iter: T) -> Self {
+        let mut queue = Self::new();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<V, const P: usize> From<Vec<(usize, V)>> for BucketQueue<V, P> {
+    fn from(items: Vec<(usize, V)>) -> Self {
+        Self::from_iter(items)
+    }
+}
+
+impl<V, const P: usize, const N: usize> From<[(usize, V); N]> for BucketQueue<V, P> {
+    fn from(items: [(usize, V); N]) -> Self {
+        Self::from_iter(items)
+    }
+}
+
+impl<V, const P: usize> Extend<(usize, V)> for BucketQueue<V, P> {
+    /// Panics if a priority isn't in `0..P`, like `insert`
+    fn extend<T: IntoIterator<Item = (usize, V)>>(&mut self, iter: T) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<V: Clone, const P: usize> Clone for BucketQueue<V, P> {
+    /// The clone has the same items in the same order but none of the handles, which keep
+    /// referring to the items in the original queue
+    fn clone(&self) -> Self {
+        let mut queue = Self::new();
+        for bucket in &self.buckets {
+            let mut cursor = bucket.head;
+            while let Some(id) = cursor {
+                queue.insert(self.nodes[id].item.clone());
+                cursor = self.nodes[id].next;
+            }
+        }
+        queue
+    }
+}
+
+impl<V: Debug, const P: usize> Debug for BucketQueue<V, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Queues are equal if they hold the same items in the same order
+impl<V: PartialEq, const P: usize> PartialEq for BucketQueue<V, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self.buckets.iter().zip(&other.buckets).all(|(a, b)| {
+                let mut cursor_a = a.head;
+                let mut cursor_b = b.head;
+                loop {
+                    match (cursor_a, cursor_b) {
+                        (Some(id_a), Some(id_b)) => {
+                            if self.nodes[id_a].item != other.nodes[id_b].item {
+                                return false;
+                            }
+                            cursor_a = self.nodes[id_a].next;
+                            cursor_b = other.nodes[id_b].next;
+                        }
+                        (None, None) => return true,
+                        _ => return false,
+                    }
+                }
+            })
+    }
+}
+
+impl<V: Eq, const P: usize> Eq for BucketQueue<V, P> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<V, const P: usize> CheckInvariants for BucketQueue<V, P> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut len = 0;
+        let mut handles_found = 0;
+        for (priority, bucket) in self.buckets.iter().enumerate() {
+            let mut prev = None;
+            let mut cursor = bucket.head;
+            while let Some(id) = cursor {
+                let node = &self.nodes[id];
+                ensure!(
+                    node.item.0 == priority,
+                    "buckets",
+                    "item of priority {} is in bucket {priority}",
+                    node.item.0
+                );
+                ensure!(
+                    node.prev == prev,
+                    "bucket lists",
+                    "a node in bucket {priority} doesn't point to the node before it"
+                );
+                check_handle(&self.handles, node.handle, id, &mut handles_found)?;
+                len += 1;
+                prev = Some(id);
+                cursor = node.next;
+            }
+            ensure!(
+                bucket.tail == prev,
+                "bucket lists",
+                "the tail of bucket {priority} isn't its last node"
+            );
+        }
+        let min = (0..P)
+            .find(|&priority| self.buckets[priority].head.is_some())
+            .unwrap_or(P);
+        ensure!(
+            self.min == min,
+            "min bucket",
+            "min is {} but the first bucket that isn't empty is {min}",
+            self.min
+        );
+        check_len(self.nodes.len(), len)?;
+        check_handle_count(&self.handles, handles_found)
+    }
+}
+
+/// Draws each bucket that isn't empty above its list of items, marking the one `min` points to
+impl<V: Debug, const P: usize> ToDot for BucketQueue<V, P> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        for (priority, bucket) in self.buckets.iter().enumerate() {
+            let Some(head) = bucket.head else {
+                continue;
+            };
+            let bucket_id = dot.label(&format!("priority {priority}"));
+            if priority == self.min {
+                dot.marker("min", bucket_id);
+            }
+            let mut items = Vec::new();
+            let mut cursor = Some(head);
+            while let Some(id) = cursor {
+                items.push(dot.node(&self.nodes[id].item, "", ""));
+                cursor = self.nodes[id].next;
+            }
+            dot.edge(bucket_id, items[0], "");
+            dot.list(&items);
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests;
+
+    type QueueChar = BucketQueue<char, 16>;
+
+    fn extract_all<V, const P: usize>(queue: &mut BucketQueue<V, P>) -> Vec<(usize, V)> {
+        queue.drain_sorted().collect()
+    }
+
+    #[test]
+    fn simple() {
+        let mut queue = QueueChar::new();
+        assert_eq!(queue.peek_min(), None);
+        assert_eq!(queue.extract_min(), None);
+        queue.insert((3, 'c'));
+        queue.insert((1, 'a'));
+        queue.insert((2, 'b'));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.peek_min(), Some(&(1, 'a')));
+        assert_eq!(queue.extract_min(), Some((1, 'a')));
+        queue.insert((0, 'z'));
+        assert_eq!(extract_all(&mut queue), vec![(0, 'z'), (2, 'b'), (3, 'c')]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn differential() {
+        tests::differential_with::<BucketQueue<()>>(tests::Config {
+            max_key: 256,
+            ..tests::Config::default()
+        });
+    }
+
+    #[test]
+    fn fifo_ties() {
+        let mut queue = QueueChar::from([(5, 'a'), (2, 'b'), (5, 'c'), (2, 'd'), (5, 'e')]);
+        assert_eq!(queue.extract_min(), Some((2, 'b')));
+        queue.insert((2, 'f'));
+        assert_eq!(
+            extract_all(&mut queue),
+            vec![(2, 'd'), (2, 'f'), (5, 'a'), (5, 'c'), (5, 'e')]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "priority 16 is out of the queue's range 0..16")]
+    fn priority_out_of_range() {
+        QueueChar::new().insert((16, 'x'));
+    }
+
+    #[test]
+    fn decrease_key_and_delete() {
+        let mut queue = QueueChar::new();
+        let a = queue.insert_with_handle((9, 'a'));
+        let b = queue.insert_with_handle((4, 'b'));
+        let c = queue.insert_with_handle((4, 'c'));
+        queue.insert((9, 'd'));
+
+        // Moves to the back of bucket 4
+        assert_eq!(queue.decrease_key(a, (4, 'A')), Ok(()));
+        // Keeps its place
+        assert_eq!(queue.decrease_key(c, (4, 'C')), Ok(()));
+        assert_eq!(
+            queue.decrease_key(b, (5, 'b')),
+            Err(HandleError::KeyIncreased)
+        );
+        assert_eq!(queue.get(a), Some(&(4, 'A')));
+
+        assert_eq!(queue.delete(b), Ok((4, 'b')));
+        assert_eq!(queue.delete(b), Err(HandleError::InvalidHandle));
+        assert_eq!(queue.get(b), None);
+        assert_eq!(queue.peek_min(), Some(&(4, 'C')));
+        assert_eq!(queue.extract_min(), Some((4, 'C')));
+        assert_eq!(
+            queue.decrease_key(c, (0, 'c')),
+            Err(HandleError::InvalidHandle)
+        );
+        assert_eq!(queue.decrease_key(a, (0, 'a')), Ok(()));
+        assert_eq!(extract_all(&mut queue), vec![(0, 'a'), (9, 'd')]);
+    }
+
+    #[test]
+    fn delete_moves_min() {
+        let mut queue = QueueChar::new();
+        let a = queue.insert_with_handle((1, 'a'));
+        queue.insert((7, 'b'));
+        assert_eq!(queue.delete(a), Ok((1, 'a')));
+        assert_eq!(queue.peek_min(), Some(&(7, 'b')));
+    }
+
+    #[test]
+    fn meld_keeps_fifo_order() {
+        // The bigger queue is the second one, so its nodes stay in place
+        let mut queue_a = QueueChar::from([(3, 'a'), (1, 'b')]);
+        let handle_a = queue_a.insert_with_handle((3, 'c'));
+        let mut queue_b = QueueChar::from([(3, 'd'), (2, 'e'), (3, 'f'), (8, 'g')]);
+        let handle_b = queue_b.insert_with_handle((8, 'h'));
+
+        let mut queue = QueueChar::meld(queue_a, queue_b);
+        assert_eq!(queue.len(), 8);
+        assert_eq!(queue.get(handle_a), Some(&(3, 'c')));
+        assert_eq!(queue.decrease_key(handle_b, (3, 'H')), Ok(()));
+        assert_eq!(
+            extract_all(&mut queue),
+            vec![
+                (1, 'b'),
+                (2, 'e'),
+                (3, 'a'),
+                (3, 'c'),
+                (3, 'd'),
+                (3, 'f'),
+                (3, 'H'),
+                (8, 'g')
+            ]
+        );
+
+        let queue = QueueChar::meld(QueueChar::from([(4, 'a'), (4, 'b')]), QueueChar::new());
+        assert_eq!(queue, QueueChar::from([(4, 'a'), (4, 'b')]));
+        let queue = QueueChar::meld(QueueChar::new(), QueueChar::from([(4, 'a')]));
+        assert_eq!(queue.peek_min(), Some(&(4, 'a')));
+        assert!(QueueChar::meld(QueueChar::new(), QueueChar::new()).is_empty());
+    }
+
+    #[test]
+    fn large_input() {
+        let mut queue = BucketQueue::<u32>::new();
+        let len = tests::sized(10_000);
+        let handles: Vec<_> = (0..len)
+            .map(|i| queue.insert_with_handle((255, i)))
+            .collect();
+        for (i, &handle) in handles.iter().enumerate() {
+            let priority = (i * 7919) % 255;
+            assert_eq!(queue.decrease_key(handle, (priority, i as u32)), Ok(()));
+        }
+        let mut extracted = extract_all(&mut queue);
+        assert_eq!(extracted.len(), len as usize);
+        assert!(extracted.is_sorted());
+        extracted.sort_unstable_by_key(|item| item.1);
+        assert!(extracted
+            .iter()
+            .enumerate()
+            .all(|(i, item)| item.0 == (i * 7919) % 255));
+    }
+
+    #[test]
+    fn std_traits() {
+        let mut queue: QueueChar = [(2, 'a'), (1, 'b'), (2, 'c')].into_iter().collect();
+        queue.extend([(0, 'd')]);
+        let handle = queue.insert_with_handle((1, 'e'));
+        let clone = queue.clone();
+        assert_eq!(clone, queue);
+        assert_eq!(clone.get(handle), None);
+        assert_ne!(
+            clone,
+            QueueChar::from([(0, 'd'), (1, 'e'), (1, 'b'), (2, 'a'), (2, 'c')])
+        );
+        assert_eq!(format!("{:?}", QueueChar::from([(3, 'x')])), "[(3, 'x')]");
+
+        assert_eq!(queue.iter().len(), 5);
+        assert_eq!((&queue).into_iter().count(), 5);
+        let mut drained: Vec<_> = queue.drain().collect();
+        drained.sort_unstable();
+        assert_eq!(
+            drained,
+            vec![(0, 'd'), (1, 'b'), (1, 'e'), (2, 'a'), (2, 'c')]
+        );
+        assert!(queue.is_empty());
+        assert_eq!(queue.get(handle), None);
+        assert_eq!(clone.into_iter().len(), 5);
+    }
+
+    #[test]
+    fn clear_and_reuse() {
+        let mut queue = QueueChar::from([(5, 'a'), (6, 'b')]);
+        let handle = queue.insert_with_handle((7, 'c'));
+        queue.clear();
+        assert!(queue.is_empty());
+        assert_eq!(queue.peek_min(), None);
+        assert_eq!(queue.get(handle), None);
+        queue.insert((9, 'd'));
+        assert_eq!(queue.extract_min(), Some((9, 'd')));
+    }
+}
@@ -0,0 +1,59 @@
+use std::cmp::Ordering;
+
+/// Order used by a heap to decide which item is the "min"
+pub trait Compare<T> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+
+    fn lt(&self, a: &T, b: &T) -> bool {
+        self.compare(a, b) == Ordering::Less
+    }
+    fn le(&self, a: &T, b: &T) -> bool {
+        self.compare(a, b) != Ordering::Greater
+    }
+    fn gt(&self, a: &T, b: &T) -> bool {
+        self.compare(a, b) == Ordering::Greater
+    }
+    fn ge(&self, a: &T, b: &T) -> bool {
+        self.compare(a, b) != Ordering::Less
+    }
+}
+
+/// Orders items by their [`Ord`] implementation, so the heap is a min-heap
+#[derive(Clone, Copy, Default, Debug)]
+pub struct MinOrder;
+
+/// Orders items by the reverse of their [`Ord`] implementation, so the heap is a max-heap
+#[derive(Clone, Copy, Default, Debug)]
+pub struct MaxOrder;
+
+/// Orders items by the key the function returns for each of them
+#[derive(Clone, Copy, Debug)]
+pub struct ByKey<F>(pub F);
+
+/// Orders items with a comparison function
+#[derive(Clone, Copy, Debug)]
+pub struct FnCompare<F>(pub F);
+
+impl<T: Ord> Compare<T> for MinOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<T: Ord> Compare<T> for MaxOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        b.cmp(a)
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> Compare<T> for ByKey<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> Compare<T> for FnCompare<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a, b)
+    }
+}
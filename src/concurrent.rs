@@ -0,0 +1,383 @@
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    hash::{BuildHasher, RandomState},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard, TryLockError,
+    },
+};
+
+use crate::{
+    dot::{Dot, ToDot},
+    BinaryHeap, Compare, MinHeap, MinOrder,
+};
+
+/// Heap shared between threads behind one lock, so every operation sees all the items and
+/// `extract_min` always returns the min, at the cost of running one operation at a time
+pub struct LockedHeap<H> {
+    heap: Mutex<H>,
+}
+
+impl<H: MinHeap + Default> Default for LockedHeap<H> {
+    fn default() -> Self {
+        Self::new(H::default())
+    }
+}
+
+impl<H: MinHeap> LockedHeap<H> {
+    pub fn new(heap: H) -> Self {
+        Self {
+            heap: Mutex::new(heap),
+        }
+    }
+
+    /// Locks the heap, to run several operations without other threads in between
+    pub fn lock(&self) -> MutexGuard<'_, H> {
+        self.heap
+            .lock()
+            .expect("a thread panicked while changing the heap")
+    }
+
+    pub fn insert(&self, item: H::Item) {
+        self.lock().insert(item);
+    }
+
+    pub fn extract_min(&self) -> Option<H::Item> {
+        self.lock().extract_min()
+    }
+
+    /// Copy of the min, since other threads can extract it once the lock is released
+    pub fn peek_min(&self) -> Option<H::Item>
+    where
+        H::Item: Clone,
+    {
+        self.lock().peek_min().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn into_inner(self) -> H {
+        self.heap
+            .into_inner()
+            .expect("a thread panicked while changing the heap")
+    }
+}
+
+thread_local! {
+    /// State of the xorshift generator each thread picks queues with
+    static RNG_STATE: Cell<u64> = Cell::new(RandomState::new().hash_one(0u64) | 1);
+}
+
+/// Random index below `len`, from a generator local to the thread so picking a queue doesn't
+/// need any synchronization
+fn random_index(len: usize) -> usize {
+    RNG_STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        (x % len as u64) as usize
+    })
+}
+
+/// Relaxed concurrent priority queue made of several binary heaps with a lock each. `insert`
+/// goes to a random heap that isn't locked, and `extract_min` takes the smaller min of two
+/// random heaps, so threads rarely wait on each other. The price is that `extract_min` returns
+/// one of the smallest items rather than always the min, since with `q` heaps the items close
+/// to the min are spread over all of them
+pub struct MultiQueue<I, C = MinOrder> {
+    queues: Box<[Mutex<BinaryHeap<I, C>>]>,
+    len: AtomicUsize,
+    compare: C,
+}
+
+impl<I, C: Compare<I> + Clone + Default> MultiQueue<I, C> {
+    /// Queue made of `queues` heaps, usually a small multiple of the number of threads using it.
+    /// Panics if `queues` is 0
+    pub fn new(queues: usize) -> Self {
+        Self::with_comparator(queues, C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Clone> MultiQueue<I, C> {
+    /// Panics if `queues` is 0
+    pub fn with_comparator(queues: usize, compare: C) -> Self {
+        assert!(queues > 0, "a multiqueue needs at least one queue");
+        Self {
+            queues: (0..queues)
+                .map(|_| Mutex::new(BinaryHeap::with_comparator(compare.clone())))
+                .collect(),
+            len: AtomicUsize::new(0),
+            compare,
+        }
+    }
+
+    /// Number of heaps the items are spread over
+    pub fn queues(&self) -> usize {
+        self.queues.len()
+    }
+
+    fn lock(&self, i: usize) -> MutexGuard<'_, BinaryHeap<I, C>> {
+        self.queues[i]
+            .lock()
+            .expect("a thread panicked while changing the queue")
+    }
+
+    pub fn insert(&self, item: I) {
+        // Give up on finding an unlocked heap after a few tries and wait for one
+        for _ in 0..self.queues.len() {
+            match self.queues[random_index(self.queues.len())].try_lock() {
+                Ok(mut queue) => {
+                    queue.insert(item);
+                    self.len.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Poisoned(_)) => {
+                    panic!("a thread panicked while changing the queue")
+                }
+            }
+        }
+        // Count the item while still holding the lock, otherwise another thread could extract it
+        // and decrement `len` below zero first
+        let mut queue = self.lock(random_index(self.queues.len()));
+        queue.insert(item);
+        self.len.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Extracts the smaller min of two random heaps, or of the first non-empty heap if both are
+    /// empty. Returns `None` only if every heap was empty when it was looked at
+    pub fn extract_min(&self) -> Option<I> {
+        if self.len.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        let item = self
+            .extract_two_choices()
+            .or_else(|| (0..self.queues.len()).find_map(|i| self.lock(i).extract_min()))?;
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        Some(item)
+    }
+
+    fn extract_two_choices(&self) -> Option<I> {
+        let i = random_index(self.queues.len());
+        let j = random_index(self.queues.len());
+        if i == j {
+            return self.lock(i).extract_min();
+        }
+        // Lock in index order so two threads can't wait on each other
+        let (mut queue_a, mut queue_b) = if i < j {
+            (self.lock(i), self.lock(j))
+        } else {
+            let queue_b = self.lock(j);
+            (self.lock(i), queue_b)
+        };
+        match (queue_a.peek_min(), queue_b.peek_min()) {
+            (Some(a), Some(b)) if self.compare.gt(a, b) => queue_b.extract_min(),
+            (Some(_), _) => queue_a.extract_min(),
+            (None, _) => queue_b.extract_min(),
+        }
+    }
+
+    /// Number of items, which other threads may be changing
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Melds all the heaps into one, with every item in strict order again
+    pub fn into_heap(self) -> BinaryHeap<I, C> {
+        let mut heap = BinaryHeap::with_comparator(self.compare);
+        for queue in self.queues.into_vec() {
+            let queue = queue
+                .into_inner()
+                .expect("a thread panicked while changing the queue");
+            heap = BinaryHeap::meld(heap, queue);
+        }
+        heap
+    }
+}
+
+/// Locks the heap while drawing it
+impl<H: ToDot> ToDot for LockedHeap<H> {
+    fn to_dot(&self) -> String {
+        self.heap
+            .lock()
+            .expect("a thread panicked while changing the heap")
+            .to_dot()
+    }
+}
+
+/// Draws each heap in its own box, locking one at a time, so items moved by other threads while
+/// drawing can show up twice or not at all
+impl<I: Debug, C: Compare<I> + Clone> ToDot for MultiQueue<I, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        for i in 0..self.queues.len() {
+            let queue = self.lock(i);
+            dot.cluster(&format!("queue {i}"), |dot| queue.write_dot(dot));
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{tests, LeftistHeap, MaxOrder};
+
+    const THREADS: u32 = 4;
+    const PER_THREAD: u32 = tests::sized(2000);
+
+    #[test]
+    fn locked_simple() {
+        let heap = LockedHeap::<BinaryHeap<u32>>::default();
+        assert!(heap.is_empty());
+        heap.insert(3);
+        heap.insert(1);
+        heap.insert(2);
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.peek_min(), Some(1));
+        assert_eq!(heap.extract_min(), Some(1));
+        {
+            let mut heap = heap.lock();
+            heap.insert(0);
+            assert_eq!(heap.extract_min(), Some(0));
+        }
+        assert_eq!(heap.into_inner().into_sorted_vec(), vec![2, 3]);
+    }
+
+    #[test]
+    fn locked_threads() {
+        let heap = LockedHeap::new(LeftistHeap::<u32>::default());
+        thread::scope(|scope| {
+            for t in 0..THREADS {
+                let heap = &heap;
+                scope.spawn(move || {
+                    for i in 0..PER_THREAD {
+                        heap.insert(i * THREADS + t);
+                    }
+                });
+            }
+        });
+        let extracted: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut extracted = Vec::new();
+                        while let Some(item) = heap.extract_min() {
+                            extracted.push(item);
+                        }
+                        extracted
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect()
+        });
+        // Each thread saw its items in increasing order
+        for items in &extracted {
+            assert!(items.is_sorted());
+        }
+        let mut all: Vec<_> = extracted.into_iter().flatten().collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn multi_queue_single_queue_is_strict() {
+        let queue = MultiQueue::<u32>::new(1);
+        for i in [5, 1, 4, 2, 3] {
+            queue.insert(i);
+        }
+        assert_eq!(queue.len(), 5);
+        for i in 1..=5 {
+            assert_eq!(queue.extract_min(), Some(i));
+        }
+        assert_eq!(queue.extract_min(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn multi_queue_keeps_every_item() {
+        let queue = MultiQueue::<u32>::new(8);
+        assert_eq!(queue.queues(), 8);
+        for i in 0..1000 {
+            queue.insert(i);
+        }
+        let mut extracted: Vec<_> = (0..600).map(|_| queue.extract_min().unwrap()).collect();
+        assert_eq!(queue.len(), 400);
+        // Relaxed, but still much closer to the min than to the max
+        assert!(extracted[..100].iter().all(|&item| item < 500));
+
+        extracted.extend(queue.into_heap().into_sorted_vec());
+        extracted.sort_unstable();
+        assert_eq!(extracted, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn multi_queue_empty_heaps_skipped() {
+        // Most heaps are empty, so the two random ones usually are too
+        let queue = MultiQueue::<u32, MaxOrder>::new(64);
+        queue.insert(7);
+        queue.insert(9);
+        let mut extracted = vec![queue.extract_min().unwrap(), queue.extract_min().unwrap()];
+        extracted.sort_unstable();
+        assert_eq!(extracted, vec![7, 9]);
+        assert_eq!(queue.extract_min(), None);
+    }
+
+    #[test]
+    fn multi_queue_threads() {
+        let queue = MultiQueue::<u32>::new(2 * THREADS as usize);
+        let extracted: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..THREADS)
+                .map(|t| {
+                    let queue = &queue;
+                    scope.spawn(move || {
+                        let mut extracted = Vec::new();
+                        for i in 0..PER_THREAD {
+                            queue.insert(i * THREADS + t);
+                            if i % 2 == 1 {
+                                extracted.extend(queue.extract_min());
+                            }
+                        }
+                        extracted
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+        assert_eq!(
+            extracted.len() + queue.len(),
+            (THREADS * PER_THREAD) as usize
+        );
+        let mut all: Vec<_> = extracted;
+        all.extend(queue.into_heap());
+        all.sort_unstable();
+        assert_eq!(all, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "a multiqueue needs at least one queue")]
+    fn multi_queue_without_queues() {
+        MultiQueue::<u32>::new(0);
+    }
+}
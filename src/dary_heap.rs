@@ -0,0 +1,591 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    slice, vec,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{
+    check_handle, check_handle_count, ensure, CheckInvariants, InvariantError,
+};
+use crate::{
+    dot::{Dot, ToDot},
+    iter::same_items,
+    stats::count,
+    AddressableHeap, Compare, Handle, HandleError, MinHeap, MinOrder,
+};
+
+/// Heap stored as a complete tree where each node has `D` children, laid out level by level in
+/// an array. More children make the tree shallower, so sifting up does fewer comparisons and
+/// sifting down visits fewer cache lines, at the cost of comparing more children on each level
+pub struct DaryHeap<I, const D: usize, C = MinOrder> {
+    array: Vec<I>,
+    /// Handle of each item in `array`, left empty until the first handle is requested
+    handles: Vec<Option<Handle>>,
+    /// Index in `array` of each item that has a handle
+    positions: HashMap<Handle, usize>,
+    compare: C,
+}
+
+#[inline(always)]
+const fn parent<const D: usize>(i: usize) -> usize {
+    const { assert!(D >= 2, "d-ary heap nodes need at least 2 children") };
+    (i - 1) / D
+}
+#[inline(always)]
+const fn first_child<const D: usize>(i: usize) -> usize {
+    const { assert!(D >= 2, "d-ary heap nodes need at least 2 children") };
+    D * i + 1
+}
+#[inline(always)]
+fn min_child<I, C: Compare<I>, const D: usize>(items: &[I], i: usize, compare: &C) -> usize {
+    let first = first_child::<D>(i);
+    if first >= items.len() {
+        return i;
+    }
+    let end = items.len().min(first + D);
+    (first + 1..end).fold(first, |min, child| {
+        if compare.lt(&items[child], &items[min]) {
+            child
+        } else {
+            min
+        }
+    })
+}
+
+impl<I, const D: usize, C: Compare<I>> DaryHeap<I, D, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            array: Vec::new(),
+            handles: Vec::new(),
+            positions: HashMap::new(),
+            compare,
+        }
+    }
+
+    /// Empty heap that can hold `capacity` items before reallocating
+    pub fn with_capacity(capacity: usize) -> Self
+    where
+        C: Default,
+    {
+        Self::with_capacity_and_comparator(capacity, C::default())
+    }
+
+    pub fn with_capacity_and_comparator(capacity: usize, compare: C) -> Self {
+        Self {
+            array: Vec::with_capacity(capacity),
+            ..Self::with_comparator(compare)
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.array.capacity()
+    }
+
+    /// Reserves space for at least `additional` more items
+    pub fn reserve(&mut self, additional: usize) {
+        self.array.reserve(additional);
+        if !self.handles.is_empty() {
+            self.handles.reserve(additional);
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.array.shrink_to_fit();
+        self.handles.shrink_to_fit();
+        self.positions.shrink_to_fit();
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> slice::Iter<'_, I> {
+        self.array.iter()
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> vec::Drain<'_, I> {
+        self.handles.clear();
+        self.positions.clear();
+        self.array.drain(..)
+    }
+
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        Self::from_entries(items, Vec::new(), compare)
+    }
+
+    fn from_entries(array: Vec<I>, handles: Vec<Option<Handle>>, compare: C) -> Self {
+        debug_assert!(handles.is_empty() || handles.len() == array.len());
+        let positions = handles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, handle)| handle.map(|handle| (handle, i)))
+            .collect();
+        let mut heap = Self {
+            array,
+            handles,
+            positions,
+            compare,
+        };
+        heap.rebuild();
+        heap
+    }
+
+    /// Restores the heap property of the whole array
+    fn rebuild(&mut self) {
+        if self.array.len() > 1 {
+            for i in (0..=parent::<D>(self.array.len() - 1)).rev() {
+                self.sift_down(i);
+            }
+        }
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.array.swap(i, j);
+        count!(swaps);
+        if !self.handles.is_empty() {
+            self.handles.swap(i, j);
+            for k in [i, j] {
+                if let Some(handle) = self.handles[k] {
+                    self.positions.insert(handle, k);
+                }
+            }
+        }
+    }
+
+    /// Removes the item at index `i` and restores the heap property
+    fn remove(&mut self, i: usize) -> I {
+        let last = self.array.len() - 1;
+        self.swap(i, last);
+        let item = self.array.pop().unwrap();
+        if let Some(handle) = self.handles.pop().flatten() {
+            self.positions.remove(&handle);
+        }
+        if i < self.array.len() {
+            let i = self.sift_up(i);
+            self.sift_down(i);
+        }
+        item
+    }
+
+    /// Fills in `handles` so it can be kept parallel to `array`
+    fn enable_handles(&mut self) {
+        if self.handles.is_empty() {
+            self.handles.resize(self.array.len(), None);
+        }
+    }
+
+    fn sift_up(&mut self, mut i: usize) -> usize {
+        debug_assert!(i < self.array.len());
+        while i != 0 && self.compare.gt(&self.array[parent::<D>(i)], &self.array[i]) {
+            self.swap(parent::<D>(i), i);
+            i = parent::<D>(i);
+        }
+        i
+    }
+    fn sift_down(&mut self, mut i: usize) {
+        debug_assert!(i < self.array.len());
+        let mut child = min_child::<_, _, D>(&self.array, i, &self.compare);
+        while self.compare.gt(&self.array[i], &self.array[child]) {
+            self.swap(i, child);
+            i = child;
+            child = min_child::<_, _, D>(&self.array, i, &self.compare);
+        }
+    }
+}
+
+impl<I, const D: usize, C: Compare<I> + Default> Default for DaryHeap<I, D, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, const D: usize, C: Compare<I>> MinHeap for DaryHeap<I, D, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        self.array.first()
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        if self.array.is_empty() {
+            return None;
+        }
+        Some(self.remove(0))
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        self.array.push(item);
+        if !self.handles.is_empty() {
+            self.handles.push(None);
+        }
+        self.sift_up(self.array.len() - 1);
+    }
+
+    fn len(&self) -> usize {
+        self.array.len()
+    }
+
+    fn clear(&mut self) {
+        self.array.clear();
+        self.handles.clear();
+        self.positions.clear();
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        Self::heapify_with_comparator(items, Self::default().compare)
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        if !heap_a.handles.is_empty() || !heap_b.handles.is_empty() {
+            heap_a.enable_handles();
+            heap_b.enable_handles();
+        }
+        let mut items = std::mem::take(&mut heap_a.array);
+        items.append(&mut heap_b.array);
+        let mut handles = std::mem::take(&mut heap_a.handles);
+        handles.append(&mut heap_b.handles);
+        Self::from_entries(items, handles, heap_a.compare)
+    }
+}
+
+impl<I, const D: usize, C: Compare<I>> AddressableHeap for DaryHeap<I, D, C> {
+    fn insert_with_handle(&mut self, item: Self::Item) -> Handle {
+        self.enable_handles();
+        let handle = Handle::new();
+        let i = self.array.len();
+        self.array.push(item);
+        self.handles.push(Some(handle));
+        self.positions.insert(handle, i);
+        self.sift_up(i);
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<&Self::Item> {
+        self.positions.get(&handle).map(|&i| &self.array[i])
+    }
+
+    fn decrease_key(&mut self, handle: Handle, item: Self::Item) -> Result<(), HandleError> {
+        let i = *self
+            .positions
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        if self.compare.gt(&item, &self.array[i]) {
+            return Err(HandleError::KeyIncreased);
+        }
+        self.array[i] = item;
+        self.sift_up(i);
+        Ok(())
+    }
+
+    fn delete(&mut self, handle: Handle) -> Result<Self::Item, HandleError> {
+        let i = *self
+            .positions
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        Ok(self.remove(i))
+    }
+}
+
+impl<I, const D: usize, C> IntoIterator for DaryHeap<I, D, C> {
+    type Item = I;
+    type IntoIter = vec::IntoIter<I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.array.into_iter()
+    }
+}
+
+impl<'a, I, const D: usize, C> IntoIterator for &'a DaryHeap<I, D, C> {
+    type Item = &'a I;
+    type IntoIter = slice::Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.array.iter()
+    }
+}
+
+impl<I, const D: usize, C: Compare<I> + Default> FromIterator<I> for DaryHeap<I, D, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, const D: usize, C: Compare<I> + Default> From<Vec<I>> for DaryHeap<I, D, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, const D: usize, C: Compare<I> + Default, const N: usize> From<[I; N]>
+    for DaryHeap<I, D, C>
+{
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, const D: usize, C: Compare<I>> Extend<I> for DaryHeap<I, D, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        let old_len = self.array.len();
+        self.array.extend(iter);
+        if !self.handles.is_empty() {
+            self.handles.resize(self.array.len(), None);
+        }
+
+        // Rebuilding the whole heap like meld does costs O(n), while sifting up each new item costs
+        // O(log n) per item, so only sift when few items were added
+        let added = self.array.len() - old_len;
+        let log_len = (usize::BITS - self.array.len().leading_zeros()) as usize;
+        if 2 * self.array.len() < added * log_len {
+            self.rebuild();
+        } else {
+            for i in old_len..self.array.len() {
+                self.sift_up(i);
+            }
+        }
+    }
+}
+
+impl<I: Clone, const D: usize, C: Clone> Clone for DaryHeap<I, D, C> {
+    /// The clone has the same items but none of the handles, which keep referring to the items in
+    /// the original heap
+    fn clone(&self) -> Self {
+        Self {
+            array: self.array.clone(),
+            handles: Vec::new(),
+            positions: HashMap::new(),
+            compare: self.compare.clone(),
+        }
+    }
+}
+
+impl<I: Debug, const D: usize, C> Debug for DaryHeap<I, D, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.array.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, const D: usize, C: Compare<I>> PartialEq for DaryHeap<I, D, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.array.iter(), other.array.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, const D: usize, C: Compare<I>> Eq for DaryHeap<I, D, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, const D: usize, C: Compare<I>> CheckInvariants for DaryHeap<I, D, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        for i in 1..self.array.len() {
+            ensure!(
+                self.compare.le(&self.array[parent::<D>(i)], &self.array[i]),
+                "heap order",
+                "item at index {i} is smaller than its parent at index {}",
+                parent::<D>(i)
+            );
+        }
+        ensure!(
+            self.handles.is_empty() || self.handles.len() == self.array.len(),
+            "handles",
+            "{} handle slots for {} items",
+            self.handles.len(),
+            self.array.len()
+        );
+        let mut found = 0;
+        for (i, &handle) in self.handles.iter().enumerate() {
+            check_handle(&self.positions, handle, i, &mut found)?;
+        }
+        check_handle_count(&self.positions, found)
+    }
+}
+
+impl<I: Debug, const D: usize, C> DaryHeap<I, D, C> {
+    /// Adds the array tree to a graph that may hold other heaps
+    pub(crate) fn write_dot(&self, dot: &mut Dot) {
+        let ids: Vec<usize> = self
+            .array
+            .iter()
+            .enumerate()
+            .map(|(i, item)| dot.node(item, &format!("[{i}]"), ""))
+            .collect();
+        for i in 1..ids.len() {
+            dot.edge(ids[parent::<D>(i)], ids[i], "");
+        }
+    }
+}
+
+/// Draws the array as the tree it stands for, labelling each item with its index
+impl<I: Debug, const D: usize, C> ToDot for DaryHeap<I, D, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        self.write_dot(&mut dot);
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DaryHeap;
+    use crate::CheckInvariants;
+    use crate::{tests, ByKey, FnCompare, MaxOrder};
+
+    type HeapU32 = DaryHeap<u32, 4>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+        tests::differential::<DaryHeap<u32, 3>>();
+        tests::differential::<DaryHeap<u32, 16>>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key() {
+        tests::decrease_key::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key_many() {
+        tests::decrease_key_many::<HeapU32>();
+    }
+    #[test]
+    fn delete() {
+        tests::delete::<HeapU32>();
+    }
+    #[test]
+    fn handles_after_meld() {
+        tests::handles_after_meld::<HeapU32>();
+    }
+    #[test]
+    fn stale_handles_rejected() {
+        tests::stale_handles_rejected::<HeapU32>();
+    }
+    #[test]
+    fn clone_handles() {
+        tests::clone_handles::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<DaryHeap<u32, 4, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| DaryHeap::<_, 4, _>::with_comparator(ByKey(|item: &(u32, char)| item.0)));
+    }
+    #[test]
+    fn fn_compare_decrease_key() {
+        tests::fn_compare_decrease_key(DaryHeap::<_, 4, _>::with_comparator(FnCompare(
+            |a: &u32, b: &u32| b.cmp(a),
+        )));
+    }
+
+    #[test]
+    fn other_arities_large_input() {
+        tests::large_input::<DaryHeap<u32, 3>>();
+        tests::large_input::<DaryHeap<u32, 8>>();
+        tests::large_input::<DaryHeap<u32, 16>>();
+    }
+    #[test]
+    fn other_arities_meld() {
+        tests::meld::<DaryHeap<u32, 3>>();
+        tests::meld::<DaryHeap<u32, 8>>();
+        tests::meld::<DaryHeap<u32, 16>>();
+    }
+    #[test]
+    fn other_arities_decrease_key_many() {
+        tests::decrease_key_many::<DaryHeap<u32, 3>>();
+        tests::decrease_key_many::<DaryHeap<u32, 8>>();
+        tests::decrease_key_many::<DaryHeap<u32, 16>>();
+    }
+    #[test]
+    fn other_arities_std_traits() {
+        tests::std_traits::<DaryHeap<u32, 3>>();
+        tests::std_traits::<DaryHeap<u32, 8>>();
+        tests::std_traits::<DaryHeap<u32, 16>>();
+    }
+
+    #[test]
+    fn broken_invariants_named() {
+        let mut heap: HeapU32 = (0..10).collect();
+        assert_eq!(heap.check_invariants(), Ok(()));
+        heap.array.swap(1, 5);
+        assert_eq!(
+            heap.check_invariants().unwrap_err().to_string(),
+            "heap order invariant broken: item at index 5 is smaller than its parent at index 1"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::{Debug, Write};
+
+/// Heap that can draw its internal structure as a [Graphviz](https://graphviz.org) DOT graph, to
+/// look at the state of a heap in a failing test or to show how an operation changes it. Render
+/// the output with `dot -Tsvg`
+pub trait ToDot {
+    /// Graph of the heap's nodes labelled with the `Debug` output of their items, plus whatever
+    /// the heap keeps per node, like degrees or ranks
+    fn to_dot(&self) -> String;
+}
+
+/// Writes a DOT graph, giving each node a fresh id
+pub(crate) struct Dot {
+    out: String,
+    nodes: usize,
+    clusters: usize,
+}
+
+impl Dot {
+    pub(crate) fn new() -> Self {
+        Self {
+            out: String::from("digraph heap {\n    node [shape=circle];\n"),
+            nodes: 0,
+            clusters: 0,
+        }
+    }
+
+    /// Adds a node with the `Debug` output of `item` on its first line and `extra`, if not
+    /// empty, on the second, and returns its id
+    pub(crate) fn node(&mut self, item: &impl Debug, extra: &str, attributes: &str) -> usize {
+        let mut label = escape(&format!("{item:?}"));
+        if !extra.is_empty() {
+            label = format!("{label}\\n{}", escape(extra));
+        }
+        let id = self.nodes;
+        self.nodes += 1;
+        let _ = write!(self.out, "    n{id} [label=\"{label}\"");
+        if !attributes.is_empty() {
+            let _ = write!(self.out, ", {attributes}");
+        }
+        self.out.push_str("];\n");
+        id
+    }
+
+    /// Adds a box node with a label but no item, like a bucket, and returns its id
+    pub(crate) fn label(&mut self, label: &str) -> usize {
+        let id = self.nodes;
+        self.nodes += 1;
+        let _ = writeln!(
+            self.out,
+            "    n{id} [label=\"{}\", shape=box];",
+            escape(label)
+        );
+        id
+    }
+
+    pub(crate) fn edge(&mut self, from: usize, to: usize, attributes: &str) {
+        let _ = write!(self.out, "    n{from} -> n{to}");
+        if !attributes.is_empty() {
+            let _ = write!(self.out, " [{attributes}]");
+        }
+        self.out.push_str(";\n");
+    }
+
+    /// Draws the nodes side by side, each with a dashed edge to the next one, like the list of
+    /// roots of a forest
+    pub(crate) fn list(&mut self, nodes: &[usize]) {
+        if nodes.len() > 1 {
+            self.out.push_str("    { rank=same;");
+            for id in nodes {
+                let _ = write!(self.out, " n{id};");
+            }
+            self.out.push_str(" }\n");
+        }
+        for pair in nodes.windows(2) {
+            self.edge(pair[0], pair[1], "style=dashed");
+        }
+    }
+
+    /// Points a plain text `name` at a node, to show where a field of the heap like `min` points
+    pub(crate) fn marker(&mut self, name: &str, node: usize) {
+        let _ = writeln!(self.out, "    {name} [shape=plaintext];");
+        let _ = writeln!(self.out, "    {name} -> n{node} [style=dotted];");
+    }
+
+    /// Draws the nodes added by `draw` inside a box with a label, to tell apart several heaps in
+    /// one graph
+    pub(crate) fn cluster(&mut self, label: &str, draw: impl FnOnce(&mut Self)) {
+        let _ = writeln!(self.out, "    subgraph cluster_{} {{", self.clusters);
+        let _ = writeln!(self.out, "    label=\"{}\";", escape(label));
+        self.clusters += 1;
+        draw(self);
+        self.out.push_str("    }\n");
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+}
+
+/// Escapes a label so it can go between double quotes
+fn escape(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BinaryHeap, BinomialHeap, LazyBinomialHeap, MinHeap};
+
+    #[test]
+    fn binary_heap() {
+        let heap = BinaryHeap::<u32>::heapify(vec![3, 1, 2]);
+        assert_eq!(
+            heap.to_dot(),
+            "digraph heap {
+    node [shape=circle];
+    n0 [label=\"1\\n[0]\"];
+    n1 [label=\"3\\n[1]\"];
+    n2 [label=\"2\\n[2]\"];
+    n0 -> n1;
+    n0 -> n2;
+}
+"
+        );
+        assert_eq!(
+            BinaryHeap::<u32>::default().to_dot(),
+            "digraph heap {\n    node [shape=circle];\n}\n"
+        );
+    }
+
+    #[test]
+    fn binomial_degrees() {
+        let mut heap = BinomialHeap::<u32>::default();
+        for item in 0..3 {
+            heap.insert(item);
+        }
+        let dot = heap.to_dot();
+        assert!(dot.contains("[label=\"2\\ndegree 0\"]"), "{dot}");
+        assert!(dot.contains("[label=\"0\\ndegree 1\"]"), "{dot}");
+        assert!(dot.contains("[label=\"1\\ndegree 0\"]"), "{dot}");
+        assert_eq!(dot.matches("rank=same").count(), 1, "{dot}");
+    }
+
+    #[test]
+    fn lazy_markers() {
+        let mut heap = LazyBinomialHeap::<u32>::default();
+        for item in [2, 0, 1] {
+            heap.insert(item);
+        }
+        let dot = heap.to_dot();
+        for marker in ["min", "prev_min", "tail"] {
+            assert!(
+                dot.contains(&format!("    {marker} [shape=plaintext];")),
+                "{dot}"
+            );
+        }
+        let min_node = dot
+            .lines()
+            .find(|line| line.contains("[label=\"0\\n"))
+            .and_then(|line| line.split_whitespace().next())
+            .unwrap();
+        assert!(dot.contains(&format!("\n    min -> {min_node} ")), "{dot}");
+    }
+
+    #[test]
+    fn labels_escaped() {
+        let heap = BinaryHeap::<&str>::heapify(vec!["a \"quoted\" \\ label"]);
+        assert!(heap
+            .to_dot()
+            .contains(r#"[label="\"a \\\"quoted\\\" \\\\ label\"\n[0]"]"#));
+    }
+}
@@ -0,0 +1,959 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem,
+    ptr::NonNull,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{
+    check_handle, check_handle_count, check_len, ensure, CheckInvariants, InvariantError,
+};
+use crate::{
+    dot::{Dot, ToDot},
+    handle::meld_handle_maps,
+    iter::same_items,
+    node_box::{NodeBox, TreeNode},
+    stats::count,
+    AddressableHeap, Compare, Handle, HandleError, MinHeap, MinOrder,
+};
+
+struct FibonacciNode<I> {
+    item: I,
+    degree: usize,
+    /// Whether the node lost a child since it last became the child of another node
+    marked: bool,
+    /// First child
+    left: FibonacciLink<I>,
+    /// Next sibling, unless node is a root then it is the next tree
+    right: FibonacciLink<I>,
+    /// Parent of the node, or None if node is a root
+    parent: Option<NonNull<FibonacciNode<I>>>,
+    /// Node whose left or right link owns this node, or None if node is the head of the list
+    prev: Option<NonNull<FibonacciNode<I>>>,
+    /// Handle of the item, if it was inserted with one
+    handle: Option<Handle>,
+}
+type FibonacciLink<I> = Option<NodeBox<FibonacciNode<I>>>;
+
+impl<I> TreeNode for FibonacciNode<I> {
+    fn take_links(&mut self) -> [Option<NodeBox<Self>>; 2] {
+        [self.right.take(), self.left.take()]
+    }
+}
+
+impl<I> FibonacciNode<I> {
+    fn with_item(item: I) -> Self {
+        count!(allocations);
+        Self {
+            item,
+            degree: 0,
+            marked: false,
+            left: None,
+            right: None,
+            parent: None,
+            prev: None,
+            handle: None,
+        }
+    }
+
+    fn link(
+        mut tree: NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) -> NodeBox<Self> {
+        if compare.gt(&tree.item, &other.item) {
+            mem::swap(&mut tree, &mut other);
+        }
+        Self::link_as_child(&mut tree, other, compare);
+        tree
+    }
+
+    fn link_as_child(
+        tree: &mut NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) {
+        count!(links);
+        debug_assert_eq!(tree.degree, other.degree);
+        debug_assert!(other.right.is_none());
+        debug_assert!(compare.le(&tree.item, &other.item));
+
+        let tree_ptr = tree.as_ptr();
+        let other_ptr = other.as_ptr();
+        if let Some(first_child) = tree.left.as_mut() {
+            first_child.prev = Some(other_ptr);
+        }
+        other.right = tree.left.take();
+        other.parent = Some(tree_ptr);
+        other.prev = Some(tree_ptr);
+        other.marked = false;
+        tree.left = Some(other);
+        tree.degree += 1;
+    }
+
+    /// Same one-pass linking as the lazy binomial heap: each tree is linked at most once, with
+    /// the first tree of equal degree that was left unmatched before it
+    fn merge_matches_one_pass(
+        mut head_a: FibonacciLink<I>,
+        mut head_b: FibonacciLink<I>,
+        compare: &impl Compare<I>,
+    ) -> FibonacciLink<I> {
+        let mut matchings: HashMap<usize, NodeBox<FibonacciNode<I>>> = HashMap::new();
+
+        if head_a.is_none() {
+            mem::swap(&mut head_a, &mut head_b);
+        }
+
+        let mut full_list_head = None;
+        let mut list_tail_cursor = &mut full_list_head;
+        let mut cursor = head_a;
+        while let Some(mut tree) = cursor.or_else(|| head_b.take()) {
+            cursor = tree.right.take();
+            // Children of the extracted min become roots here
+            tree.parent = None;
+            tree.marked = false;
+
+            let degree = tree.degree;
+
+            if let Some(matching_tree) = matchings.remove(&degree) {
+                *list_tail_cursor = Some(Self::link(tree, matching_tree, compare));
+                list_tail_cursor = &mut list_tail_cursor.as_mut().unwrap().right;
+            } else {
+                matchings.insert(degree, tree);
+            }
+        }
+        // Add leftovers
+        for tree in matchings.into_values() {
+            *list_tail_cursor = Some(tree);
+            list_tail_cursor = &mut list_tail_cursor.as_mut().unwrap().right;
+        }
+
+        full_list_head
+    }
+}
+
+pub struct FibonacciHeap<I, C = MinOrder> {
+    // Head of the list of trees. "Owns" the list
+    head: FibonacciLink<I>,
+    // Pointer to the last tree in the list
+    tail: Option<NonNull<FibonacciNode<I>>>,
+    // Pointer to the tree with min value in the list
+    min: Option<NonNull<FibonacciNode<I>>>,
+    // Node holding the item of each handle in the heap
+    handles: HashMap<Handle, NonNull<FibonacciNode<I>>>,
+    // Number of items in the heap
+    len: usize,
+    // Order of the items
+    compare: C,
+}
+
+impl<I, C: Compare<I> + Default> Default for FibonacciHeap<I, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, C: Compare<I>> FibonacciHeap<I, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            head: None,
+            tail: None,
+            min: None,
+            handles: HashMap::new(),
+            len: 0,
+            compare,
+        }
+    }
+
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        let mut heap = Self::with_comparator(compare);
+        for item in items.into_iter() {
+            heap.insert(item);
+        }
+        heap
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I> {
+        Iter {
+            stack: self.head.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<I> {
+        let iter = IntoIter {
+            stack: self.head.take().into_iter().collect(),
+            remaining: self.len,
+        };
+        self.clear();
+        iter
+    }
+
+    /// Replaces the list of trees, finding its tail and min
+    fn set_list(&mut self, list: FibonacciLink<I>) {
+        if list.is_none() {
+            self.head = None;
+            self.tail = None;
+            self.min = None;
+            return;
+        }
+
+        let mut min_ptr = list.as_ref().unwrap().as_ptr();
+        let mut prev_ptr = None;
+        // Walks the list through raw pointers, since min_ptr can point to the tree whose `right`
+        // link a mutable cursor would be borrowing
+        let mut cursor = list.as_ref().map(NodeBox::as_ptr);
+        while let Some(mut curr_ptr) = cursor {
+            // SAFETY: Both point to trees in the list we own, and the shared references are gone
+            // before the mutable one is made
+            let (min, curr) = unsafe { (min_ptr.as_ref(), curr_ptr.as_ref()) };
+            if self.compare.gt(&min.item, &curr.item) {
+                min_ptr = curr_ptr;
+            }
+            let curr_tree = unsafe { curr_ptr.as_mut() };
+            curr_tree.prev = prev_ptr;
+            prev_ptr = Some(curr_ptr);
+            cursor = curr_tree.right.as_ref().map(NodeBox::as_ptr);
+        }
+
+        self.head = list;
+        self.tail = prev_ptr;
+        self.min = Some(min_ptr);
+    }
+
+    /// Detaches `node` and its subtree from the list it is in, putting its right sibling in its place
+    fn unlink(&mut self, mut node_ptr: NonNull<FibonacciNode<I>>) -> NodeBox<FibonacciNode<I>> {
+        // SAFETY: We only store pointers to nodes owned by the heap, and a node is never its own
+        // prev, so the two mutable references don't alias.
+        // We hold a mutable reference to self, so no one can have any other reference
+        let node = unsafe { node_ptr.as_mut() };
+        let prev = node.prev.take();
+        let link = match prev {
+            None => &mut self.head,
+            Some(mut prev_ptr) => {
+                let prev_node = unsafe { prev_ptr.as_mut() };
+                if prev_node
+                    .left
+                    .as_ref()
+                    .is_some_and(|left| left.as_ptr() == node_ptr)
+                {
+                    &mut prev_node.left
+                } else {
+                    &mut prev_node.right
+                }
+            }
+        };
+        let mut boxed_node = link.take().unwrap();
+        debug_assert!(boxed_node.as_ptr() == node_ptr);
+
+        *link = boxed_node.right.take();
+        match link {
+            Some(next) => next.prev = prev,
+            None if self.tail == Some(node_ptr) => self.tail = prev,
+            None => {}
+        }
+        boxed_node
+    }
+
+    /// Appends a tree to the end of the list of trees, updating the min if needed
+    fn push_root(&mut self, mut tree: NodeBox<FibonacciNode<I>>) {
+        debug_assert!(tree.right.is_none());
+        tree.parent = None;
+        tree.marked = false;
+        tree.prev = self.tail;
+        let tree_ptr = tree.as_ptr();
+
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // We hold a mutable reference to self, so no one can have any other reference
+        if self.min.is_none_or(|min_ptr| {
+            self.compare
+                .gt(unsafe { &min_ptr.as_ref().item }, &tree.item)
+        }) {
+            self.min = Some(tree_ptr);
+        }
+        match self.tail {
+            Some(mut tail_ptr) => unsafe { tail_ptr.as_mut() }.right = Some(tree),
+            None => self.head = Some(tree),
+        }
+        self.tail = Some(tree_ptr);
+    }
+
+    /// Cuts `node` from its parent and makes it a root, then does the same with every marked
+    /// ancestor until finding one that is unmarked, which gets marked
+    fn cascading_cut(&mut self, mut node_ptr: NonNull<FibonacciNode<I>>) {
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // We hold a mutable reference to self, so no one can have any other reference
+        while let Some(mut parent_ptr) = unsafe { node_ptr.as_ref() }.parent {
+            let tree = self.unlink(node_ptr);
+            self.push_root(tree);
+
+            let parent = unsafe { parent_ptr.as_mut() };
+            parent.degree -= 1;
+            if parent.parent.is_none() {
+                break;
+            }
+            if !parent.marked {
+                parent.marked = true;
+                break;
+            }
+            node_ptr = parent_ptr;
+        }
+    }
+}
+
+impl<I, C: Compare<I>> MinHeap for FibonacciHeap<I, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        self.min.map(|ptr| {
+            // SAFETY: We only store Some(ptr) from valid trees
+            // Since we have &self borrowed, we know there are not mutable references
+            let x = unsafe { ptr.as_ref() };
+            &x.item
+        })
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let min = self.unlink(self.min.unwrap());
+
+        // Extracting the min item and the rest of the elements from the tree that contained it
+        let FibonacciNode {
+            item: min_item,
+            left: min_tree_remaining, // Children of a root can be thought of as list of trees
+            handle: min_handle,
+            ..
+        } = min.into_inner();
+
+        if let Some(handle) = min_handle {
+            self.handles.remove(&handle);
+        }
+
+        let list = self.head.take();
+        let merged = FibonacciNode::merge_matches_one_pass(list, min_tree_remaining, &self.compare);
+        self.set_list(merged);
+        self.len -= 1;
+
+        Some(min_item)
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        self.push_root(NodeBox::new(FibonacciNode::with_item(item)));
+        self.len += 1;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        if self.len == 0 && self.head.is_none() && self.tail.is_none() && self.min.is_none() {
+            true
+        } else if self.len != 0 && self.head.is_some() && self.tail.is_some() && self.min.is_some()
+        {
+            false
+        } else {
+            // Invalid state
+            unreachable!()
+        }
+    }
+
+    fn clear(&mut self) {
+        self.set_list(None);
+        self.handles.clear();
+        self.len = 0;
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        Self::heapify_with_comparator(items, Self::default().compare)
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        if heap_a.is_empty() {
+            // The melded heap keeps the comparator of heap_a
+            mem::swap(&mut heap_a.compare, &mut heap_b.compare);
+            return heap_b;
+        }
+        if heap_b.is_empty() {
+            return heap_a;
+        }
+
+        let mut head_b = heap_b.head.take().unwrap();
+        let tail_ptr_b = heap_b.tail.unwrap();
+        let min_ptr_b = heap_b.min.unwrap();
+
+        let mut tail_ptr_a = heap_a.tail.unwrap();
+        let min_ptr_a = heap_a.min.unwrap();
+
+        let handles = meld_handle_maps(
+            mem::take(&mut heap_a.handles),
+            mem::take(&mut heap_b.handles),
+        );
+
+        // Append list b to the tail of list a
+        {
+            // SAFETY: We only store valid pointers to trees
+            // We own heap_a so no-one else can have a reference
+            let tail_a = unsafe { tail_ptr_a.as_mut() };
+            head_b.prev = Some(tail_ptr_a);
+            tail_a.right = Some(head_b);
+        }
+        let min = {
+            // Safety: We only store valid pointer to trees
+            // We own both heap_a and heap_b so there can't be mutable references
+            let min_a = unsafe { min_ptr_a.as_ref() };
+            let min_b = unsafe { min_ptr_b.as_ref() };
+
+            if heap_a.compare.le(&min_a.item, &min_b.item) {
+                min_ptr_a
+            } else {
+                min_ptr_b
+            }
+        };
+
+        heap_a.tail = Some(tail_ptr_b);
+        heap_a.min = Some(min);
+        heap_a.handles = handles;
+        heap_a.len += heap_b.len;
+        heap_a
+    }
+}
+
+impl<I, C: Compare<I>> AddressableHeap for FibonacciHeap<I, C> {
+    fn insert_with_handle(&mut self, item: Self::Item) -> Handle {
+        let handle = Handle::new();
+        let mut node = NodeBox::new(FibonacciNode::with_item(item));
+        node.handle = Some(handle);
+        self.handles.insert(handle, node.as_ptr());
+        self.push_root(node);
+        self.len += 1;
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<&Self::Item> {
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // Since we have &self borrowed, we know there are not mutable references
+        self.handles
+            .get(&handle)
+            .map(|node| unsafe { &node.as_ref().item })
+    }
+
+    fn decrease_key(&mut self, handle: Handle, item: Self::Item) -> Result<(), HandleError> {
+        let mut node_ptr = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // We hold a mutable reference to self, so no one can have any other reference
+        let node = unsafe { node_ptr.as_mut() };
+        if self.compare.gt(&item, &node.item) {
+            return Err(HandleError::KeyIncreased);
+        }
+        node.item = item;
+        // Only shared references from here on, since the node can also be the one min points to
+        let node = unsafe { node_ptr.as_ref() };
+
+        match node.parent {
+            Some(parent_ptr)
+                if self
+                    .compare
+                    .gt(unsafe { &parent_ptr.as_ref().item }, &node.item) =>
+            {
+                // cascading_cut updates the min when the node becomes a root
+                self.cascading_cut(node_ptr);
+            }
+            Some(_) => {}
+            None => {
+                if self
+                    .compare
+                    .gt(unsafe { &self.min.unwrap().as_ref().item }, &node.item)
+                {
+                    self.min = Some(node_ptr);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, handle: Handle) -> Result<Self::Item, HandleError> {
+        let node_ptr = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        self.cascading_cut(node_ptr);
+
+        // Make the node the min so extract_min removes it
+        self.min = Some(node_ptr);
+        Ok(self.extract_min().unwrap())
+    }
+}
+
+/// Iterator over the items of a [`FibonacciHeap`] in arbitrary order
+pub struct Iter<'a, I> {
+    /// Nodes whose item, children and next siblings haven't been visited yet
+    stack: Vec<&'a FibonacciNode<I>>,
+    remaining: usize,
+}
+
+impl<'a, I> Iterator for Iter<'a, I> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.remaining -= 1;
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for Iter<'_, I> {}
+impl<I> FusedIterator for Iter<'_, I> {}
+
+/// Owning iterator over the items of a [`FibonacciHeap`] in arbitrary order
+pub struct IntoIter<I> {
+    /// Trees whose items haven't been returned yet
+    stack: Vec<NodeBox<FibonacciNode<I>>>,
+    remaining: usize,
+}
+
+impl<I> Iterator for IntoIter<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?.into_inner();
+        self.stack.extend(node.right.take());
+        self.stack.extend(node.left.take());
+        self.remaining -= 1;
+        Some(node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for IntoIter<I> {}
+impl<I> FusedIterator for IntoIter<I> {}
+
+impl<I, C: Compare<I>> IntoIterator for FibonacciHeap<I, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl<'a, I, C: Compare<I>> IntoIterator for &'a FibonacciHeap<I, C> {
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<I, C: Compare<I> + Default> FromIterator<I> for FibonacciHeap<I, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default> From<Vec<I>> for FibonacciHeap<I, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default, const N: usize> From<[I; N]> for FibonacciHeap<I, C> {
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, C: Compare<I>> Extend<I> for FibonacciHeap<I, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Inserting is O(1), which is also how heapify builds the heap
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<I: Clone, C: Compare<I> + Clone> Clone for FibonacciHeap<I, C> {
+    /// The clone has the same items but none of the handles, which keep referring to the items in
+    /// the original heap
+    fn clone(&self) -> Self {
+        Self::heapify_with_comparator(self.iter().cloned().collect(), self.compare.clone())
+    }
+}
+
+impl<I: Debug, C: Compare<I>> Debug for FibonacciHeap<I, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, C: Compare<I>> PartialEq for FibonacciHeap<I, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, C: Compare<I>> Eq for FibonacciHeap<I, C> {}
+
+/// Fewest nodes a tree whose root has `degree` children can have, which is the Fibonacci number
+/// F(degree + 2)
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+fn min_tree_size(degree: usize) -> usize {
+    let (mut size, mut next) = (1usize, 2usize);
+    for _ in 0..degree {
+        (size, next) = (next, size.saturating_add(next));
+    }
+    size
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> CheckInvariants for FibonacciHeap<I, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        // Every node of the heap, each with the index of its parent in the list. Children are
+        // added after their parent, so the trees can be walked without recursing
+        let mut nodes: Vec<(&FibonacciNode<I>, Option<usize>)> = Vec::new();
+        let mut prev = None;
+        let mut min_found = false;
+        let mut smallest: Option<&FibonacciNode<I>> = None;
+        let mut cursor = self.head.as_deref();
+        for i in 0.. {
+            let Some(root) = cursor else {
+                break;
+            };
+            ensure!(
+                root.parent.is_none(),
+                "parent pointers",
+                "root {i} has a parent"
+            );
+            ensure!(
+                root.prev == prev,
+                "prev pointers",
+                "root {i} doesn't point to the root before it"
+            );
+            ensure!(!root.marked, "marks", "root {i} is marked");
+            min_found |= self.min == Some(NonNull::from(root));
+            if smallest.is_none_or(|smallest| self.compare.lt(&root.item, &smallest.item)) {
+                smallest = Some(root);
+            }
+            nodes.push((root, None));
+            prev = Some(NonNull::from(root));
+            cursor = root.right.as_deref();
+        }
+        ensure!(
+            self.tail == prev,
+            "tail pointer",
+            "tail doesn't point to the last root"
+        );
+        match smallest {
+            Some(smallest) => {
+                ensure!(min_found, "min pointer", "min doesn't point to a root");
+                // SAFETY: min points to one of the roots walked above
+                let min = unsafe { self.min.unwrap().as_ref() };
+                ensure!(
+                    self.compare.le(&min.item, &smallest.item),
+                    "min pointer",
+                    "a root is smaller than the one min points to"
+                );
+            }
+            None => ensure!(
+                self.min.is_none(),
+                "min pointer",
+                "min is set in an empty heap"
+            ),
+        }
+
+        let mut handles_found = 0;
+        let mut i = 0;
+        while let Some(&(node, _)) = nodes.get(i) {
+            check_handle(
+                &self.handles,
+                node.handle,
+                NonNull::from(node),
+                &mut handles_found,
+            )?;
+            // The first child points back to its parent and the rest to their left sibling
+            let mut prev = NonNull::from(node);
+            let mut children = 0;
+            let mut cursor = node.left.as_deref();
+            while let Some(child) = cursor {
+                children += 1;
+                ensure!(
+                    child.parent == Some(NonNull::from(node)),
+                    "parent pointers",
+                    "child {children} of a node of degree {} doesn't point to it",
+                    node.degree
+                );
+                ensure!(
+                    child.prev == Some(prev),
+                    "prev pointers",
+                    "child {children} of a node of degree {} doesn't point to the node before it",
+                    node.degree
+                );
+                ensure!(
+                    self.compare.le(&node.item, &child.item),
+                    "heap order",
+                    "child {children} of a node of degree {} is smaller than it",
+                    node.degree
+                );
+                nodes.push((child, Some(i)));
+                prev = NonNull::from(child);
+                cursor = child.right.as_deref();
+            }
+            ensure!(
+                children == node.degree,
+                "degree",
+                "node of degree {} has {children} children",
+                node.degree
+            );
+            i += 1;
+        }
+
+        // Add up the sizes of the trees from the bottom up
+        let mut sizes = vec![1; nodes.len()];
+        for (i, &(node, parent)) in nodes.iter().enumerate().rev() {
+            ensure!(
+                sizes[i] >= min_tree_size(node.degree),
+                "fibonacci tree size",
+                "tree of degree {} has {} nodes, fewer than F({}) = {}",
+                node.degree,
+                sizes[i],
+                node.degree + 2,
+                min_tree_size(node.degree)
+            );
+            if let Some(parent) = parent {
+                sizes[parent] += sizes[i];
+            }
+        }
+        check_len(self.len, nodes.len())?;
+        check_handle_count(&self.handles, handles_found)
+    }
+}
+
+impl<I: Debug> FibonacciNode<I> {
+    fn write_dot(&self, dot: &mut Dot) -> usize {
+        let attributes = if self.marked {
+            "style=filled, fillcolor=lightgray"
+        } else {
+            ""
+        };
+        dot.node(&self.item, &format!("degree {}", self.degree), attributes)
+    }
+}
+
+/// Draws the list of roots side by side, marking the ones `min` and `tail` point to, with marked
+/// nodes filled in gray
+impl<I: Debug, C> ToDot for FibonacciHeap<I, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut roots = Vec::new();
+        let mut stack = Vec::new();
+        let mut cursor = self.head.as_deref();
+        while let Some(root) = cursor {
+            let id = root.write_dot(&mut dot);
+            let root_ptr = Some(NonNull::from(root));
+            for (name, ptr) in [("min", self.min), ("tail", self.tail)] {
+                if ptr == root_ptr {
+                    dot.marker(name, id);
+                }
+            }
+            roots.push(id);
+            stack.push((root, id));
+            cursor = root.right.as_deref();
+        }
+        dot.list(&roots);
+        while let Some((node, id)) = stack.pop() {
+            let mut cursor = node.left.as_deref();
+            while let Some(child) = cursor {
+                let child_id = child.write_dot(&mut dot);
+                dot.edge(id, child_id, "");
+                stack.push((child, child_id));
+                cursor = child.right.as_deref();
+            }
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests, ByKey, FnCompare, MaxOrder};
+
+    #[test]
+    fn cascading_cut() {
+        let mut heap: FibonacciHeap<i32> = FibonacciHeap::make_heap();
+        let handles: Vec<_> = (0..17).map(|i| heap.insert_with_handle(i)).collect();
+        // One-pass linking after these leaves a degree 3 tree rooted at 3 at the head of the list,
+        // whose first child is 7 with children 9 and 8
+        assert_eq!(heap.extract_min(), Some(0));
+        assert_eq!(heap.extract_min(), Some(1));
+        assert_eq!(heap.extract_min(), Some(2));
+
+        let root = heap.head.as_ref().unwrap();
+        let child = root.left.as_ref().unwrap();
+        assert_eq!((root.item, root.degree), (3, 3));
+        assert_eq!((child.item, child.degree), (7, 2));
+
+        // Cutting one child of 7 marks it
+        heap.decrease_key(handles[8], 0).unwrap();
+        let child = heap.head.as_ref().unwrap().left.as_ref().unwrap();
+        assert_eq!((child.item, child.degree), (7, 1));
+        assert!(child.marked);
+
+        // Cutting a second child of 7 cuts 7 as well
+        heap.decrease_key(handles[9], 0).unwrap();
+        let root = heap.head.as_ref().unwrap();
+        assert_eq!((root.item, root.degree), (3, 2));
+        assert!(!root.marked);
+        let seven = heap.get(handles[7]).unwrap();
+        assert_eq!(*seven, 7);
+
+        for i in [0, 0, 3, 4, 5, 6, 7, 10, 11, 12, 13, 14, 15, 16] {
+            assert_eq!(heap.extract_min(), Some(i));
+        }
+        assert_eq!(heap.extract_min(), None);
+    }
+
+    type HeapU32 = FibonacciHeap<u32>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key() {
+        tests::decrease_key::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key_many() {
+        tests::decrease_key_many::<HeapU32>();
+    }
+    #[test]
+    fn delete() {
+        tests::delete::<HeapU32>();
+    }
+    #[test]
+    fn handles_after_meld() {
+        tests::handles_after_meld::<HeapU32>();
+    }
+    #[test]
+    fn stale_handles_rejected() {
+        tests::stale_handles_rejected::<HeapU32>();
+    }
+    #[test]
+    fn clone_handles() {
+        tests::clone_handles::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<FibonacciHeap<u32, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| FibonacciHeap::with_comparator(ByKey(|item: &(u32, char)| item.0)));
+    }
+    #[test]
+    fn fn_compare_decrease_key() {
+        tests::fn_compare_decrease_key(FibonacciHeap::with_comparator(FnCompare(
+            |a: &u32, b: &u32| b.cmp(a),
+        )));
+    }
+}
@@ -0,0 +1,59 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    num::NonZeroU64,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Counter used to give every handle a different id, even across different heaps
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/// Refers to an item inserted with [`AddressableHeap::insert_with_handle`](crate::AddressableHeap::insert_with_handle).
+///
+/// Handles are unique across all heaps, so a handle keeps referring to its item after the heap
+/// holding it is melded into another one, and is rejected once the item leaves the heap.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Handle(NonZeroU64);
+
+impl Handle {
+    pub(crate) fn new() -> Self {
+        let id = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+        Self(NonZeroU64::new(id).unwrap())
+    }
+}
+
+/// Reason why an operation on a [`Handle`] was rejected
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HandleError {
+    /// The handle doesn't refer to an item in the heap, either because it was already removed
+    /// or because it was given out by a different heap
+    InvalidHandle,
+    /// The new item passed to `decrease_key` is greater than the current one
+    KeyIncreased,
+}
+
+impl fmt::Display for HandleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandleError::InvalidHandle => write!(f, "handle does not refer to an item in the heap"),
+            HandleError::KeyIncreased => write!(f, "new item is greater than the current item"),
+        }
+    }
+}
+
+impl Error for HandleError {}
+
+/// Joins the handle maps of two heaps being melded, moving the entries of the smaller one
+pub(crate) fn meld_handle_maps<P>(
+    map_a: HashMap<Handle, P>,
+    map_b: HashMap<Handle, P>,
+) -> HashMap<Handle, P> {
+    let (mut larger, smaller) = if map_a.len() >= map_b.len() {
+        (map_a, map_b)
+    } else {
+        (map_b, map_a)
+    };
+    larger.extend(smaller);
+    larger
+}
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::Handle;
+
+/// Heap that can check its internal structure is consistent, which walks the whole heap. Only
+/// compiled with debug assertions or the `validate` feature, and called by the tests after every
+/// operation
+pub trait CheckInvariants {
+    /// Returns the first broken invariant found
+    fn check_invariants(&self) -> Result<(), InvariantError>;
+}
+
+/// Invariant of a heap's structure that [`CheckInvariants::check_invariants`] found broken
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InvariantError {
+    /// Name of the invariant, like "heap order"
+    pub invariant: &'static str,
+    /// Where the invariant is broken
+    pub details: String,
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} invariant broken: {}", self.invariant, self.details)
+    }
+}
+
+impl Error for InvariantError {}
+
+/// Returns an [`InvariantError`] from the enclosing function if the condition doesn't hold
+macro_rules! ensure {
+    ($condition:expr, $invariant:expr, $($details:tt)+) => {
+        if !$condition {
+            return Err($crate::InvariantError {
+                invariant: $invariant,
+                details: format!($($details)+),
+            });
+        }
+    };
+}
+pub(crate) use ensure;
+
+/// Checks the handle of an item found at `position` while walking the heap refers to that
+/// position, counting the handles found
+pub(crate) fn check_handle<P: PartialEq>(
+    handles: &HashMap<Handle, P>,
+    handle: Option<Handle>,
+    position: P,
+    found: &mut usize,
+) -> Result<(), InvariantError> {
+    if let Some(handle) = handle {
+        ensure!(
+            handles.get(&handle) == Some(&position),
+            "handles",
+            "the handle of an item doesn't refer to its position"
+        );
+        *found += 1;
+    }
+    Ok(())
+}
+
+/// Checks every handle in the map was found on an item of the heap
+pub(crate) fn check_handle_count<P>(
+    handles: &HashMap<Handle, P>,
+    found: usize,
+) -> Result<(), InvariantError> {
+    ensure!(
+        handles.len() == found,
+        "handles",
+        "{} handles refer to items but {found} items have one",
+        handles.len()
+    );
+    Ok(())
+}
+
+/// Checks the number of items found while walking the heap is its length
+pub(crate) fn check_len(len: usize, found: usize) -> Result<(), InvariantError> {
+    ensure!(
+        len == found,
+        "length",
+        "the heap holds {found} items but its length is {len}"
+    );
+    Ok(())
+}
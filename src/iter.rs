@@ -0,0 +1,96 @@
+use std::{cmp::Ordering, iter::FusedIterator};
+
+use crate::{Compare, MinHeap};
+
+/// Iterator that extracts the items of a heap from the min onwards, returned by
+/// [`MinHeap::drain_sorted`]. Items that weren't extracted when it is dropped are removed too
+pub struct DrainSorted<'a, H: MinHeap> {
+    heap: &'a mut H,
+}
+
+impl<'a, H: MinHeap> DrainSorted<'a, H> {
+    pub(crate) fn new(heap: &'a mut H) -> Self {
+        Self { heap }
+    }
+}
+
+impl<H: MinHeap> Iterator for DrainSorted<'_, H> {
+    type Item = H::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.heap.extract_min()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<H: MinHeap> ExactSizeIterator for DrainSorted<'_, H> {}
+impl<H: MinHeap> FusedIterator for DrainSorted<'_, H> {}
+
+impl<H: MinHeap> Drop for DrainSorted<'_, H> {
+    fn drop(&mut self) {
+        self.heap.clear();
+    }
+}
+
+/// Whether the two iterators hold the same items, counting duplicates, in any order. Items are
+/// sorted with `compare` and only the ones it finds equal are checked with `==`
+pub(crate) fn same_items<'a, I: PartialEq + 'a>(
+    items_a: impl Iterator<Item = &'a I>,
+    items_b: impl Iterator<Item = &'a I>,
+    compare: &impl Compare<I>,
+) -> bool {
+    let mut items_a: Vec<&I> = items_a.collect();
+    let mut items_b: Vec<&I> = items_b.collect();
+    if items_a.len() != items_b.len() {
+        return false;
+    }
+    items_a.sort_by(|a, b| compare.compare(a, b));
+    items_b.sort_by(|a, b| compare.compare(a, b));
+
+    let mut start = 0;
+    while start < items_a.len() {
+        // Run of items that the comparator can't tell apart
+        let end = (start..items_a.len())
+            .find(|&i| compare.compare(items_a[start], items_a[i]) != Ordering::Equal)
+            .unwrap_or(items_a.len());
+        if (start..end).any(|i| compare.compare(items_a[start], items_b[i]) != Ordering::Equal) {
+            return false;
+        }
+        let run_b = &mut items_b[start..end];
+        let mut unmatched = run_b.len();
+        for item_a in &items_a[start..end] {
+            match run_b[..unmatched]
+                .iter()
+                .position(|item_b| item_a == item_b)
+            {
+                Some(i) => {
+                    unmatched -= 1;
+                    run_b.swap(i, unmatched);
+                }
+                None => return false,
+            }
+        }
+        start = end;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::same_items;
+    use crate::ByKey;
+
+    #[test]
+    fn same_items_with_ties() {
+        let by_first = ByKey(|item: &(u32, char)| item.0);
+        let items_a = [(1, 'a'), (2, 'x'), (1, 'b'), (1, 'a')];
+        let items_b = [(1, 'b'), (1, 'a'), (2, 'x'), (1, 'a')];
+        let items_c = [(1, 'b'), (1, 'b'), (2, 'x'), (1, 'a')];
+        assert!(same_items(items_a.iter(), items_b.iter(), &by_first));
+        assert!(!same_items(items_a.iter(), items_c.iter(), &by_first));
+        assert!(!same_items(items_a.iter(), items_b[..3].iter(), &by_first));
+    }
+}
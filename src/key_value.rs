@@ -0,0 +1,173 @@
+use std::cmp::Ordering;
+
+use crate::{
+    dot::ToDot, BinaryHeap, BinomialHeap, Compare, FibonacciHeap, LazyBinomialHeap, MinHeap,
+    MinOrder, PairingHeap, TwoPass,
+};
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::{CheckInvariants, InvariantError};
+
+/// Item stored by a [`KeyValueHeap`]. The value is boxed so moving the item around the heap only
+/// moves the key and a pointer
+#[derive(Debug)]
+pub struct KeyValue<K, V> {
+    key: K,
+    value: Box<V>,
+}
+
+/// Orders [`KeyValue`] items by comparing only their keys with `C`
+#[derive(Clone, Copy, Default, Debug)]
+pub struct KeyOrder<C>(pub C);
+
+impl<K, V, C: Compare<K>> Compare<KeyValue<K, V>> for KeyOrder<C> {
+    fn compare(&self, a: &KeyValue<K, V>, b: &KeyValue<K, V>) -> Ordering {
+        self.0.compare(&a.key, &b.key)
+    }
+}
+
+/// Heap of values with separate priorities, where only the keys are ever compared
+pub struct KeyValueHeap<H> {
+    heap: H,
+}
+
+pub type BinaryKeyValueHeap<K, V, C = MinOrder> =
+    KeyValueHeap<BinaryHeap<KeyValue<K, V>, KeyOrder<C>>>;
+pub type BinomialKeyValueHeap<K, V, C = MinOrder> =
+    KeyValueHeap<BinomialHeap<KeyValue<K, V>, KeyOrder<C>>>;
+pub type LazyBinomialKeyValueHeap<K, V, C = MinOrder> =
+    KeyValueHeap<LazyBinomialHeap<KeyValue<K, V>, KeyOrder<C>>>;
+pub type FibonacciKeyValueHeap<K, V, C = MinOrder> =
+    KeyValueHeap<FibonacciHeap<KeyValue<K, V>, KeyOrder<C>>>;
+pub type PairingKeyValueHeap<K, V, S = TwoPass, C = MinOrder> =
+    KeyValueHeap<PairingHeap<KeyValue<K, V>, S, KeyOrder<C>>>;
+
+impl<H: Default> Default for KeyValueHeap<H> {
+    fn default() -> Self {
+        Self { heap: H::default() }
+    }
+}
+
+impl<K, V, H: MinHeap<Item = KeyValue<K, V>>> KeyValueHeap<H> {
+    pub fn make_heap() -> Self
+    where
+        H: Default,
+    {
+        Self::default()
+    }
+
+    /// Wraps an empty heap, which is how to use a key comparator that doesn't implement `Default`
+    pub fn from_heap(heap: H) -> Self {
+        Self { heap }
+    }
+
+    pub fn peek_min(&self) -> Option<(&K, &V)> {
+        self.heap.peek_min().map(|item| (&item.key, &*item.value))
+    }
+
+    pub fn extract_min(&mut self) -> Option<(K, V)> {
+        self.heap.extract_min().map(|item| (item.key, *item.value))
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.heap.insert(KeyValue {
+            key,
+            value: Box::new(value),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    pub fn heapify(pairs: Vec<(K, V)>) -> Self
+    where
+        H: Default,
+    {
+        let items = pairs
+            .into_iter()
+            .map(|(key, value)| KeyValue {
+                key,
+                value: Box::new(value),
+            })
+            .collect();
+        Self {
+            heap: H::heapify(items),
+        }
+    }
+
+    pub fn meld(heap_a: Self, heap_b: Self) -> Self {
+        Self {
+            heap: H::meld(heap_a.heap, heap_b.heap),
+        }
+    }
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<H: CheckInvariants> CheckInvariants for KeyValueHeap<H> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        self.heap.check_invariants()
+    }
+}
+
+impl<H: ToDot> ToDot for KeyValueHeap<H> {
+    fn to_dot(&self) -> String {
+        self.heap.to_dot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests, ByKey, MaxOrder};
+
+    type Item = KeyValue<u32, tests::Payload>;
+    type Order = KeyOrder<MinOrder>;
+
+    #[test]
+    fn binary() {
+        tests::key_value::<BinaryHeap<Item, Order>>();
+    }
+    #[test]
+    fn binomial() {
+        tests::key_value::<BinomialHeap<Item, Order>>();
+    }
+    #[test]
+    fn lazy_binomial() {
+        tests::key_value::<LazyBinomialHeap<Item, Order>>();
+    }
+    #[test]
+    fn fibonacci() {
+        tests::key_value::<FibonacciHeap<Item, Order>>();
+    }
+    #[test]
+    fn pairing() {
+        tests::key_value::<PairingHeap<Item, TwoPass, Order>>();
+    }
+
+    #[test]
+    fn key_comparator() {
+        let mut heap: BinaryKeyValueHeap<u32, &str, MaxOrder> = KeyValueHeap::make_heap();
+        heap.insert(1, "one");
+        heap.insert(3, "three");
+        heap.insert(2, "two");
+        assert_eq!(heap.extract_min(), Some((3, "three")));
+
+        let mut heap = KeyValueHeap::from_heap(LazyBinomialHeap::with_comparator(KeyOrder(ByKey(
+            |key: &i32| key.abs(),
+        ))));
+        heap.insert(-5, ());
+        heap.insert(2, ());
+        heap.insert(-1, ());
+        assert_eq!(heap.extract_min(), Some((-1, ())));
+        assert_eq!(heap.extract_min(), Some((2, ())));
+        assert_eq!(heap.extract_min(), Some((-5, ())));
+    }
+}
@@ -1,54 +1,97 @@
 use std::{
     collections::HashMap,
+    fmt::{self, Debug},
+    iter::FusedIterator,
     mem,
-    ptr::{self, NonNull},
+    ptr::NonNull,
 };
 
-use crate::{Item, MinHeap};
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{
+    check_handle, check_handle_count, check_len, ensure, CheckInvariants, InvariantError,
+};
+use crate::{
+    dot::{Dot, ToDot},
+    handle::meld_handle_maps,
+    iter::same_items,
+    node_box::{NodeBox, TreeNode},
+    stats::count,
+    AddressableHeap, Compare, Handle, HandleError, MinHeap, MinOrder,
+};
 
 #[derive(Debug)]
-struct LeftTreeNode<I: Item> {
+struct LeftTreeNode<I> {
     item: I,
     degree: usize,
     /// Left child
     left: LeftTreeLink<I>,
     /// Right child, unless node is a root then it is the next tree
     right: LeftTreeLink<I>,
+    /// Parent in the binomial tree (not the half tree), or None if node is a root
+    parent: Option<NonNull<LeftTreeNode<I>>>,
+    /// Previous tree in the list if node is a root
+    prev: Option<NonNull<LeftTreeNode<I>>>,
+    /// Handle of the item, if it was inserted with one
+    handle: Option<Handle>,
+}
+type LeftTreeLink<I> = Option<NodeBox<LeftTreeNode<I>>>;
+
+impl<I> TreeNode for LeftTreeNode<I> {
+    fn take_links(&mut self) -> [Option<NodeBox<Self>>; 2] {
+        [self.right.take(), self.left.take()]
+    }
 }
-type LeftTreeLink<I> = Option<Box<LeftTreeNode<I>>>;
 
-impl<I: Item> LeftTreeNode<I> {
+impl<I> LeftTreeNode<I> {
     fn with_item(item: I) -> Self {
+        count!(allocations);
         Self {
             item,
             degree: 0,
             left: None,
             right: None,
+            parent: None,
+            prev: None,
+            handle: None,
         }
     }
-    fn link(&mut self, mut other: Box<Self>) {
-        if self.item > other.item {
-            mem::swap(self, &mut other);
+
+    fn link(
+        mut tree: NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) -> NodeBox<Self> {
+        if compare.gt(&tree.item, &other.item) {
+            mem::swap(&mut tree, &mut other);
         }
-        self.link_as_child(other);
+        Self::link_as_child(&mut tree, other, compare);
+        tree
     }
 
-    fn link_as_child(&mut self, mut other: Box<Self>) {
-        assert_eq!(self.degree, other.degree);
+    fn link_as_child(
+        tree: &mut NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) {
+        count!(links);
+        assert_eq!(tree.degree, other.degree);
         assert!(other.right.is_none());
-        assert!(self.item <= other.item);
+        assert!(compare.le(&tree.item, &other.item));
 
-        let curr_left = self.left.take();
+        let curr_left = tree.left.take();
         other.right = curr_left;
-        self.left = Some(other);
-        self.degree += 1;
+        other.parent = Some(tree.as_ptr());
+        other.prev = None;
+        tree.left = Some(other);
+        tree.degree += 1;
     }
 
     fn merge_matches_one_pass(
         mut head_a: LeftTreeLink<I>,
         mut head_b: LeftTreeLink<I>,
+        compare: &impl Compare<I>,
     ) -> LeftTreeLink<I> {
-        let mut matchings: HashMap<usize, Box<LeftTreeNode<I>>> = HashMap::new();
+        let mut matchings: HashMap<usize, NodeBox<LeftTreeNode<I>>> = HashMap::new();
 
         if head_a.is_none() {
             mem::swap(&mut head_a, &mut head_b);
@@ -59,12 +102,13 @@ impl<I: Item> LeftTreeNode<I> {
         let mut cursor = head_a;
         while let Some(mut tree) = cursor.or_else(|| head_b.take()) {
             cursor = tree.right.take();
+            // Children of the extracted min become roots here
+            tree.parent = None;
 
             let degree = tree.degree;
 
             if let Some(matching_tree) = matchings.remove(&degree) {
-                tree.link(matching_tree);
-                *list_tail_cursor = Some(tree);
+                *list_tail_cursor = Some(Self::link(tree, matching_tree, compare));
                 list_tail_cursor = &mut list_tail_cursor.as_mut().unwrap().right;
             } else {
                 matchings.insert(degree, tree);
@@ -80,7 +124,7 @@ impl<I: Item> LeftTreeNode<I> {
     }
 }
 
-pub struct LazyBinomialHeap<I: Item> {
+pub struct LazyBinomialHeap<I, C = MinOrder> {
     // Head of the list of trees. "Owns" the list
     head: LeftTreeLink<I>,
     // Pointer to the last tree in the list
@@ -89,81 +133,161 @@ pub struct LazyBinomialHeap<I: Item> {
     prev_min: Option<NonNull<LeftTreeNode<I>>>,
     // Pointer to the tree with min value in the list
     min: Option<NonNull<LeftTreeNode<I>>>,
+    // Node holding the item of each handle in the heap
+    handles: HashMap<Handle, NonNull<LeftTreeNode<I>>>,
+    // Number of items in the heap
+    len: usize,
+    // Order of the items
+    compare: C,
 }
 
-impl<I: Item> Default for LazyBinomialHeap<I> {
+impl<I, C: Compare<I> + Default> Default for LazyBinomialHeap<I, C> {
     fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, C: Compare<I>> LazyBinomialHeap<I, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
         Self {
             head: None,
             tail: None,
             prev_min: None,
             min: None,
+            handles: HashMap::new(),
+            len: 0,
+            compare,
         }
     }
-}
 
-impl<I: Item> LazyBinomialHeap<I> {
-    fn with_item(item: I) -> Self {
-        let mut boxed_root = Box::new(LeftTreeNode::with_item(item));
-        let ptr = NonNull::new(&mut *boxed_root).unwrap();
-        Self {
-            head: Some(boxed_root),
-            tail: Some(ptr),
-            prev_min: None,
-            min: Some(ptr),
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        let mut heap = Self::with_comparator(compare);
+        for item in items.into_iter() {
+            heap.insert(item);
         }
+        heap
     }
 
-    fn is_empty(&self) -> bool {
-        if self.head.is_none()
-            && self.tail.is_none()
-            && self.prev_min.is_none()
-            && self.min.is_none()
-        {
-            true
-        } else if self.head.is_some() && self.tail.is_some() && self.min.is_some() {
-            false
-        } else {
-            // Invalid state
-            unreachable!()
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I> {
+        Iter {
+            stack: self.head.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<I> {
+        let iter = IntoIter {
+            stack: self.head.take().into_iter().collect(),
+            remaining: self.len,
+        };
+        self.clear();
+        iter
+    }
+
+    /// Appends a tree to the end of the list of trees, updating the min if needed
+    fn push_root(&mut self, mut tree: NodeBox<LeftTreeNode<I>>) {
+        debug_assert!(tree.right.is_none());
+        tree.prev = self.tail;
+        let tree_ptr = tree.as_ptr();
+
+        // SAFETY: We only store valid pointers to trees
+        // We hold a mutable reference to self, so no one can have any other reference
+        if self.min.is_none_or(|min_ptr| {
+            self.compare
+                .gt(unsafe { &min_ptr.as_ref().item }, &tree.item)
+        }) {
+            self.prev_min = self.tail;
+            self.min = Some(tree_ptr);
+        }
+        match self.tail {
+            Some(mut tail_ptr) => unsafe { tail_ptr.as_mut() }.right = Some(tree),
+            None => self.head = Some(tree),
         }
+        self.tail = Some(tree_ptr);
+        self.len += 1;
     }
 
-    fn from_list(list: LeftTreeLink<I>) -> LazyBinomialHeap<I> {
+    /// Replaces the list of trees, finding its tail and min
+    fn set_list(&mut self, list: LeftTreeLink<I>) {
         if list.is_none() {
-            return Self::make_heap();
+            self.head = None;
+            self.tail = None;
+            self.prev_min = None;
+            self.min = None;
+            return;
         }
 
         // Moving the min to front of list to simplify prev_min pointer
         let mut min_tree = list.unwrap(); // start with first element, find actual min in loop
         let mut remaining_list = min_tree.right.take();
         let mut cursor = &mut remaining_list;
-        let mut tail_ptr = NonNull::new(&mut *min_tree).unwrap();
+        let mut tail_ptr = min_tree.as_ptr();
 
         while let Some(mut curr_tree) = cursor.take() {
-            if min_tree.item > curr_tree.item {
+            if self.compare.gt(&min_tree.item, &curr_tree.item) {
                 min_tree.right = curr_tree.right.take();
                 mem::swap(&mut min_tree, &mut curr_tree);
             }
 
-            tail_ptr = NonNull::new(&mut *curr_tree).unwrap();
+            tail_ptr = curr_tree.as_ptr();
             *cursor = Some(curr_tree);
             cursor = &mut cursor.as_mut().unwrap().right;
         }
         min_tree.right = remaining_list;
 
-        let min_ptr = NonNull::new(&mut *min_tree).unwrap();
+        let min_ptr = min_tree.as_ptr();
 
-        Self {
-            head: Some(min_tree),
-            tail: Some(tail_ptr),
-            prev_min: None,
-            min: Some(min_ptr),
+        // Fix the prev pointers of the roots now that the order of the list is final
+        min_tree.prev = None;
+        let mut prev_ptr = min_ptr;
+        let mut cursor = &mut min_tree.right;
+        while let Some(curr_tree) = cursor {
+            curr_tree.prev = Some(prev_ptr);
+            prev_ptr = curr_tree.as_ptr();
+            cursor = &mut curr_tree.right;
+        }
+
+        self.head = Some(min_tree);
+        self.tail = Some(tail_ptr);
+        self.prev_min = None;
+        self.min = Some(min_ptr);
+    }
+
+    /// Moves the item in `node` up its tree while it is smaller than its parent's item, or all the
+    /// way to the root if `to_root` is true. Returns the node that ends up holding the item.
+    fn sift_up(
+        &mut self,
+        mut node: NonNull<LeftTreeNode<I>>,
+        to_root: bool,
+    ) -> NonNull<LeftTreeNode<I>> {
+        // SAFETY: We only store pointers to nodes owned by the heap, and a node is never its own
+        // parent, so the two mutable references don't alias.
+        // We hold a mutable reference to self, so no one can have any other reference
+        while let Some(mut parent) = unsafe { node.as_ref() }.parent {
+            let (child_node, parent_node) = unsafe { (node.as_mut(), parent.as_mut()) };
+            if !to_root && self.compare.le(&parent_node.item, &child_node.item) {
+                break;
+            }
+            mem::swap(&mut child_node.item, &mut parent_node.item);
+            count!(swaps);
+            mem::swap(&mut child_node.handle, &mut parent_node.handle);
+            if let Some(handle) = child_node.handle {
+                self.handles.insert(handle, node);
+            }
+            if let Some(handle) = parent_node.handle {
+                self.handles.insert(handle, parent);
+            }
+            node = parent;
         }
+        node
     }
 }
 
-impl<I: Item> MinHeap for LazyBinomialHeap<I> {
+impl<I, C: Compare<I>> MinHeap for LazyBinomialHeap<I, C> {
     type Item = I;
 
     fn peek_min(&self) -> Option<&Self::Item> {
@@ -199,7 +323,7 @@ impl<I: Item> MinHeap for LazyBinomialHeap<I> {
             min
         };
 
-        assert!(ptr::eq(min_ptr.as_ptr(), &*min)); // Sanity check
+        assert!(min.as_ptr() == min_ptr); // Sanity check
 
         // Extracting the min item and the rest of the elements from the tree that contained it
         let LeftTreeNode {
@@ -207,52 +331,90 @@ impl<I: Item> MinHeap for LazyBinomialHeap<I> {
             degree: _,
             left: min_tree_remaining, // Left child of a root can be thought of as list of trees
             right: _,
-        } = *min;
+            parent: _,
+            prev: _,
+            handle: min_handle,
+        } = min.into_inner();
 
-        let merged = LeftTreeNode::merge_matches_one_pass(list, min_tree_remaining);
-        let full_heap = Self::from_list(merged);
-        *self = full_heap;
+        if let Some(handle) = min_handle {
+            self.handles.remove(&handle);
+        }
+
+        let merged = LeftTreeNode::merge_matches_one_pass(list, min_tree_remaining, &self.compare);
+        self.set_list(merged);
+        self.len -= 1;
 
         Some(min_item)
     }
 
     fn insert(&mut self, item: Self::Item) {
-        let heap = mem::take(self);
-        let new_item = Self::with_item(item);
-        *self = Self::meld(heap, new_item);
+        self.push_root(NodeBox::new(LeftTreeNode::with_item(item)));
     }
 
-    fn heapify(items: Vec<Self::Item>) -> Self {
-        let mut heap = Self::make_heap();
-        for item in items.into_iter() {
-            heap.insert(item);
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        if self.len == 0
+            && self.head.is_none()
+            && self.tail.is_none()
+            && self.prev_min.is_none()
+            && self.min.is_none()
+        {
+            true
+        } else if self.len != 0 && self.head.is_some() && self.tail.is_some() && self.min.is_some()
+        {
+            false
+        } else {
+            // Invalid state
+            unreachable!()
         }
-        heap
     }
 
-    fn meld(heap_a: Self, heap_b: Self) -> Self {
+    fn clear(&mut self) {
+        self.set_list(None);
+        self.handles.clear();
+        self.len = 0;
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        Self::heapify_with_comparator(items, Self::default().compare)
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
         if heap_a.is_empty() {
+            // The melded heap keeps the comparator of heap_a
+            mem::swap(&mut heap_a.compare, &mut heap_b.compare);
             return heap_b;
         }
         if heap_b.is_empty() {
             return heap_a;
         }
 
-        let head_a = heap_a.head.unwrap();
+        let mut head_b = heap_b.head.take().unwrap();
+        let tail_ptr_b = heap_b.tail.unwrap();
+        let prev_min_b = heap_b.prev_min;
+        let min_ptr_b = heap_b.min.unwrap();
+
         let mut tail_ptr_a = heap_a.tail.unwrap();
         let prev_min_a = heap_a.prev_min;
         let min_ptr_a = heap_a.min.unwrap();
 
-        let head_b = heap_b.head.unwrap();
-        let tail_ptr_b = heap_b.tail.unwrap();
-        let prev_min_b = heap_b.prev_min;
-        let min_ptr_b = heap_b.min.unwrap();
+        let handles = meld_handle_maps(
+            mem::take(&mut heap_a.handles),
+            mem::take(&mut heap_b.handles),
+        );
 
         // Append list b to the tail of list a
         {
             // SAFETY: We only store valid pointers to trees
             // We own heap_a so no-one else can have a reference
             let tail_a = unsafe { tail_ptr_a.as_mut() };
+            head_b.prev = Some(tail_ptr_a);
             tail_a.right = Some(head_b);
         }
         let (prev_min, min) = {
@@ -262,7 +424,7 @@ impl<I: Item> MinHeap for LazyBinomialHeap<I> {
             let min_b = unsafe { min_ptr_b.as_ref() };
 
             // update min
-            if min_a.item <= min_b.item {
+            if heap_a.compare.le(&min_a.item, &min_b.item) {
                 (prev_min_a, min_ptr_a)
             } else if prev_min_b.is_some() {
                 (prev_min_b, min_ptr_b)
@@ -272,19 +434,363 @@ impl<I: Item> MinHeap for LazyBinomialHeap<I> {
             }
         };
 
-        Self {
-            head: Some(head_a),
-            tail: Some(tail_ptr_b),
-            prev_min,
-            min: Some(min),
+        heap_a.tail = Some(tail_ptr_b);
+        heap_a.prev_min = prev_min;
+        heap_a.min = Some(min);
+        heap_a.handles = handles;
+        heap_a.len += heap_b.len;
+        heap_a
+    }
+}
+
+impl<I, C: Compare<I>> AddressableHeap for LazyBinomialHeap<I, C> {
+    fn insert_with_handle(&mut self, item: Self::Item) -> Handle {
+        let handle = Handle::new();
+        let mut node = NodeBox::new(LeftTreeNode::with_item(item));
+        node.handle = Some(handle);
+        self.handles.insert(handle, node.as_ptr());
+        self.push_root(node);
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<&Self::Item> {
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // Since we have &self borrowed, we know there are not mutable references
+        self.handles
+            .get(&handle)
+            .map(|node| unsafe { &node.as_ref().item })
+    }
+
+    fn decrease_key(&mut self, handle: Handle, item: Self::Item) -> Result<(), HandleError> {
+        let mut node = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        {
+            // SAFETY: We only store pointers to nodes owned by the heap
+            // We hold a mutable reference to self, so no one can have any other reference
+            let node = unsafe { node.as_mut() };
+            if self.compare.gt(&item, &node.item) {
+                return Err(HandleError::KeyIncreased);
+            }
+            node.item = item;
+        }
+        let node_ptr = self.sift_up(node, false);
+
+        // SAFETY: Both point to nodes owned by the heap, and we only read through them
+        let (node, min) = unsafe { (node_ptr.as_ref(), self.min.unwrap().as_ref()) };
+        if node.parent.is_none() && self.compare.lt(&node.item, &min.item) {
+            // Item reached a root, so it may be the new min
+            self.prev_min = node.prev;
+            self.min = Some(node_ptr);
         }
+        Ok(())
+    }
+
+    fn delete(&mut self, handle: Handle) -> Result<Self::Item, HandleError> {
+        let node = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        let root_ptr = self.sift_up(node, true);
+
+        // Make the tree holding the item the min tree so extract_min removes it
+        // SAFETY: sift_up returns a root owned by the heap, and we only read through it
+        self.prev_min = unsafe { root_ptr.as_ref() }.prev;
+        self.min = Some(root_ptr);
+        Ok(self.extract_min().unwrap())
+    }
+}
+
+/// Iterator over the items of a [`LazyBinomialHeap`] in arbitrary order
+pub struct Iter<'a, I> {
+    /// Nodes whose item, children and next siblings haven't been visited yet
+    stack: Vec<&'a LeftTreeNode<I>>,
+    remaining: usize,
+}
+
+impl<'a, I> Iterator for Iter<'a, I> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.remaining -= 1;
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for Iter<'_, I> {}
+impl<I> FusedIterator for Iter<'_, I> {}
+
+/// Owning iterator over the items of a [`LazyBinomialHeap`] in arbitrary order
+pub struct IntoIter<I> {
+    /// Trees whose items haven't been returned yet
+    stack: Vec<NodeBox<LeftTreeNode<I>>>,
+    remaining: usize,
+}
+
+impl<I> Iterator for IntoIter<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?.into_inner();
+        self.stack.extend(node.right.take());
+        self.stack.extend(node.left.take());
+        self.remaining -= 1;
+        Some(node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for IntoIter<I> {}
+impl<I> FusedIterator for IntoIter<I> {}
+
+impl<I, C: Compare<I>> IntoIterator for LazyBinomialHeap<I, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl<'a, I, C: Compare<I>> IntoIterator for &'a LazyBinomialHeap<I, C> {
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<I, C: Compare<I> + Default> FromIterator<I> for LazyBinomialHeap<I, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default> From<Vec<I>> for LazyBinomialHeap<I, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default, const N: usize> From<[I; N]> for LazyBinomialHeap<I, C> {
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, C: Compare<I>> Extend<I> for LazyBinomialHeap<I, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Inserting is O(1), which is also how heapify builds the heap
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<I: Clone, C: Compare<I> + Clone> Clone for LazyBinomialHeap<I, C> {
+    /// The clone has the same items but none of the handles, which keep referring to the items in
+    /// the original heap
+    fn clone(&self) -> Self {
+        Self::heapify_with_comparator(self.iter().cloned().collect(), self.compare.clone())
+    }
+}
+
+impl<I: Debug, C: Compare<I>> Debug for LazyBinomialHeap<I, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, C: Compare<I>> PartialEq for LazyBinomialHeap<I, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, C: Compare<I>> Eq for LazyBinomialHeap<I, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> LazyBinomialHeap<I, C> {
+    /// Checks the binomial tree below `node`, returning its number of nodes
+    fn check_tree(
+        &self,
+        node: &LeftTreeNode<I>,
+        handles_found: &mut usize,
+    ) -> Result<usize, InvariantError> {
+        check_handle(
+            &self.handles,
+            node.handle,
+            NonNull::from(node),
+            handles_found,
+        )?;
+        // Children go from degree `node.degree - 1` down to 0, the first one in the left link
+        // and the rest in the right links of their siblings
+        let mut size = 1;
+        let mut children = 0;
+        let mut cursor = node.left.as_deref();
+        while let Some(child) = cursor {
+            children += 1;
+            ensure!(
+                children <= node.degree && child.degree == node.degree - children,
+                "child degrees",
+                "child {children} of a node of degree {} has degree {}",
+                node.degree,
+                child.degree
+            );
+            ensure!(
+                child.parent == Some(NonNull::from(node)),
+                "parent pointers",
+                "child {children} of a node of degree {} doesn't point to it",
+                node.degree
+            );
+            ensure!(
+                self.compare.le(&node.item, &child.item),
+                "heap order",
+                "child {children} of a node of degree {} is smaller than it",
+                node.degree
+            );
+            size += self.check_tree(child, handles_found)?;
+            cursor = child.right.as_deref();
+        }
+        ensure!(
+            children == node.degree,
+            "child degrees",
+            "node of degree {} has {children} children",
+            node.degree
+        );
+        ensure!(
+            size == 1 << node.degree,
+            "binomial tree size",
+            "tree of degree {} has {size} nodes instead of {}",
+            node.degree,
+            1usize << node.degree
+        );
+        Ok(size)
+    }
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> CheckInvariants for LazyBinomialHeap<I, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut len = 0;
+        let mut handles_found = 0;
+        let mut prev = None;
+        let mut min_found = false;
+        let mut smallest: Option<&LeftTreeNode<I>> = None;
+        let mut cursor = self.head.as_deref();
+        for i in 0.. {
+            let Some(root) = cursor else {
+                break;
+            };
+            ensure!(
+                root.parent.is_none(),
+                "parent pointers",
+                "root {i} has a parent"
+            );
+            ensure!(
+                root.prev == prev,
+                "prev pointers",
+                "root {i} doesn't point to the root before it"
+            );
+            if self.min == Some(NonNull::from(root)) {
+                ensure!(
+                    self.prev_min == prev,
+                    "prev_min pointer",
+                    "the min is root {i} but prev_min doesn't point to the root before it"
+                );
+                min_found = true;
+            }
+            if smallest.is_none_or(|smallest| self.compare.lt(&root.item, &smallest.item)) {
+                smallest = Some(root);
+            }
+            len += self.check_tree(root, &mut handles_found)?;
+            prev = Some(NonNull::from(root));
+            cursor = root.right.as_deref();
+        }
+        ensure!(
+            self.tail == prev,
+            "tail pointer",
+            "tail doesn't point to the last root"
+        );
+        match smallest {
+            Some(smallest) => {
+                ensure!(min_found, "min pointer", "min doesn't point to a root");
+                // SAFETY: min points to one of the roots walked above
+                let min = unsafe { self.min.unwrap().as_ref() };
+                ensure!(
+                    self.compare.le(&min.item, &smallest.item),
+                    "min pointer",
+                    "a root is smaller than the one min points to"
+                );
+            }
+            None => ensure!(
+                self.min.is_none() && self.prev_min.is_none(),
+                "min pointer",
+                "min or prev_min is set in an empty heap"
+            ),
+        }
+        check_len(self.len, len)?;
+        check_handle_count(&self.handles, handles_found)
+    }
+}
+
+/// Draws the list of roots side by side, marking the ones `min`, `prev_min` and `tail` point to,
+/// with the half trees drawn as the binomial trees they stand for
+impl<I: Debug, C> ToDot for LazyBinomialHeap<I, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut roots = Vec::new();
+        let mut stack = Vec::new();
+        let mut cursor = self.head.as_deref();
+        while let Some(root) = cursor {
+            let id = dot.node(&root.item, &format!("degree {}", root.degree), "");
+            let root_ptr = Some(NonNull::from(root));
+            for (name, ptr) in [
+                ("min", self.min),
+                ("prev_min", self.prev_min),
+                ("tail", self.tail),
+            ] {
+                if ptr == root_ptr {
+                    dot.marker(name, id);
+                }
+            }
+            roots.push(id);
+            stack.push((root, id));
+            cursor = root.right.as_deref();
+        }
+        dot.list(&roots);
+        // The left link of a node is its first child and the right links of the children are
+        // their next siblings
+        while let Some((node, id)) = stack.pop() {
+            let mut cursor = node.left.as_deref();
+            while let Some(child) = cursor {
+                let child_id = dot.node(&child.item, &format!("degree {}", child.degree), "");
+                dot.edge(id, child_id, "");
+                stack.push((child, child_id));
+                cursor = child.right.as_deref();
+            }
+        }
+        dot.finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::LazyBinomialHeap;
-    use crate::tests;
+    use super::*;
+    use crate::{tests, ByKey, FnCompare, MaxOrder};
 
     type HeapU32 = LazyBinomialHeap<u32>;
 
@@ -293,6 +799,10 @@ mod tests {
         tests::simple::<HeapU32>();
     }
     #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
     fn empty_heap() {
         tests::empty_heap::<HeapU32>();
     }
@@ -332,4 +842,100 @@ mod tests {
     fn meld_two_empty_heaps() {
         tests::meld_two_empty_heaps::<HeapU32>();
     }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key() {
+        tests::decrease_key::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key_many() {
+        tests::decrease_key_many::<HeapU32>();
+    }
+    #[test]
+    fn delete() {
+        tests::delete::<HeapU32>();
+    }
+    #[test]
+    fn handles_after_meld() {
+        tests::handles_after_meld::<HeapU32>();
+    }
+    #[test]
+    fn stale_handles_rejected() {
+        tests::stale_handles_rejected::<HeapU32>();
+    }
+    #[test]
+    fn clone_handles() {
+        tests::clone_handles::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<LazyBinomialHeap<u32, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| LazyBinomialHeap::with_comparator(ByKey(|item: &(u32, char)| item.0)));
+    }
+    #[test]
+    fn fn_compare_decrease_key() {
+        tests::fn_compare_decrease_key(LazyBinomialHeap::with_comparator(FnCompare(
+            |a: &u32, b: &u32| b.cmp(a),
+        )));
+    }
+
+    #[test]
+    fn broken_invariants_named() {
+        let mut heap = HeapU32::heapify(vec![0, 1, 2]);
+        assert_eq!(heap.check_invariants(), Ok(()));
+
+        let (min, tail) = (heap.min, heap.tail);
+        heap.min = tail;
+        assert_eq!(
+            heap.check_invariants().unwrap_err().invariant,
+            "prev_min pointer"
+        );
+        heap.prev_min = heap
+            .head
+            .as_ref()
+            .map(|head| head.right.as_ref().unwrap().as_ptr());
+        assert_eq!(
+            heap.check_invariants().unwrap_err().invariant,
+            "min pointer"
+        );
+        heap.min = min;
+        heap.prev_min = None;
+        heap.tail = min;
+        assert_eq!(
+            heap.check_invariants().unwrap_err().invariant,
+            "tail pointer"
+        );
+        heap.tail = tail;
+        assert_eq!(heap.check_invariants(), Ok(()));
+    }
 }
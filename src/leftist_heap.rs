@@ -0,0 +1,499 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{check_len, ensure, CheckInvariants, InvariantError};
+use crate::{
+    dot::{Dot, ToDot},
+    iter::same_items,
+    stats::count,
+    Compare, MinHeap, MinOrder,
+};
+
+struct LeftistNode<I> {
+    item: I,
+    /// Number of nodes in the right spine of the subtree, which is never longer than the left one
+    rank: usize,
+    left: LeftistLink<I>,
+    right: LeftistLink<I>,
+}
+type LeftistLink<I> = Option<Box<LeftistNode<I>>>;
+
+impl<I> LeftistNode<I> {
+    fn with_item(item: I) -> Box<Self> {
+        count!(allocations);
+        Box::new(Self {
+            item,
+            rank: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn rank(link: &LeftistLink<I>) -> usize {
+        link.as_ref().map_or(0, |node| node.rank)
+    }
+
+    /// Drops a tree one node at a time, since dropping the boxes recursively can overflow the
+    /// stack on long left paths
+    fn drop_tree(tree: LeftistLink<I>) {
+        let mut stack: Vec<Box<Self>> = tree.into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+
+    /// Merges the right spines of both trees, then walks back up swapping children wherever the
+    /// right spine got longer than the left one
+    fn merge(
+        mut tree_a: LeftistLink<I>,
+        mut tree_b: LeftistLink<I>,
+        compare: &impl Compare<I>,
+    ) -> LeftistLink<I> {
+        // Nodes of the merged right spine, from the top down
+        let mut spine = Vec::new();
+        let mut merged = loop {
+            match (tree_a, tree_b) {
+                (Some(a), Some(b)) => {
+                    let (mut smaller, larger) = if compare.gt(&a.item, &b.item) {
+                        (b, a)
+                    } else {
+                        (a, b)
+                    };
+                    tree_a = smaller.right.take();
+                    tree_b = Some(larger);
+                    spine.push(smaller);
+                    count!(links);
+                }
+                (rest, None) | (None, rest) => break rest,
+            }
+        };
+
+        while let Some(mut node) = spine.pop() {
+            node.right = merged;
+            if Self::rank(&node.left) < Self::rank(&node.right) {
+                mem::swap(&mut node.left, &mut node.right);
+            }
+            node.rank = Self::rank(&node.right) + 1;
+            merged = Some(node);
+        }
+        merged
+    }
+}
+
+/// Heap-ordered binary tree where the right spine of every subtree is no longer than its left
+/// one, so it has O(log n) nodes and melding along right spines takes O(log n)
+pub struct LeftistHeap<I, C = MinOrder> {
+    root: LeftistLink<I>,
+    len: usize,
+    compare: C,
+}
+
+impl<I, C: Compare<I> + Default> Default for LeftistHeap<I, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, C: Compare<I>> LeftistHeap<I, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            compare,
+        }
+    }
+
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        Self {
+            len: items.len(),
+            root: Self::heapify_tree(items, &compare),
+            compare,
+        }
+    }
+
+    /// Melds the items in pairs, then the pairs in pairs and so on, which takes O(n) in total
+    fn heapify_tree(items: Vec<I>, compare: &C) -> LeftistLink<I> {
+        let mut queue: VecDeque<Box<LeftistNode<I>>> =
+            items.into_iter().map(LeftistNode::with_item).collect();
+        while queue.len() > 1 {
+            let tree_a = queue.pop_front();
+            let tree_b = queue.pop_front();
+            queue.extend(LeftistNode::merge(tree_a, tree_b, compare));
+        }
+        queue.pop_front()
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I> {
+        Iter {
+            stack: self.root.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<I> {
+        let iter = IntoIter {
+            stack: self.root.take().into_iter().collect(),
+            remaining: self.len,
+        };
+        self.len = 0;
+        iter
+    }
+}
+
+impl<I, C: Compare<I>> MinHeap for LeftistHeap<I, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        self.root.as_ref().map(|root| &root.item)
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        let mut root = self.root.take()?;
+        self.root = LeftistNode::merge(root.left.take(), root.right.take(), &self.compare);
+        self.len -= 1;
+        Some(root.item)
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        let root = self.root.take();
+        self.root = LeftistNode::merge(root, Some(LeftistNode::with_item(item)), &self.compare);
+        self.len += 1;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        LeftistNode::drop_tree(self.root.take());
+        self.len = 0;
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        let mut heap = Self::default();
+        heap.extend(items);
+        heap
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        let root_a = heap_a.root.take();
+        heap_a.root = LeftistNode::merge(root_a, heap_b.root.take(), &heap_a.compare);
+        heap_a.len += heap_b.len;
+        heap_a
+    }
+}
+
+impl<I, C> Drop for LeftistHeap<I, C> {
+    fn drop(&mut self) {
+        LeftistNode::drop_tree(self.root.take());
+    }
+}
+
+/// Iterator over the items of a [`LeftistHeap`] in arbitrary order
+pub struct Iter<'a, I> {
+    /// Nodes whose item and children haven't been visited yet
+    stack: Vec<&'a LeftistNode<I>>,
+    remaining: usize,
+}
+
+impl<'a, I> Iterator for Iter<'a, I> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.remaining -= 1;
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for Iter<'_, I> {}
+impl<I> FusedIterator for Iter<'_, I> {}
+
+/// Owning iterator over the items of a [`LeftistHeap`] in arbitrary order
+pub struct IntoIter<I> {
+    /// Trees whose items haven't been returned yet
+    stack: Vec<Box<LeftistNode<I>>>,
+    remaining: usize,
+}
+
+impl<I> Iterator for IntoIter<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        self.stack.extend(node.right.take());
+        self.stack.extend(node.left.take());
+        self.remaining -= 1;
+        Some(node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for IntoIter<I> {}
+impl<I> FusedIterator for IntoIter<I> {}
+
+impl<I> Drop for IntoIter<I> {
+    fn drop(&mut self) {
+        // Takes the remaining nodes apart one at a time like drop_tree
+        self.for_each(drop);
+    }
+}
+
+impl<I, C: Compare<I>> IntoIterator for LeftistHeap<I, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl<'a, I, C: Compare<I>> IntoIterator for &'a LeftistHeap<I, C> {
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<I, C: Compare<I> + Default> FromIterator<I> for LeftistHeap<I, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default> From<Vec<I>> for LeftistHeap<I, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default, const N: usize> From<[I; N]> for LeftistHeap<I, C> {
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, C: Compare<I>> Extend<I> for LeftistHeap<I, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Heapify the new items on their own and meld them in, like meld would with a second heap
+        let items: Vec<I> = iter.into_iter().collect();
+        self.len += items.len();
+        let tree = Self::heapify_tree(items, &self.compare);
+        self.root = LeftistNode::merge(self.root.take(), tree, &self.compare);
+    }
+}
+
+impl<I: Clone, C: Compare<I> + Clone> Clone for LeftistHeap<I, C> {
+    fn clone(&self) -> Self {
+        Self::heapify_with_comparator(self.iter().cloned().collect(), self.compare.clone())
+    }
+}
+
+impl<I: Debug, C: Compare<I>> Debug for LeftistHeap<I, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, C: Compare<I>> PartialEq for LeftistHeap<I, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, C: Compare<I>> Eq for LeftistHeap<I, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> CheckInvariants for LeftistHeap<I, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut len = 0;
+        let mut stack: Vec<&LeftistNode<I>> = self.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            len += 1;
+            let left_rank = LeftistNode::rank(&node.left);
+            let right_rank = LeftistNode::rank(&node.right);
+            ensure!(
+                right_rank <= left_rank,
+                "leftist property",
+                "node of rank {} has a right child of rank {right_rank} and a left one of rank \
+                 {left_rank}",
+                node.rank
+            );
+            ensure!(
+                node.rank == right_rank + 1,
+                "rank",
+                "node of rank {} has a right child of rank {right_rank}",
+                node.rank
+            );
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                ensure!(
+                    self.compare.le(&node.item, &child.item),
+                    "heap order",
+                    "a child of a node of rank {} is smaller than it",
+                    node.rank
+                );
+                stack.push(child);
+            }
+        }
+        check_len(self.len, len)
+    }
+}
+
+/// Draws the tree with each node labelled with its rank
+impl<I: Debug, C> ToDot for LeftistHeap<I, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut stack = Vec::new();
+        if let Some(root) = self.root.as_deref() {
+            stack.push((root, None));
+        }
+        while let Some((node, parent)) = stack.pop() {
+            let id = dot.node(&node.item, &format!("rank {}", node.rank), "");
+            if let Some((parent_id, attributes)) = parent {
+                dot.edge(parent_id, id, attributes);
+            }
+            if let Some(right) = node.right.as_deref() {
+                stack.push((right, Some((id, "label=right"))));
+            }
+            if let Some(left) = node.left.as_deref() {
+                stack.push((left, Some((id, "label=left"))));
+            }
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests, ByKey, MaxOrder};
+
+    type HeapU32 = LeftistHeap<u32>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<LeftistHeap<u32, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| LeftistHeap::with_comparator(ByKey(|item: &(u32, char)| item.0)));
+    }
+
+    #[test]
+    fn leftist_property() {
+        let mut heap = HeapU32::heapify((0..500).map(|i| (i * 7919) % 500).collect());
+        heap.check_invariants().unwrap();
+        for i in 0..200 {
+            heap.insert((i * 31) % 97);
+            if i % 3 == 0 {
+                heap.extract_min();
+            }
+            heap.check_invariants().unwrap();
+        }
+        let other = HeapU32::heapify((0..300).rev().collect());
+        let heap = HeapU32::meld(heap, other);
+        heap.check_invariants().unwrap();
+        assert!(heap.root.as_ref().unwrap().rank <= (heap.len() + 1).ilog2() as usize);
+    }
+}
@@ -1,8 +1,33 @@
 #![warn(clippy::all, clippy::cargo)]
 
-mod binary_heap;
-mod binomial_heap;
-mod lazy_binomial;
+mod arena;
+pub mod arena_binomial_heap;
+pub mod arena_lazy_binomial;
+pub mod binary_heap;
+pub mod binomial_heap;
+pub mod bounded_heap;
+pub mod bucket_queue;
+mod compare;
+pub mod concurrent;
+pub mod dary_heap;
+mod dot;
+pub mod fibonacci_heap;
+mod handle;
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+mod invariants;
+mod iter;
+mod key_value;
+pub mod lazy_binomial;
+pub mod leftist_heap;
+pub mod min_max_heap;
+mod node_box;
+pub mod pairing_heap;
+pub mod persistent_heap;
+pub mod radix_heap;
+pub mod rank_pairing_heap;
+pub mod skew_heap;
+pub mod stable;
+mod stats;
 
 #[cfg(test)]
 mod tests;
@@ -10,19 +35,101 @@ mod tests;
 pub trait Item: Ord {}
 impl<I: Ord> Item for I {}
 
-pub trait MinHeap: Default {
+/// Heap whose min is the first item in the order given by its comparator, which defaults to
+/// [`MinOrder`] for every heap in the crate
+pub trait MinHeap: Sized {
     type Item;
 
-    fn make_heap() -> Self {
+    /// Needs a comparator that implements `Default`, otherwise use the heap's `with_comparator`
+    fn make_heap() -> Self
+    where
+        Self: Default,
+    {
         Self::default()
     }
     fn peek_min(&self) -> Option<&Self::Item>;
     fn extract_min(&mut self) -> Option<Self::Item>;
     fn insert(&mut self, item: Self::Item);
-    fn heapify(items: Vec<Self::Item>) -> Self;
+    /// Number of items in the heap
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Removes all items from the heap, keeping its comparator
+    fn clear(&mut self);
+    /// Extracts the items in the heap's order, leaving it empty even if the iterator isn't used up
+    fn drain_sorted(&mut self) -> DrainSorted<'_, Self> {
+        DrainSorted::new(self)
+    }
+    /// Items of the heap in the order `extract_min` would return them
+    fn into_sorted_vec(mut self) -> Vec<Self::Item> {
+        self.drain_sorted().collect()
+    }
+    /// Needs a comparator that implements `Default`, otherwise use the heap's
+    /// `heapify_with_comparator`
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default;
+    /// The melded heap keeps the comparator of `heap_a`, so both heaps should order their items
+    /// the same way. Comparators of the same type can still differ, like two `FnCompare`s holding
+    /// different function pointers. The array heaps then put the items of `heap_b` back in order,
+    /// but most node-based heaps link its trees in as they are, and `extract_min` returns their
+    /// items out of order
     fn meld(heap_a: Self, heap_b: Self) -> Self;
 }
 
+/// A [`MinHeap`] whose items can be changed or removed after insertion through a [`Handle`]
+pub trait AddressableHeap: MinHeap {
+    /// Inserts an item and returns a handle that refers to it while it stays in the heap
+    fn insert_with_handle(&mut self, item: Self::Item) -> Handle;
+    /// Returns the item the handle refers to
+    fn get(&self, handle: Handle) -> Option<&Self::Item>;
+    /// Replaces the item the handle refers to with one that is not greater in the heap's order
+    fn decrease_key(&mut self, handle: Handle, item: Self::Item) -> Result<(), HandleError>;
+    /// Removes the item the handle refers to from the heap
+    fn delete(&mut self, handle: Handle) -> Result<Self::Item, HandleError>;
+}
+
+/// A [`MinHeap`] that can also look at and remove its max item, the last one in the heap's order
+pub trait DoubleEndedHeap: MinHeap {
+    fn peek_max(&self) -> Option<&Self::Item>;
+    fn extract_max(&mut self) -> Option<Self::Item>;
+    /// Inserts an item, then extracts the max, which can be the item itself
+    fn push_pop_max(&mut self, item: Self::Item) -> Self::Item {
+        self.insert(item);
+        self.extract_max().unwrap()
+    }
+}
+
+pub use arena_binomial_heap::ArenaBinomialHeap;
+pub use arena_lazy_binomial::ArenaLazyBinomialHeap;
 pub use binary_heap::BinaryHeap;
 pub use binomial_heap::BinomialHeap;
+pub use bounded_heap::BoundedHeap;
+pub use bucket_queue::BucketQueue;
+pub use compare::{ByKey, Compare, FnCompare, MaxOrder, MinOrder};
+pub use concurrent::{LockedHeap, MultiQueue};
+pub use dary_heap::DaryHeap;
+pub use dot::ToDot;
+pub use fibonacci_heap::FibonacciHeap;
+pub use handle::{Handle, HandleError};
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+pub use invariants::{CheckInvariants, InvariantError};
+pub use iter::DrainSorted;
+pub use key_value::{
+    BinaryKeyValueHeap, BinomialKeyValueHeap, FibonacciKeyValueHeap, KeyOrder, KeyValue,
+    KeyValueHeap, LazyBinomialKeyValueHeap, PairingKeyValueHeap,
+};
 pub use lazy_binomial::LazyBinomialHeap;
+pub use leftist_heap::LeftistHeap;
+pub use min_max_heap::MinMaxHeap;
+pub use pairing_heap::{FrontToBack, Multipass, PairingHeap, PairingStrategy, TwoPass};
+pub use persistent_heap::{ArcPointer, PersistentLeftistHeap, RcPointer, SharedPointer};
+pub use radix_heap::{MonotoneError, RadixHeap, RadixKey};
+pub use rank_pairing_heap::{RankPairingHeap, RankRule, Type1, Type2};
+pub use skew_heap::SkewHeap;
+pub use stable::{
+    StableBinaryHeap, StableBinomialHeap, StableFibonacciHeap, StableHeap, StableLazyBinomialHeap,
+    StableOrder, StablePairingHeap, Stamped,
+};
+pub use stats::{CountingOrder, Stats};
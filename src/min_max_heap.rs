@@ -0,0 +1,553 @@
+use std::{
+    fmt::{self, Debug},
+    mem, slice, vec,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{ensure, CheckInvariants, InvariantError};
+use crate::{
+    dot::{Dot, ToDot},
+    iter::same_items,
+    stats::count,
+    Compare, DoubleEndedHeap, MinHeap, MinOrder,
+};
+
+/// Heap stored as a complete binary tree in an array like [`BinaryHeap`](crate::BinaryHeap),
+/// where the levels alternate between being ordered like a min-heap and like a max-heap. The
+/// root is the min item and one of its children is the max item
+#[derive(Clone)]
+pub struct MinMaxHeap<I, C = MinOrder> {
+    array: Vec<I>,
+    compare: C,
+}
+
+#[inline(always)]
+const fn parent(i: usize) -> usize {
+    (i - 1) / 2
+}
+#[inline(always)]
+const fn first_child(i: usize) -> usize {
+    2 * i + 1
+}
+#[inline(always)]
+const fn first_grandchild(i: usize) -> usize {
+    4 * i + 3
+}
+/// The root is on level 0, a min level, and the levels below alternate
+#[inline(always)]
+const fn is_max_level(i: usize) -> bool {
+    (i + 1).ilog2() % 2 == 1
+}
+
+impl<I, C: Compare<I>> MinMaxHeap<I, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            array: Vec::new(),
+            compare,
+        }
+    }
+
+    /// Empty heap that can hold `capacity` items before reallocating
+    pub fn with_capacity(capacity: usize) -> Self
+    where
+        C: Default,
+    {
+        Self::with_capacity_and_comparator(capacity, C::default())
+    }
+
+    pub fn with_capacity_and_comparator(capacity: usize, compare: C) -> Self {
+        Self {
+            array: Vec::with_capacity(capacity),
+            compare,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.array.capacity()
+    }
+
+    /// Reserves space for at least `additional` more items
+    pub fn reserve(&mut self, additional: usize) {
+        self.array.reserve(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.array.shrink_to_fit();
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> slice::Iter<'_, I> {
+        self.array.iter()
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> vec::Drain<'_, I> {
+        self.array.drain(..)
+    }
+
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        let mut heap = Self {
+            array: items,
+            compare,
+        };
+        heap.rebuild();
+        heap
+    }
+
+    /// Inserts an item, then extracts the min, which can be the item itself. Takes a single sift
+    /// down instead of the sift up and sift down of calling both
+    pub fn push_pop(&mut self, item: I) -> I {
+        match self.array.first_mut() {
+            Some(min) if self.compare.gt(&item, min) => {
+                let min = mem::replace(min, item);
+                self.sift_down(0);
+                min
+            }
+            _ => item,
+        }
+    }
+
+    /// Restores the heap property of the whole array
+    fn rebuild(&mut self) {
+        for i in (0..self.array.len() / 2).rev() {
+            self.sift_down(i);
+        }
+    }
+
+    /// Index of the max item
+    fn max_index(&self) -> Option<usize> {
+        match self.array.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ if self.compare.gt(&self.array[2], &self.array[1]) => Some(2),
+            _ => Some(1),
+        }
+    }
+
+    /// Whether the item at `i` goes before the item at `j` in the order of a max level if `max`,
+    /// or of a min level otherwise
+    fn precedes(&self, i: usize, j: usize, max: bool) -> bool {
+        if max {
+            self.compare.gt(&self.array[i], &self.array[j])
+        } else {
+            self.compare.lt(&self.array[i], &self.array[j])
+        }
+    }
+
+    /// Removes the item at index `i`, which must be the min or the max, and restores the heap
+    /// property
+    fn remove(&mut self, i: usize) -> I {
+        let item = self.array.swap_remove(i);
+        if i < self.array.len() {
+            self.sift_down(i);
+        }
+        item
+    }
+
+    fn sift_up(&mut self, i: usize) {
+        debug_assert!(i < self.array.len());
+        if i == 0 {
+            return;
+        }
+        let max = is_max_level(i);
+        let parent = parent(i);
+        if self.precedes(i, parent, !max) {
+            // The item belongs to the levels ordered the other way
+            self.array.swap(i, parent);
+            count!(swaps);
+            self.sift_up_levels(parent, !max);
+        } else {
+            self.sift_up_levels(i, max);
+        }
+    }
+
+    /// Sifts up through the grandparents, which are on levels ordered the same way as `i`
+    fn sift_up_levels(&mut self, mut i: usize, max: bool) {
+        while i > 2 {
+            let grandparent = parent(parent(i));
+            if !self.precedes(i, grandparent, max) {
+                break;
+            }
+            self.array.swap(i, grandparent);
+            count!(swaps);
+            i = grandparent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        debug_assert!(i < self.array.len());
+        let max = is_max_level(i);
+        let len = self.array.len();
+        loop {
+            let child = first_child(i);
+            let grandchild = first_grandchild(i);
+            // Min of the children and grandchildren on a min level, max on a max level
+            let Some(next) = (child..child + 2)
+                .chain(grandchild..grandchild + 4)
+                .take_while(|&j| j < len)
+                .reduce(|next, j| if self.precedes(j, next, max) { j } else { next })
+            else {
+                return;
+            };
+            if !self.precedes(next, i, max) {
+                return;
+            }
+            self.array.swap(next, i);
+            count!(swaps);
+            if next < grandchild {
+                // Children have no children of their own here
+                return;
+            }
+            if self.precedes(parent(next), next, max) {
+                // The item went past the level between, which is ordered the other way
+                self.array.swap(next, parent(next));
+                count!(swaps);
+            }
+            i = next;
+        }
+    }
+}
+
+impl<I, C: Compare<I> + Default> Default for MinMaxHeap<I, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, C: Compare<I>> MinHeap for MinMaxHeap<I, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        self.array.first()
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        if self.array.is_empty() {
+            return None;
+        }
+        Some(self.remove(0))
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        self.array.push(item);
+        self.sift_up(self.array.len() - 1);
+    }
+
+    fn len(&self) -> usize {
+        self.array.len()
+    }
+
+    fn clear(&mut self) {
+        self.array.clear();
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        Self::heapify_with_comparator(items, Self::default().compare)
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        heap_a.array.append(&mut heap_b.array);
+        heap_a.rebuild();
+        heap_a
+    }
+}
+
+impl<I, C: Compare<I>> DoubleEndedHeap for MinMaxHeap<I, C> {
+    fn peek_max(&self) -> Option<&Self::Item> {
+        self.max_index().map(|i| &self.array[i])
+    }
+
+    fn extract_max(&mut self) -> Option<Self::Item> {
+        let i = self.max_index()?;
+        Some(self.remove(i))
+    }
+
+    fn push_pop_max(&mut self, mut item: Self::Item) -> Self::Item {
+        let Some(i) = self.max_index() else {
+            return item;
+        };
+        if !self.compare.lt(&item, &self.array[i]) {
+            return item;
+        }
+        if i == 0 {
+            return mem::replace(&mut self.array[0], item);
+        }
+        if self.compare.lt(&item, &self.array[0]) {
+            // The item is the new min, and the old min is sifted down from the max's place
+            mem::swap(&mut item, &mut self.array[0]);
+        }
+        let max = mem::replace(&mut self.array[i], item);
+        self.sift_down(i);
+        max
+    }
+}
+
+impl<I, C> IntoIterator for MinMaxHeap<I, C> {
+    type Item = I;
+    type IntoIter = vec::IntoIter<I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.array.into_iter()
+    }
+}
+
+impl<'a, I, C> IntoIterator for &'a MinMaxHeap<I, C> {
+    type Item = &'a I;
+    type IntoIter = slice::Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.array.iter()
+    }
+}
+
+impl<I, C: Compare<I> + Default> FromIterator<I> for MinMaxHeap<I, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default> From<Vec<I>> for MinMaxHeap<I, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, C: Compare<I> + Default, const N: usize> From<[I; N]> for MinMaxHeap<I, C> {
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, C: Compare<I>> Extend<I> for MinMaxHeap<I, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        let old_len = self.array.len();
+        self.array.extend(iter);
+
+        // Same trade-off as DaryHeap::extend between rebuilding and sifting up each new item
+        let added = self.array.len() - old_len;
+        let log_len = (usize::BITS - self.array.len().leading_zeros()) as usize;
+        if 2 * self.array.len() < added * log_len {
+            self.rebuild();
+        } else {
+            for i in old_len..self.array.len() {
+                self.sift_up(i);
+            }
+        }
+    }
+}
+
+impl<I: Debug, C> Debug for MinMaxHeap<I, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.array.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, C: Compare<I>> PartialEq for MinMaxHeap<I, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.array.iter(), other.array.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, C: Compare<I>> Eq for MinMaxHeap<I, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, C: Compare<I>> CheckInvariants for MinMaxHeap<I, C> {
+    /// Comparing each item with its children and grandchildren is enough, since the grandchildren
+    /// are on a level of the same kind and the children's own children are checked against them
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        for i in 0..self.array.len() {
+            let first = first_child(i);
+            let grandchildren = first_grandchild(i)..first_grandchild(i) + 4;
+            for j in (first..first + 2).chain(grandchildren) {
+                let Some(descendant) = self.array.get(j) else {
+                    break;
+                };
+                if is_max_level(i) {
+                    ensure!(
+                        self.compare.ge(&self.array[i], descendant),
+                        "min-max order",
+                        "item at index {i} on a max level is smaller than its descendant at \
+                         index {j}"
+                    );
+                } else {
+                    ensure!(
+                        self.compare.le(&self.array[i], descendant),
+                        "min-max order",
+                        "item at index {i} on a min level is greater than its descendant at \
+                         index {j}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draws the array as the tree it stands for, with the items on max levels in boxes
+impl<I: Debug, C> ToDot for MinMaxHeap<I, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        for (i, item) in self.array.iter().enumerate() {
+            let shape = if is_max_level(i) { "shape=box" } else { "" };
+            // Items are added in array order, so the node ids are the indices
+            dot.node(item, &format!("[{i}]"), shape);
+            if i > 0 {
+                dot.edge(parent(i), i, "");
+            }
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests, ByKey, MaxOrder};
+
+    type HeapU32 = MinMaxHeap<u32>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<MinMaxHeap<u32, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| MinMaxHeap::with_comparator(ByKey(|item: &(u32, char)| item.0)));
+    }
+    #[test]
+    fn double_ended() {
+        tests::double_ended::<HeapU32>();
+    }
+    #[test]
+    fn push_pop_max() {
+        tests::push_pop_max::<HeapU32>();
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut heap = HeapU32::from([5, 1, 9, 3]);
+        assert_eq!(heap.push_pop(0), 0);
+        assert_eq!(heap.push_pop(4), 1);
+        assert_eq!(heap.push_pop(20), 3);
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.peek_max(), Some(&20));
+        assert_eq!(heap.into_sorted_vec(), vec![4, 5, 9, 20]);
+
+        let mut heap = HeapU32::default();
+        assert_eq!(heap.push_pop(7), 7);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn levels_ordered() {
+        let mut heap =
+            HeapU32::heapify((0..tests::sized(1000)).map(|i| (i * 7919) % 1000).collect());
+        for i in 0..tests::sized(3000) {
+            match i % 4 {
+                0 => heap.insert((i * 31) % 1009),
+                1 => drop(heap.push_pop(i % 1013)),
+                2 => drop(heap.extract_max()),
+                _ => drop(heap.push_pop_max(i % 997)),
+            }
+            // Every item on a min level is at most all its descendants, and at least them on a
+            // max level
+            for j in 1..heap.len() {
+                let mut ancestor = parent(j);
+                loop {
+                    if is_max_level(ancestor) {
+                        assert!(heap.array[ancestor] >= heap.array[j]);
+                    } else {
+                        assert!(heap.array[ancestor] <= heap.array[j]);
+                    }
+                    if ancestor == 0 {
+                        break;
+                    }
+                    ancestor = parent(ancestor);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+/// Node of a node-based heap that owns other nodes through its links
+pub(crate) trait TreeNode: Sized {
+    /// Takes the nodes out of the links, leaving them empty
+    fn take_links(&mut self) -> [Option<NodeBox<Self>>; 2];
+}
+
+/// Owning pointer to a node of a node-based heap, which frees the node when dropped like a `Box`,
+/// along with the nodes it links to one at a time so dropping a long list can't overflow the stack.
+/// Unlike a `Box`, moving it doesn't claim unique access to the node, so the raw pointers a heap
+/// keeps to its nodes for handles, parents and list ends stay valid while the node is moved
+/// between lists. Those pointers have to be copies of [`NodeBox::as_ptr`], since a pointer made
+/// from a reference to the node is invalidated by the next access through the `NodeBox`
+pub(crate) struct NodeBox<T: TreeNode> {
+    ptr: NonNull<T>,
+    /// Tells the drop checker a `T` is dropped along with the `NodeBox`
+    node: PhantomData<T>,
+}
+
+impl<T: TreeNode> NodeBox<T> {
+    pub(crate) fn new(node: T) -> Self {
+        Self {
+            ptr: NonNull::from(Box::leak(Box::new(node))),
+            node: PhantomData,
+        }
+    }
+
+    /// Pointer to the node that stays valid until the node is dropped or taken out of its box
+    pub(crate) fn as_ptr(&self) -> NonNull<T> {
+        self.ptr
+    }
+
+    /// Takes the node out of its box and frees the allocation
+    pub(crate) fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: The pointer came from `Box::leak` and this is the only `NodeBox` owning it,
+        // which won't free it again since it is never dropped
+        *unsafe { Box::from_raw(this.ptr.as_ptr()) }
+    }
+}
+
+impl<T: TreeNode> Deref for NodeBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: The node is alive as long as the `NodeBox` that owns it. Heaps only access a
+        // node through their other raw pointers to it while holding a mutable reference to
+        // themselves, so no reference returned here is in use at the same time
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: TreeNode> DerefMut for NodeBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: Same as `deref`, and the mutable borrow of the `NodeBox` keeps other
+        // references made through it from being used at the same time
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T: TreeNode + Debug> Debug for NodeBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: TreeNode> Drop for NodeBox<T> {
+    fn drop(&mut self) {
+        // SAFETY: The pointer came from `Box::leak` and this is the only `NodeBox` owning it
+        let mut node = unsafe { Box::from_raw(self.ptr.as_ptr()) };
+        let mut stack: Vec<Self> = node.take_links().into_iter().flatten().collect();
+        drop(node);
+        // Each node is dropped with its links already empty, so this doesn't recurse
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.take_links().into_iter().flatten());
+        }
+    }
+}
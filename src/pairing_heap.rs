@@ -0,0 +1,748 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+    ptr::NonNull,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{
+    check_handle, check_handle_count, check_len, ensure, CheckInvariants, InvariantError,
+};
+use crate::{
+    dot::{Dot, ToDot},
+    handle::meld_handle_maps,
+    iter::same_items,
+    node_box::{NodeBox, TreeNode},
+    stats::count,
+    AddressableHeap, Compare, Handle, HandleError, MinHeap, MinOrder,
+};
+
+/// How [`PairingHeap::extract_min`](crate::MinHeap::extract_min) combines the children of the
+/// removed root back into a single tree
+pub trait PairingStrategy {
+    /// Combines the trees, in the order they appear in the list of children, into one tree by
+    /// repeatedly calling `link` on two of them
+    fn combine<T>(trees: Vec<T>, link: impl FnMut(T, T) -> T) -> Option<T>;
+}
+
+/// Links the trees in pairs from front to back, then links the results from back to front
+pub struct TwoPass;
+/// Links the trees in pairs from front to back, then links the results from front to back
+pub struct FrontToBack;
+/// Keeps linking the trees in pairs from front to back until there is only one left
+pub struct Multipass;
+
+/// First pass shared by [`TwoPass`] and [`FrontToBack`]
+fn link_pairs<T>(trees: Vec<T>, link: &mut impl FnMut(T, T) -> T) -> Vec<T> {
+    let mut pairs = Vec::with_capacity(trees.len().div_ceil(2));
+    let mut trees = trees.into_iter();
+    while let Some(tree_a) = trees.next() {
+        match trees.next() {
+            Some(tree_b) => pairs.push(link(tree_a, tree_b)),
+            None => pairs.push(tree_a),
+        }
+    }
+    pairs
+}
+
+impl PairingStrategy for TwoPass {
+    fn combine<T>(trees: Vec<T>, mut link: impl FnMut(T, T) -> T) -> Option<T> {
+        link_pairs(trees, &mut link)
+            .into_iter()
+            .rev()
+            .reduce(|combined, tree| link(tree, combined))
+    }
+}
+
+impl PairingStrategy for FrontToBack {
+    fn combine<T>(trees: Vec<T>, mut link: impl FnMut(T, T) -> T) -> Option<T> {
+        link_pairs(trees, &mut link).into_iter().reduce(link)
+    }
+}
+
+impl PairingStrategy for Multipass {
+    fn combine<T>(trees: Vec<T>, mut link: impl FnMut(T, T) -> T) -> Option<T> {
+        let mut queue = VecDeque::from(trees);
+        while queue.len() > 1 {
+            let tree_a = queue.pop_front().unwrap();
+            let tree_b = queue.pop_front().unwrap();
+            queue.push_back(link(tree_a, tree_b));
+        }
+        queue.pop_front()
+    }
+}
+
+struct PairingNode<I> {
+    item: I,
+    /// First child
+    left: PairingLink<I>,
+    /// Next sibling
+    right: PairingLink<I>,
+    /// Node whose left or right link owns this node, or None if node is the root
+    prev: Option<NonNull<PairingNode<I>>>,
+    /// Handle of the item, if it was inserted with one
+    handle: Option<Handle>,
+}
+type PairingLink<I> = Option<NodeBox<PairingNode<I>>>;
+
+impl<I> TreeNode for PairingNode<I> {
+    fn take_links(&mut self) -> [Option<NodeBox<Self>>; 2] {
+        [self.right.take(), self.left.take()]
+    }
+}
+
+impl<I> PairingNode<I> {
+    fn with_item(item: I) -> NodeBox<Self> {
+        count!(allocations);
+        NodeBox::new(Self {
+            item,
+            left: None,
+            right: None,
+            prev: None,
+            handle: None,
+        })
+    }
+
+    fn link(
+        mut tree: NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) -> NodeBox<Self> {
+        if compare.gt(&tree.item, &other.item) {
+            mem::swap(&mut tree, &mut other);
+        }
+        Self::link_as_child(&mut tree, other, compare);
+        tree
+    }
+
+    fn link_as_child(
+        tree: &mut NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) {
+        count!(links);
+        debug_assert!(other.right.is_none());
+        debug_assert!(compare.le(&tree.item, &other.item));
+
+        let other_ptr = other.as_ptr();
+        if let Some(first_child) = tree.left.as_mut() {
+            first_child.prev = Some(other_ptr);
+        }
+        other.right = tree.left.take();
+        other.prev = Some(tree.as_ptr());
+        tree.left = Some(other);
+    }
+
+    /// Removes the children of the node and combines them into one tree
+    fn combine_children<S: PairingStrategy>(
+        &mut self,
+        compare: &impl Compare<I>,
+    ) -> PairingLink<I> {
+        let mut children = Vec::new();
+        let mut cursor = self.left.take();
+        while let Some(mut child) = cursor {
+            cursor = child.right.take();
+            child.prev = None;
+            children.push(child);
+        }
+        S::combine(children, |tree_a, tree_b| {
+            Self::link(tree_a, tree_b, compare)
+        })
+    }
+}
+
+/// Pairing heap where `S` picks how the children of the min are combined in `extract_min`
+pub struct PairingHeap<I, S: PairingStrategy = TwoPass, C = MinOrder> {
+    /// Root of the heap-ordered tree
+    root: PairingLink<I>,
+    /// Node holding the item of each handle in the heap
+    handles: HashMap<Handle, NonNull<PairingNode<I>>>,
+    /// Number of items in the heap
+    len: usize,
+    /// Order of the items
+    compare: C,
+    strategy: PhantomData<S>,
+}
+
+impl<I, S: PairingStrategy, C: Compare<I> + Default> Default for PairingHeap<I, S, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, S: PairingStrategy, C: Compare<I>> PairingHeap<I, S, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            root: None,
+            handles: HashMap::new(),
+            len: 0,
+            compare,
+            strategy: PhantomData,
+        }
+    }
+
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        let mut heap = Self::with_comparator(compare);
+        for item in items.into_iter() {
+            heap.insert(item);
+        }
+        heap
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I> {
+        Iter {
+            stack: self.root.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<I> {
+        let iter = IntoIter {
+            stack: self.root.take().into_iter().collect(),
+            remaining: self.len,
+        };
+        self.clear();
+        iter
+    }
+
+    /// Detaches the subtree rooted at a non-root node, putting its right sibling in its place
+    fn unlink(&mut self, mut node_ptr: NonNull<PairingNode<I>>) -> NodeBox<PairingNode<I>> {
+        // SAFETY: We only store pointers to nodes owned by the heap, and a node is never its own
+        // prev, so the two mutable references don't alias.
+        // We hold a mutable reference to self, so no one can have any other reference
+        let node = unsafe { node_ptr.as_mut() };
+        let mut prev_ptr = node.prev.take().unwrap();
+        let prev_node = unsafe { prev_ptr.as_mut() };
+        let link = if prev_node
+            .left
+            .as_ref()
+            .is_some_and(|left| left.as_ptr() == node_ptr)
+        {
+            &mut prev_node.left
+        } else {
+            &mut prev_node.right
+        };
+        let mut boxed_node = link.take().unwrap();
+        debug_assert!(boxed_node.as_ptr() == node_ptr);
+
+        *link = boxed_node.right.take();
+        if let Some(next) = link {
+            next.prev = Some(prev_ptr);
+        }
+        boxed_node
+    }
+
+    /// Links a tree with the root of the heap
+    fn push_tree(&mut self, tree: NodeBox<PairingNode<I>>) {
+        self.root = Some(match self.root.take() {
+            Some(root) => PairingNode::link(root, tree, &self.compare),
+            None => tree,
+        });
+    }
+}
+
+impl<I, S: PairingStrategy, C: Compare<I>> MinHeap for PairingHeap<I, S, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        self.root.as_ref().map(|root| &root.item)
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        let mut root = self.root.take()?;
+        self.root = root.combine_children::<S>(&self.compare);
+
+        if let Some(handle) = root.handle {
+            self.handles.remove(&handle);
+        }
+        self.len -= 1;
+        Some(root.into_inner().item)
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        self.push_tree(PairingNode::with_item(item));
+        self.len += 1;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.root = None;
+        self.handles.clear();
+        self.len = 0;
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        Self::heapify_with_comparator(items, Self::default().compare)
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        heap_a.handles = meld_handle_maps(
+            mem::take(&mut heap_a.handles),
+            mem::take(&mut heap_b.handles),
+        );
+        heap_a.len += heap_b.len;
+        if let Some(root_b) = heap_b.root.take() {
+            heap_a.push_tree(root_b);
+        }
+        heap_a
+    }
+}
+
+impl<I, S: PairingStrategy, C: Compare<I>> AddressableHeap for PairingHeap<I, S, C> {
+    fn insert_with_handle(&mut self, item: Self::Item) -> Handle {
+        let handle = Handle::new();
+        let mut node = PairingNode::with_item(item);
+        node.handle = Some(handle);
+        self.handles.insert(handle, node.as_ptr());
+        self.push_tree(node);
+        self.len += 1;
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<&Self::Item> {
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // Since we have &self borrowed, we know there are not mutable references
+        self.handles
+            .get(&handle)
+            .map(|node| unsafe { &node.as_ref().item })
+    }
+
+    fn decrease_key(&mut self, handle: Handle, item: Self::Item) -> Result<(), HandleError> {
+        let mut node_ptr = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // We hold a mutable reference to self, so no one can have any other reference
+        let node = unsafe { node_ptr.as_mut() };
+        if self.compare.gt(&item, &node.item) {
+            return Err(HandleError::KeyIncreased);
+        }
+        node.item = item;
+
+        if node.prev.is_some() {
+            // Cut the subtree and link it back with the root, since it may be smaller now
+            let tree = self.unlink(node_ptr);
+            self.push_tree(tree);
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, handle: Handle) -> Result<Self::Item, HandleError> {
+        let node_ptr = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+
+        // SAFETY: We only store pointers to nodes owned by the heap
+        if unsafe { node_ptr.as_ref() }.prev.is_none() {
+            return Ok(self.extract_min().unwrap());
+        }
+
+        let mut tree = self.unlink(node_ptr);
+        if let Some(rest) = tree.combine_children::<S>(&self.compare) {
+            self.push_tree(rest);
+        }
+        self.handles.remove(&handle);
+        self.len -= 1;
+        Ok(tree.into_inner().item)
+    }
+}
+
+/// Iterator over the items of a [`PairingHeap`] in arbitrary order
+pub struct Iter<'a, I> {
+    /// Nodes whose item, children and next siblings haven't been visited yet
+    stack: Vec<&'a PairingNode<I>>,
+    remaining: usize,
+}
+
+impl<'a, I> Iterator for Iter<'a, I> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.remaining -= 1;
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for Iter<'_, I> {}
+impl<I> FusedIterator for Iter<'_, I> {}
+
+/// Owning iterator over the items of a [`PairingHeap`] in arbitrary order
+pub struct IntoIter<I> {
+    /// Trees whose items haven't been returned yet
+    stack: Vec<NodeBox<PairingNode<I>>>,
+    remaining: usize,
+}
+
+impl<I> Iterator for IntoIter<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?.into_inner();
+        self.stack.extend(node.right.take());
+        self.stack.extend(node.left.take());
+        self.remaining -= 1;
+        Some(node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for IntoIter<I> {}
+impl<I> FusedIterator for IntoIter<I> {}
+
+impl<I, S: PairingStrategy, C: Compare<I>> IntoIterator for PairingHeap<I, S, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl<'a, I, S: PairingStrategy, C: Compare<I>> IntoIterator for &'a PairingHeap<I, S, C> {
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<I, S: PairingStrategy, C: Compare<I> + Default> FromIterator<I> for PairingHeap<I, S, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, S: PairingStrategy, C: Compare<I> + Default> From<Vec<I>> for PairingHeap<I, S, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, S: PairingStrategy, C: Compare<I> + Default, const N: usize> From<[I; N]>
+    for PairingHeap<I, S, C>
+{
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, S: PairingStrategy, C: Compare<I>> Extend<I> for PairingHeap<I, S, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Inserting is O(1), which is also how heapify builds the heap
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<I: Clone, S: PairingStrategy, C: Compare<I> + Clone> Clone for PairingHeap<I, S, C> {
+    /// The clone has the same items but none of the handles, which keep referring to the items in
+    /// the original heap
+    fn clone(&self) -> Self {
+        Self::heapify_with_comparator(self.iter().cloned().collect(), self.compare.clone())
+    }
+}
+
+impl<I: Debug, S: PairingStrategy, C: Compare<I>> Debug for PairingHeap<I, S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, S: PairingStrategy, C: Compare<I>> PartialEq for PairingHeap<I, S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, S: PairingStrategy, C: Compare<I>> Eq for PairingHeap<I, S, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, S: PairingStrategy, C: Compare<I>> CheckInvariants for PairingHeap<I, S, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        if let Some(root) = &self.root {
+            ensure!(
+                root.prev.is_none() && root.right.is_none(),
+                "single root",
+                "the root has a sibling or points to a node before it"
+            );
+        }
+        let mut len = 0;
+        let mut handles_found = 0;
+        let mut stack: Vec<&PairingNode<I>> = self.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            len += 1;
+            check_handle(
+                &self.handles,
+                node.handle,
+                NonNull::from(node),
+                &mut handles_found,
+            )?;
+            // The first child points back to its parent and the rest to their left sibling
+            let mut prev = NonNull::from(node);
+            let mut cursor = node.left.as_deref();
+            while let Some(child) = cursor {
+                ensure!(
+                    child.prev == Some(prev),
+                    "prev pointers",
+                    "a node doesn't point to the node before it"
+                );
+                ensure!(
+                    self.compare.le(&node.item, &child.item),
+                    "heap order",
+                    "a node is smaller than its parent"
+                );
+                stack.push(child);
+                prev = NonNull::from(child);
+                cursor = child.right.as_deref();
+            }
+        }
+        check_len(self.len, len)?;
+        check_handle_count(&self.handles, handles_found)
+    }
+}
+
+/// Draws the tree with an edge from each node to its children
+impl<I: Debug, S: PairingStrategy, C> ToDot for PairingHeap<I, S, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut stack = Vec::new();
+        if let Some(root) = self.root.as_deref() {
+            stack.push((root, dot.node(&root.item, "", "")));
+        }
+        // The left link of a node is its first child and the right links of the children are
+        // their next siblings
+        while let Some((node, id)) = stack.pop() {
+            let mut cursor = node.left.as_deref();
+            while let Some(child) = cursor {
+                let child_id = dot.node(&child.item, "", "");
+                dot.edge(id, child_id, "");
+                stack.push((child, child_id));
+                cursor = child.right.as_deref();
+            }
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests, ByKey, FnCompare, MaxOrder};
+
+    #[test]
+    fn combine_order() {
+        let link = |a: String, b: String| format!("({a} {b})");
+        let trees = || (1..=5).map(|i| i.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            TwoPass::combine(trees(), link).unwrap(),
+            "((1 2) ((3 4) 5))"
+        );
+        assert_eq!(
+            FrontToBack::combine(trees(), link).unwrap(),
+            "(((1 2) (3 4)) 5)"
+        );
+        assert_eq!(
+            Multipass::combine(trees(), link).unwrap(),
+            "((3 4) (5 (1 2)))"
+        );
+        assert_eq!(TwoPass::combine(Vec::new(), link), None);
+    }
+
+    type HeapU32 = PairingHeap<u32>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key() {
+        tests::decrease_key::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key_many() {
+        tests::decrease_key_many::<HeapU32>();
+    }
+    #[test]
+    fn delete() {
+        tests::delete::<HeapU32>();
+    }
+    #[test]
+    fn handles_after_meld() {
+        tests::handles_after_meld::<HeapU32>();
+    }
+    #[test]
+    fn stale_handles_rejected() {
+        tests::stale_handles_rejected::<HeapU32>();
+    }
+
+    #[test]
+    fn clone_handles() {
+        tests::clone_handles::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<PairingHeap<u32, TwoPass, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| {
+            PairingHeap::<_, TwoPass, _>::with_comparator(ByKey(|item: &(u32, char)| item.0))
+        });
+    }
+    #[test]
+    fn fn_compare_decrease_key() {
+        tests::fn_compare_decrease_key(PairingHeap::<_, TwoPass, _>::with_comparator(FnCompare(
+            |a: &u32, b: &u32| b.cmp(a),
+        )));
+    }
+    #[test]
+    fn meld_different_comparators() {
+        // The second heap's tree is linked in under its largest item, whose children stay in
+        // decreasing order
+        assert_eq!(
+            tests::meld_different_comparators(PairingHeap::<_, TwoPass, _>::with_comparator),
+            vec![1, 3, 5, 6, 4, 2]
+        );
+    }
+
+    type FrontToBackU32 = PairingHeap<u32, FrontToBack>;
+    type MultipassU32 = PairingHeap<u32, Multipass>;
+
+    #[test]
+    fn other_strategies_mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<FrontToBackU32>();
+        tests::mixed_insertions_and_extractions::<MultipassU32>();
+    }
+    #[test]
+    fn other_strategies_differential() {
+        tests::differential::<FrontToBackU32>();
+        tests::differential::<MultipassU32>();
+    }
+    #[test]
+    fn other_strategies_meld() {
+        tests::meld::<FrontToBackU32>();
+        tests::meld::<MultipassU32>();
+    }
+    #[test]
+    fn other_strategies_heapify_duplicates() {
+        tests::heapify_duplicates::<FrontToBackU32>();
+        tests::heapify_duplicates::<MultipassU32>();
+    }
+    #[test]
+    fn other_strategies_large_input() {
+        tests::large_input::<FrontToBackU32>();
+        tests::large_input::<MultipassU32>();
+    }
+    #[test]
+    fn other_strategies_decrease_key_many() {
+        tests::decrease_key_many::<FrontToBackU32>();
+        tests::decrease_key_many::<MultipassU32>();
+    }
+    #[test]
+    fn other_strategies_delete() {
+        tests::delete::<FrontToBackU32>();
+        tests::delete::<MultipassU32>();
+    }
+}
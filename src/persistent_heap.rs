@@ -0,0 +1,768 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+    ops::Deref,
+    rc::Rc,
+    sync::Arc,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{check_len, ensure, CheckInvariants, InvariantError};
+use crate::{
+    dot::{Dot, ToDot},
+    iter::same_items,
+    stats::count,
+    Compare, MinHeap, MinOrder,
+};
+
+/// Reference-counted pointer that [`PersistentLeftistHeap`] shares its nodes with
+pub trait SharedPointer {
+    type Pointer<T>: Deref<Target = T> + Clone;
+
+    fn new<T>(value: T) -> Self::Pointer<T>;
+    /// Returns the value if this is the only pointer to it, otherwise gives the pointer back
+    fn try_unwrap<T>(pointer: Self::Pointer<T>) -> Result<T, Self::Pointer<T>>;
+    /// Returns the value if this is the only pointer to it, otherwise drops the pointer. Unlike
+    /// `try_unwrap`, when several threads drop the last pointers to a value one of them gets it
+    fn into_inner<T>(pointer: Self::Pointer<T>) -> Option<T>;
+}
+
+/// Shares nodes with [`Rc`], for heaps used by a single thread
+pub struct RcPointer;
+
+/// Shares nodes with [`Arc`], so versions of the heap can be sent to other threads
+pub struct ArcPointer;
+
+impl SharedPointer for RcPointer {
+    type Pointer<T> = Rc<T>;
+
+    fn new<T>(value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+    fn try_unwrap<T>(pointer: Rc<T>) -> Result<T, Rc<T>> {
+        Rc::try_unwrap(pointer)
+    }
+    fn into_inner<T>(pointer: Rc<T>) -> Option<T> {
+        Rc::into_inner(pointer)
+    }
+}
+
+impl SharedPointer for ArcPointer {
+    type Pointer<T> = Arc<T>;
+
+    fn new<T>(value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+    fn try_unwrap<T>(pointer: Arc<T>) -> Result<T, Arc<T>> {
+        Arc::try_unwrap(pointer)
+    }
+    fn into_inner<T>(pointer: Arc<T>) -> Option<T> {
+        Arc::into_inner(pointer)
+    }
+}
+
+struct PersistentNode<I, P: SharedPointer> {
+    item: I,
+    /// Number of nodes in the right spine of the subtree, which is never longer than the left one
+    rank: usize,
+    left: PersistentLink<I, P>,
+    right: PersistentLink<I, P>,
+}
+type PersistentLink<I, P> = Option<<P as SharedPointer>::Pointer<PersistentNode<I, P>>>;
+
+impl<I: Clone, P: SharedPointer> Clone for PersistentNode<I, P> {
+    fn clone(&self) -> Self {
+        Self {
+            item: self.item.clone(),
+            rank: self.rank,
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
+impl<I, P: SharedPointer> PersistentNode<I, P> {
+    fn with_item(item: I) -> P::Pointer<Self> {
+        count!(allocations);
+        P::new(Self {
+            item,
+            rank: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn rank(link: &PersistentLink<I, P>) -> usize {
+        link.as_ref().map_or(0, |node| node.rank)
+    }
+
+    /// Drops the nodes no other version of the heap shares one at a time, since dropping the
+    /// pointers recursively can overflow the stack on long left paths
+    fn drop_tree(tree: PersistentLink<I, P>) {
+        let mut stack: Vec<P::Pointer<Self>> = tree.into_iter().collect();
+        while let Some(pointer) = stack.pop() {
+            // Shared nodes only lose a reference, and their children stay alive. If another
+            // version is dropped at the same time on another thread, one of the two threads
+            // still gets the node, so it is never dropped recursively
+            if let Some(mut node) = P::into_inner(pointer) {
+                stack.extend(node.left.take());
+                stack.extend(node.right.take());
+            }
+        }
+    }
+}
+
+impl<I: Clone, P: SharedPointer> PersistentNode<I, P> {
+    /// Takes the node out of its pointer, or copies it if another version of the heap shares it
+    fn unwrap_or_copy(pointer: P::Pointer<Self>) -> Self {
+        P::try_unwrap(pointer).unwrap_or_else(|shared| (*shared).clone())
+    }
+
+    /// Same merge as the leftist heap's, except that the nodes of the merged right spines are
+    /// copied when they are shared, so the trees of other versions of the heap don't change
+    fn merge(
+        mut tree_a: PersistentLink<I, P>,
+        mut tree_b: PersistentLink<I, P>,
+        compare: &impl Compare<I>,
+    ) -> PersistentLink<I, P> {
+        // Nodes of the merged right spine, from the top down
+        let mut spine = Vec::new();
+        let mut merged = loop {
+            match (tree_a, tree_b) {
+                (Some(a), Some(b)) => {
+                    let (smaller, larger) = if compare.gt(&a.item, &b.item) {
+                        (b, a)
+                    } else {
+                        (a, b)
+                    };
+                    let mut smaller = Self::unwrap_or_copy(smaller);
+                    tree_a = smaller.right.take();
+                    tree_b = Some(larger);
+                    spine.push(smaller);
+                    count!(links);
+                }
+                (rest, None) | (None, rest) => break rest,
+            }
+        };
+
+        while let Some(mut node) = spine.pop() {
+            node.right = merged;
+            if Self::rank(&node.left) < Self::rank(&node.right) {
+                mem::swap(&mut node.left, &mut node.right);
+            }
+            node.rank = Self::rank(&node.right) + 1;
+            merged = Some(P::new(node));
+            count!(allocations);
+        }
+        merged
+    }
+}
+
+/// Leftist heap whose nodes are shared between versions of the heap instead of owned by one.
+/// `inserted`, `extracted_min` and `melded` return a new version in O(log n) and leave the old
+/// one valid, by copying only the O(log n) nodes on the right spines they change, and cloning a
+/// version takes O(1). The [`MinHeap`] operations change the heap in place, which reuses the
+/// nodes no other version shares
+pub struct PersistentLeftistHeap<I, P: SharedPointer = RcPointer, C = MinOrder> {
+    root: PersistentLink<I, P>,
+    len: usize,
+    compare: C,
+    pointer: PhantomData<P>,
+}
+
+impl<I: Clone, P: SharedPointer, C: Compare<I> + Default> Default
+    for PersistentLeftistHeap<I, P, C>
+{
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I: Clone, P: SharedPointer, C: Compare<I>> PersistentLeftistHeap<I, P, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            compare,
+            pointer: PhantomData,
+        }
+    }
+
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        Self {
+            len: items.len(),
+            root: Self::heapify_tree(items, &compare),
+            compare,
+            pointer: PhantomData,
+        }
+    }
+
+    /// Melds the items in pairs, then the pairs in pairs and so on, which takes O(n) in total
+    fn heapify_tree(items: Vec<I>, compare: &C) -> PersistentLink<I, P> {
+        let mut queue: VecDeque<_> = items
+            .into_iter()
+            .map(PersistentNode::<I, P>::with_item)
+            .collect();
+        while queue.len() > 1 {
+            let tree_a = queue.pop_front();
+            let tree_b = queue.pop_front();
+            queue.extend(PersistentNode::<I, P>::merge(tree_a, tree_b, compare));
+        }
+        queue.pop_front()
+    }
+
+    /// Version of the heap with the item added
+    pub fn inserted(&self, item: I) -> Self
+    where
+        C: Clone,
+    {
+        let tree = Some(PersistentNode::<I, P>::with_item(item));
+        Self {
+            root: PersistentNode::<I, P>::merge(self.root.clone(), tree, &self.compare),
+            len: self.len + 1,
+            compare: self.compare.clone(),
+            pointer: PhantomData,
+        }
+    }
+
+    /// Min of the heap, and the version of the heap without it
+    pub fn extracted_min(&self) -> Option<(I, Self)>
+    where
+        C: Clone,
+    {
+        let root = self.root.as_ref()?;
+        let rest = Self {
+            root: PersistentNode::<I, P>::merge(
+                root.left.clone(),
+                root.right.clone(),
+                &self.compare,
+            ),
+            len: self.len - 1,
+            compare: self.compare.clone(),
+            pointer: PhantomData,
+        };
+        Some((root.item.clone(), rest))
+    }
+
+    /// Version of the heap with the items of both heaps, keeping the comparator of `self`
+    pub fn melded(&self, other: &Self) -> Self
+    where
+        C: Clone,
+    {
+        Self {
+            root: PersistentNode::<I, P>::merge(
+                self.root.clone(),
+                other.root.clone(),
+                &self.compare,
+            ),
+            len: self.len + other.len,
+            compare: self.compare.clone(),
+            pointer: PhantomData,
+        }
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I, P> {
+        Iter {
+            stack: self.root.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order. Items of nodes that
+    /// other versions of the heap share are cloned
+    pub fn drain(&mut self) -> IntoIter<I, P> {
+        let iter = IntoIter {
+            stack: self.root.take().into_iter().collect(),
+            remaining: self.len,
+        };
+        self.len = 0;
+        iter
+    }
+}
+
+impl<I: Clone, P: SharedPointer, C: Compare<I>> MinHeap for PersistentLeftistHeap<I, P, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        self.root.as_ref().map(|root| &root.item)
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        let mut root = PersistentNode::<I, P>::unwrap_or_copy(self.root.take()?);
+        self.root =
+            PersistentNode::<I, P>::merge(root.left.take(), root.right.take(), &self.compare);
+        self.len -= 1;
+        Some(root.item)
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        let tree = Some(PersistentNode::<I, P>::with_item(item));
+        self.root = PersistentNode::<I, P>::merge(self.root.take(), tree, &self.compare);
+        self.len += 1;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        PersistentNode::<I, P>::drop_tree(self.root.take());
+        self.len = 0;
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        let mut heap = Self::default();
+        heap.extend(items);
+        heap
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        let root_a = heap_a.root.take();
+        heap_a.root = PersistentNode::<I, P>::merge(root_a, heap_b.root.take(), &heap_a.compare);
+        heap_a.len += heap_b.len;
+        heap_a
+    }
+}
+
+impl<I, P: SharedPointer, C> Drop for PersistentLeftistHeap<I, P, C> {
+    fn drop(&mut self) {
+        PersistentNode::<I, P>::drop_tree(self.root.take());
+    }
+}
+
+/// Iterator over the items of a [`PersistentLeftistHeap`] in arbitrary order
+pub struct Iter<'a, I, P: SharedPointer> {
+    /// Nodes whose item and children haven't been visited yet
+    stack: Vec<&'a PersistentNode<I, P>>,
+    remaining: usize,
+}
+
+impl<'a, I, P: SharedPointer> Iterator for Iter<'a, I, P> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.remaining -= 1;
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I, P: SharedPointer> ExactSizeIterator for Iter<'_, I, P> {}
+impl<I, P: SharedPointer> FusedIterator for Iter<'_, I, P> {}
+
+/// Owning iterator over the items of a [`PersistentLeftistHeap`] in arbitrary order
+pub struct IntoIter<I, P: SharedPointer> {
+    /// Trees whose items haven't been returned yet
+    stack: Vec<P::Pointer<PersistentNode<I, P>>>,
+    remaining: usize,
+}
+
+impl<I: Clone, P: SharedPointer> Iterator for IntoIter<I, P> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = PersistentNode::<I, P>::unwrap_or_copy(self.stack.pop()?);
+        self.stack.extend(node.right.take());
+        self.stack.extend(node.left.take());
+        self.remaining -= 1;
+        Some(node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I: Clone, P: SharedPointer> ExactSizeIterator for IntoIter<I, P> {}
+impl<I: Clone, P: SharedPointer> FusedIterator for IntoIter<I, P> {}
+
+impl<I, P: SharedPointer> Drop for IntoIter<I, P> {
+    fn drop(&mut self) {
+        // Drops the remaining trees without copying the shared nodes
+        for tree in mem::take(&mut self.stack) {
+            PersistentNode::<I, P>::drop_tree(Some(tree));
+        }
+    }
+}
+
+impl<I: Clone, P: SharedPointer, C: Compare<I>> IntoIterator for PersistentLeftistHeap<I, P, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I, P>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl<'a, I: Clone, P: SharedPointer, C: Compare<I>> IntoIterator
+    for &'a PersistentLeftistHeap<I, P, C>
+{
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<I: Clone, P: SharedPointer, C: Compare<I> + Default> FromIterator<I>
+    for PersistentLeftistHeap<I, P, C>
+{
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I: Clone, P: SharedPointer, C: Compare<I> + Default> From<Vec<I>>
+    for PersistentLeftistHeap<I, P, C>
+{
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I: Clone, P: SharedPointer, C: Compare<I> + Default, const N: usize> From<[I; N]>
+    for PersistentLeftistHeap<I, P, C>
+{
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I: Clone, P: SharedPointer, C: Compare<I>> Extend<I> for PersistentLeftistHeap<I, P, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Heapify the new items on their own and meld them in, like meld would with a second heap
+        let items: Vec<I> = iter.into_iter().collect();
+        self.len += items.len();
+        let tree = Self::heapify_tree(items, &self.compare);
+        self.root = PersistentNode::<I, P>::merge(self.root.take(), tree, &self.compare);
+    }
+}
+
+impl<I, P: SharedPointer, C: Clone> Clone for PersistentLeftistHeap<I, P, C> {
+    /// Shares the whole tree with the original heap in O(1)
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+            compare: self.compare.clone(),
+            pointer: PhantomData,
+        }
+    }
+}
+
+impl<I: Clone + Debug, P: SharedPointer, C: Compare<I>> Debug for PersistentLeftistHeap<I, P, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: Clone + PartialEq, P: SharedPointer, C: Compare<I>> PartialEq
+    for PersistentLeftistHeap<I, P, C>
+{
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Clone + Eq, P: SharedPointer, C: Compare<I>> Eq for PersistentLeftistHeap<I, P, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, P: SharedPointer, C: Compare<I>> CheckInvariants for PersistentLeftistHeap<I, P, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut len = 0;
+        let mut stack: Vec<&PersistentNode<I, P>> = self.root.iter().map(|root| &**root).collect();
+        while let Some(node) = stack.pop() {
+            len += 1;
+            let left_rank = PersistentNode::<I, P>::rank(&node.left);
+            let right_rank = PersistentNode::<I, P>::rank(&node.right);
+            ensure!(
+                right_rank <= left_rank,
+                "leftist property",
+                "node of rank {} has a right child of rank {right_rank} and a left one of rank \
+                 {left_rank}",
+                node.rank
+            );
+            ensure!(
+                node.rank == right_rank + 1,
+                "rank",
+                "node of rank {} has a right child of rank {right_rank}",
+                node.rank
+            );
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                ensure!(
+                    self.compare.le(&node.item, &child.item),
+                    "heap order",
+                    "a child of a node of rank {} is smaller than it",
+                    node.rank
+                );
+                stack.push(child);
+            }
+        }
+        check_len(self.len, len)
+    }
+}
+
+/// Draws the tree of this version with each node labelled with its rank, drawing nodes shared
+/// with other versions as if they were its own
+impl<I: Debug, P: SharedPointer, C> ToDot for PersistentLeftistHeap<I, P, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut stack = Vec::new();
+        if let Some(root) = self.root.as_deref() {
+            stack.push((root, None));
+        }
+        while let Some((node, parent)) = stack.pop() {
+            let id = dot.node(&node.item, &format!("rank {}", node.rank), "");
+            if let Some((parent_id, attributes)) = parent {
+                dot.edge(parent_id, id, attributes);
+            }
+            if let Some(right) = node.right.as_deref() {
+                stack.push((right, Some((id, "label=right"))));
+            }
+            if let Some(left) = node.left.as_deref() {
+                stack.push((left, Some((id, "label=left"))));
+            }
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Barrier, thread};
+
+    use super::*;
+    use crate::{tests, ByKey, MaxOrder};
+
+    type HeapU32 = PersistentLeftistHeap<u32>;
+    type ArcHeapU32 = PersistentLeftistHeap<u32, ArcPointer>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<PersistentLeftistHeap<u32, RcPointer, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| {
+            PersistentLeftistHeap::<_, RcPointer, _>::with_comparator(ByKey(
+                |item: &(u32, char)| item.0,
+            ))
+        });
+    }
+    #[test]
+    fn arc_shared_tests() {
+        tests::simple::<ArcHeapU32>();
+        tests::differential::<ArcHeapU32>();
+        tests::meld::<ArcHeapU32>();
+        tests::iter::<ArcHeapU32>();
+        tests::std_traits::<ArcHeapU32>();
+    }
+
+    #[test]
+    fn old_versions_stay_valid() {
+        let empty = HeapU32::default();
+        let mut versions = vec![empty.clone()];
+        for i in [5, 3, 8, 1, 9, 2] {
+            let next = versions.last().unwrap().inserted(i);
+            versions.push(next);
+        }
+        assert!(empty.is_empty());
+        assert_eq!(versions[1].clone().into_sorted_vec(), vec![5]);
+        assert_eq!(versions[3].clone().into_sorted_vec(), vec![3, 5, 8]);
+        assert_eq!(versions[6].len(), 6);
+
+        let (min, rest) = versions[6].extracted_min().unwrap();
+        assert_eq!(min, 1);
+        assert_eq!(rest.clone().into_sorted_vec(), vec![2, 3, 5, 8, 9]);
+        assert_eq!(versions[6].peek_min(), Some(&1));
+        assert_eq!(
+            versions[6].clone().into_sorted_vec(),
+            vec![1, 2, 3, 5, 8, 9]
+        );
+        assert!(empty.extracted_min().is_none());
+
+        let melded = versions[2].melded(&rest);
+        assert_eq!(melded.len(), 7);
+        assert_eq!(melded.into_sorted_vec(), vec![2, 3, 3, 5, 5, 8, 9]);
+        assert_eq!(versions[2].clone().into_sorted_vec(), vec![3, 5]);
+        assert_eq!(rest.into_sorted_vec(), vec![2, 3, 5, 8, 9]);
+    }
+
+    #[test]
+    fn in_place_changes_leave_clones_alone() {
+        let mut heap: HeapU32 = (0..100).rev().collect();
+        let snapshot = heap.clone();
+        for i in 0..50 {
+            assert_eq!(heap.extract_min(), Some(i));
+        }
+        heap.insert(7);
+        heap = HeapU32::meld(heap, snapshot.clone());
+        assert_eq!(heap.len(), 151);
+        assert_eq!(snapshot.len(), 100);
+        assert_eq!(
+            snapshot.clone().into_sorted_vec(),
+            (0..100).collect::<Vec<_>>()
+        );
+        let mut drained: Vec<_> = snapshot.clone().drain().collect();
+        drained.sort_unstable();
+        assert_eq!(drained, (0..100).collect::<Vec<_>>());
+        assert_eq!(heap.peek_min(), Some(&0));
+    }
+
+    #[test]
+    fn backtracking() {
+        // Depth-first search keeping one version of the heap per level
+        let mut stack = vec![(HeapU32::default(), 0)];
+        let mut leaves = 0;
+        while let Some((heap, depth)) = stack.pop() {
+            if depth == 10 {
+                let sorted = heap.clone().into_sorted_vec();
+                assert_eq!(sorted.len(), 10);
+                assert!(sorted.is_sorted());
+                leaves += 1;
+                continue;
+            }
+            stack.push((heap.inserted(depth), depth + 1));
+            stack.push((heap.inserted(100 - depth), depth + 1));
+        }
+        assert_eq!(leaves, 1024);
+    }
+
+    #[test]
+    fn versions_across_threads() {
+        let heap: ArcHeapU32 = (0..1000).collect();
+        let workers: Vec<_> = (0..4)
+            .map(|i| {
+                let heap = heap.clone();
+                thread::spawn(move || {
+                    let mut heap = heap.inserted(i);
+                    heap.extract_min();
+                    heap.extract_min();
+                    heap.into_sorted_vec()
+                })
+            })
+            .collect();
+        for (i, worker) in workers.into_iter().enumerate() {
+            let sorted = worker.join().unwrap();
+            assert_eq!(sorted.len(), 999);
+            // The two smallest of 0..1000 and i are gone
+            assert_eq!(sorted[0], [1, 1, 2, 2][i]);
+        }
+        assert_eq!(heap.len(), 1000);
+        assert_eq!(heap.peek_min(), Some(&0));
+    }
+
+    #[test]
+    fn deep_versions_dropped_across_threads() {
+        // Inserting in decreasing order makes one long left path
+        let mut heap = ArcHeapU32::default();
+        for i in (0..tests::DEEP_LEN).rev() {
+            heap.insert(i);
+        }
+        let barrier = Barrier::new(4);
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                let heap = heap.clone();
+                let barrier = &barrier;
+                scope.spawn(move || {
+                    barrier.wait();
+                    drop(heap);
+                });
+            }
+            drop(heap);
+        });
+    }
+}
@@ -0,0 +1,549 @@
+use std::{
+    error::Error,
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem, slice, vec,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{check_len, ensure, CheckInvariants, InvariantError};
+use crate::{
+    dot::{Dot, ToDot},
+    iter::same_items,
+    ByKey, MinHeap,
+};
+
+/// Unsigned integer that can be the key of a [`RadixHeap`]
+pub trait RadixKey: Copy + Ord + Debug {
+    const BITS: u32;
+    const MIN: Self;
+    /// Number of leading bits that are the same in both keys
+    fn common_prefix_len(self, other: Self) -> u32;
+}
+
+macro_rules! impl_radix_key {
+    ($($key:ty),*) => {$(
+        impl RadixKey for $key {
+            const BITS: u32 = <$key>::BITS;
+            const MIN: Self = 0;
+            fn common_prefix_len(self, other: Self) -> u32 {
+                (self ^ other).leading_zeros()
+            }
+        }
+    )*};
+}
+
+impl_radix_key!(u8, u16, u32, u64, u128, usize);
+
+/// Returned by [`RadixHeap::try_insert`] when the key of the item is smaller than the last
+/// extracted key, giving the item back
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MonotoneError<K, V> {
+    pub item: (K, V),
+    pub last_extracted: K,
+}
+
+impl<K: Debug, V> fmt::Display for MonotoneError<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "key {:?} is smaller than the last extracted key {:?}",
+            self.item.0, self.last_extracted
+        )
+    }
+}
+
+impl<K: Debug, V: Debug> Error for MonotoneError<K, V> {}
+
+/// Monotone priority queue for unsigned integer keys, where a key can't be inserted after a
+/// larger one was extracted. Items are kept in buckets by the highest bit where their key differs
+/// from the last extracted key, so no keys are compared except when a bucket is split up, which
+/// makes `extract_min` O(log C) amortized for keys up to C
+#[derive(Clone)]
+pub struct RadixHeap<K, V = ()> {
+    /// Bucket 0 holds the items whose key is `last`, and bucket i > 0 those whose key first
+    /// differs from `last` at bit i - 1, counting from the lowest
+    buckets: Vec<Vec<(K, V)>>,
+    /// Last extracted key, no key in the heap is smaller
+    last: K,
+    len: usize,
+}
+
+impl<K: RadixKey, V> Default for RadixHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: RadixKey, V> RadixHeap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            buckets: (0..=K::BITS).map(|_| Vec::new()).collect(),
+            last: K::MIN,
+            len: 0,
+        }
+    }
+
+    /// Smallest key that can still be inserted, which is the last extracted key or 0 if no item
+    /// was extracted since the heap was created or cleared
+    pub fn last_extracted(&self) -> K {
+        self.last
+    }
+
+    /// Inserts the item unless its key is smaller than the last extracted key
+    pub fn try_insert(&mut self, item: (K, V)) -> Result<(), MonotoneError<K, V>> {
+        if item.0 < self.last {
+            return Err(MonotoneError {
+                item,
+                last_extracted: self.last,
+            });
+        }
+        self.push(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            buckets: self.buckets.iter(),
+            bucket: [].iter(),
+            remaining: self.len,
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order. Like `clear`, it lets
+    /// any key be inserted again
+    pub fn drain(&mut self) -> IntoIter<K, V> {
+        let heap = mem::take(self);
+        heap.into_iter()
+    }
+
+    /// Puts the item in its bucket without checking its key or counting it
+    fn push(&mut self, item: (K, V)) {
+        let bucket = (K::BITS - item.0.common_prefix_len(self.last)) as usize;
+        self.buckets[bucket].push(item);
+    }
+}
+
+impl<K: RadixKey, V> MinHeap for RadixHeap<K, V> {
+    type Item = (K, V);
+
+    /// Looks through the first bucket that isn't empty, which can take O(n) when there are no
+    /// items with the last extracted key
+    fn peek_min(&self) -> Option<&Self::Item> {
+        if let Some(item) = self.buckets[0].last() {
+            return Some(item);
+        }
+        let bucket = self.buckets.iter().find(|bucket| !bucket.is_empty())?;
+        bucket.iter().min_by_key(|item| item.0)
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        if self.buckets[0].is_empty() {
+            // Every key in the first bucket that isn't empty is smaller than the keys in the ones
+            // after it, so its min is the new last key. Its items all move to lower buckets
+            let i = self.buckets.iter().position(|bucket| !bucket.is_empty())?;
+            let mut bucket = mem::take(&mut self.buckets[i]);
+            self.last = bucket.iter().map(|item| item.0).min().unwrap();
+            for item in bucket.drain(..) {
+                self.push(item);
+            }
+            // Keep the allocation for later
+            self.buckets[i] = bucket;
+        }
+        self.len -= 1;
+        self.buckets[0].pop()
+    }
+
+    /// Panics if the key of the item is smaller than the last extracted key, use
+    /// [`RadixHeap::try_insert`] to get an error instead
+    fn insert(&mut self, item: Self::Item) {
+        if let Err(err) = self.try_insert(item) {
+            panic!("{err}");
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Also lets any key be inserted again
+    fn clear(&mut self) {
+        self.buckets.iter_mut().for_each(Vec::clear);
+        self.last = K::MIN;
+        self.len = 0;
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        let mut heap = Self::default();
+        heap.extend(items);
+        heap
+    }
+
+    /// The melded heap accepts keys down to the smaller of the two last extracted keys. Takes
+    /// O(n) to move the items of the other heap to new buckets, unless both heaps had the same
+    /// last extracted key
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        if (heap_b.last, heap_a.len) < (heap_a.last, heap_b.len) {
+            mem::swap(&mut heap_a, &mut heap_b);
+        }
+        if heap_a.last == heap_b.last {
+            for (bucket_a, mut bucket_b) in heap_a.buckets.iter_mut().zip(heap_b.buckets) {
+                bucket_a.append(&mut bucket_b);
+            }
+        } else {
+            for item in heap_b.buckets.into_iter().flatten() {
+                heap_a.push(item);
+            }
+        }
+        heap_a.len += heap_b.len;
+        heap_a
+    }
+}
+
+/// Iterator over the items of a [`RadixHeap`] in arbitrary order
+pub struct Iter<'a, K, V> {
+    /// Buckets that haven't been visited yet
+    buckets: slice::Iter<'a, Vec<(K, V)>>,
+    bucket: slice::Iter<'a, (K, V)>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = &'a (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = loop {
+            match self.bucket.next() {
+                Some(item) => break item,
+                None => self.bucket = self.buckets.next()?.iter(),
+            }
+        };
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+/// Owning iterator over the items of a [`RadixHeap`] in arbitrary order
+pub struct IntoIter<K, V> {
+    /// Buckets that haven't been visited yet
+    buckets: vec::IntoIter<Vec<(K, V)>>,
+    bucket: vec::IntoIter<(K, V)>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = loop {
+            match self.bucket.next() {
+                Some(item) => break item,
+                None => self.bucket = self.buckets.next()?.into_iter(),
+            }
+        };
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+impl<K, V> IntoIterator for RadixHeap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            buckets: self.buckets.into_iter(),
+            bucket: Vec::new().into_iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<'a, K: RadixKey, V> IntoIterator for &'a RadixHeap<K, V> {
+    type Item = &'a (K, V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: RadixKey, V> FromIterator<(K, V)> for RadixHeap<K, V> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+impl<K: RadixKey, V> From<Vec<(K, V)>> for RadixHeap<K, V> {
+    fn from(items: Vec<(K, V)>) -> Self {
+        Self::from_iter(items)
+    }
+}
+
+impl<K: RadixKey, V, const N: usize> From<[(K, V); N]> for RadixHeap<K, V> {
+    fn from(items: [(K, V); N]) -> Self {
+        Self::from_iter(items)
+    }
+}
+
+impl<K: RadixKey, V> Extend<(K, V)> for RadixHeap<K, V> {
+    /// Panics if a key is smaller than the last extracted key, like `insert`
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for RadixHeap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.buckets.iter().flatten())
+            .finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter which key was last extracted
+impl<K: RadixKey, V: PartialEq> PartialEq for RadixHeap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &ByKey(|item: &(K, V)| item.0))
+    }
+}
+
+impl<K: RadixKey, V: Eq> Eq for RadixHeap<K, V> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<K: RadixKey, V> CheckInvariants for RadixHeap<K, V> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        ensure!(
+            self.buckets.len() == K::BITS as usize + 1,
+            "buckets",
+            "{} buckets for {}-bit keys",
+            self.buckets.len(),
+            K::BITS
+        );
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            for &(key, _) in bucket {
+                ensure!(
+                    key >= self.last,
+                    "monotone keys",
+                    "key {key:?} is smaller than the last extracted key {:?}",
+                    self.last
+                );
+                let expected = K::BITS - key.common_prefix_len(self.last);
+                ensure!(
+                    i == expected as usize,
+                    "buckets",
+                    "key {key:?} is in bucket {i} instead of bucket {expected}"
+                );
+            }
+        }
+        check_len(self.len, self.buckets.iter().map(Vec::len).sum())
+    }
+}
+
+/// Draws each bucket that isn't empty above its items, next to the last extracted key
+impl<K: RadixKey, V: Debug> ToDot for RadixHeap<K, V> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        dot.label(&format!("last {:?}", self.last));
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            if bucket.is_empty() {
+                continue;
+            }
+            let bucket_id = dot.label(&format!("bucket {i}"));
+            let items: Vec<usize> = bucket.iter().map(|item| dot.node(item, "", "")).collect();
+            dot.edge(bucket_id, items[0], "");
+            dot.list(&items);
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests;
+
+    type HeapU32 = RadixHeap<u32>;
+
+    fn keys<K: RadixKey>(heap: RadixHeap<K>) -> Vec<K> {
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|item| item.0)
+            .collect()
+    }
+
+    #[test]
+    fn simple() {
+        let mut heap = HeapU32::new();
+        assert_eq!(heap.peek_min(), None);
+        for key in [3, 2, 1] {
+            heap.insert((key, ()));
+        }
+        assert_eq!(heap.peek_min(), Some(&(1, ())));
+        assert_eq!(heap.extract_min(), Some((1, ())));
+        assert_eq!(heap.extract_min(), Some((2, ())));
+        assert_eq!(heap.peek_min(), Some(&(3, ())));
+        assert_eq!(heap.extract_min(), Some((3, ())));
+        assert_eq!(heap.extract_min(), None);
+        assert_eq!(heap.last_extracted(), 3);
+    }
+
+    #[test]
+    fn by_key() {
+        tests::by_key(RadixHeap::<u32, char>::new);
+    }
+
+    #[test]
+    fn differential() {
+        tests::differential_with::<HeapU32>(tests::Config {
+            monotone: true,
+            ..tests::Config::default()
+        });
+    }
+
+    #[test]
+    fn monotone_violation() {
+        let mut heap = RadixHeap::<u32, &str>::new();
+        heap.extend([(10, "a"), (20, "b"), (15, "c")]);
+        assert_eq!(heap.extract_min(), Some((10, "a")));
+
+        let err = heap.try_insert((5, "d")).unwrap_err();
+        assert_eq!(err.item, (5, "d"));
+        assert_eq!(err.last_extracted, 10);
+        assert_eq!(
+            err.to_string(),
+            "key 5 is smaller than the last extracted key 10"
+        );
+        assert_eq!(heap.len(), 2);
+
+        // Keys equal to the last extracted one are still fine
+        assert_eq!(heap.try_insert((10, "e")), Ok(()));
+        assert_eq!(heap.extract_min(), Some((10, "e")));
+        assert_eq!(heap.extract_min(), Some((15, "c")));
+        assert_eq!(heap.extract_min(), Some((20, "b")));
+    }
+
+    #[test]
+    #[should_panic(expected = "key 1 is smaller than the last extracted key 2")]
+    fn insert_panics_on_violation() {
+        let mut heap = HeapU32::from([(2, ()), (3, ())]);
+        heap.extract_min();
+        heap.insert((1, ()));
+    }
+
+    #[test]
+    fn clear_resets_last_extracted() {
+        let mut heap = HeapU32::from([(7, ()), (9, ())]);
+        heap.extract_min();
+        heap.clear();
+        assert!(heap.is_empty());
+        assert_eq!(heap.last_extracted(), 0);
+        heap.insert((1, ()));
+
+        heap.extract_min();
+        heap.insert((4, ()));
+        assert_eq!(heap.drain().collect::<Vec<_>>(), vec![(4, ())]);
+        assert_eq!(heap.last_extracted(), 0);
+    }
+
+    #[test]
+    fn monotone_workload() {
+        // Like Dijkstra's algorithm, every extracted key is reinserted a few times with
+        // non-negative weights added
+        let mut heap = HeapU32::new();
+        let mut expected = Vec::new();
+        heap.insert((0, ()));
+        let mut inserted = 1;
+        let mut extracted = Vec::new();
+        while let Some((key, ())) = heap.extract_min() {
+            extracted.push(key);
+            for i in 0..3 {
+                if inserted < tests::sized(10_000) {
+                    let weight = (inserted * 7919 + i) % 1000;
+                    heap.insert((key + weight, ()));
+                    expected.push(key + weight);
+                    inserted += 1;
+                }
+            }
+            assert_eq!(heap.len(), inserted as usize - extracted.len());
+        }
+        expected.push(0);
+        expected.sort_unstable();
+        assert_eq!(extracted, expected);
+    }
+
+    #[test]
+    fn extreme_keys() {
+        let heap = RadixHeap::<u8>::from([(255, ()), (0, ()), (128, ()), (127, ()), (255, ())]);
+        assert_eq!(keys(heap), vec![0, 127, 128, 255, 255]);
+        let heap = RadixHeap::<u128>::from([(u128::MAX, ()), (1 << 100, ()), (3, ())]);
+        assert_eq!(keys(heap), vec![3, 1 << 100, u128::MAX]);
+    }
+
+    #[test]
+    fn meld() {
+        let mut heap_a = HeapU32::from([(5, ()), (50, ()), (500, ())]);
+        let mut heap_b = HeapU32::from([(8, ()), (80, ()), (800, ())]);
+        heap_a.extract_min();
+        heap_b.extract_min();
+
+        // Keys down to the smaller last extracted key are accepted
+        let mut heap = HeapU32::meld(heap_a, heap_b);
+        assert_eq!(heap.last_extracted(), 5);
+        heap.insert((6, ()));
+        assert_eq!(heap.len(), 5);
+        assert_eq!(keys(heap), vec![6, 50, 80, 500, 800]);
+
+        let heap_a = HeapU32::from([(1, ()), (3, ())]);
+        let heap_b = HeapU32::from([(2, ()), (4, ()), (6, ())]);
+        assert_eq!(keys(HeapU32::meld(heap_a, heap_b)), vec![1, 2, 3, 4, 6]);
+        let heap = HeapU32::meld(HeapU32::new(), HeapU32::new());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn iter_and_std_traits() {
+        let mut heap: HeapU32 = (0..100).rev().map(|key| (key * 3, ())).collect();
+        for _ in 0..10 {
+            heap.extract_min();
+        }
+        assert_eq!(heap.iter().len(), 90);
+        let mut items: Vec<u32> = heap.iter().map(|item| item.0).collect();
+        items.sort_unstable();
+        assert_eq!(items, (10..100).map(|key| key * 3).collect::<Vec<_>>());
+
+        let clone = heap.clone();
+        assert_eq!(clone, heap);
+        assert_eq!(clone.last_extracted(), 27);
+        assert_ne!(clone, HeapU32::from([(30, ())]));
+        assert_eq!(format!("{:?}", HeapU32::from([(4, ())])), "[(4, ())]");
+        assert_eq!((&heap).into_iter().count(), 90);
+        assert_eq!(heap.into_iter().count(), 90);
+    }
+}
@@ -0,0 +1,1043 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+    ptr::NonNull,
+};
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::invariants::{
+    check_handle, check_handle_count, check_len, ensure, CheckInvariants, InvariantError,
+};
+use crate::{
+    dot::{Dot, ToDot},
+    handle::meld_handle_maps,
+    iter::same_items,
+    node_box::{NodeBox, TreeNode},
+    stats::count,
+    AddressableHeap, Compare, Handle, HandleError, MinHeap, MinOrder,
+};
+
+/// How [`RankPairingHeap::decrease_key`] recomputes the ranks of the ancestors of a cut node
+pub trait RankRule {
+    /// Rank of a node that isn't a root given the ranks of its children, where a missing child
+    /// has rank -1
+    fn rank(left: isize, right: isize) -> isize;
+}
+
+/// Every node that isn't a root is a 1,1-node or a 0,i-node, where the numbers are the rank
+/// differences with its children
+pub struct Type1;
+/// Every node that isn't a root is a 1,1-node, a 1,2-node or a 0,i-node with i > 1. Ranks drop
+/// faster after a cut, so fewer ancestors need updating
+pub struct Type2;
+
+impl RankRule for Type1 {
+    fn rank(left: isize, right: isize) -> isize {
+        if left == right {
+            left + 1
+        } else {
+            left.max(right)
+        }
+    }
+}
+
+impl RankRule for Type2 {
+    fn rank(left: isize, right: isize) -> isize {
+        if left.abs_diff(right) <= 1 {
+            left.max(right) + 1
+        } else {
+            left.max(right)
+        }
+    }
+}
+
+struct HalfTreeNode<I> {
+    item: I,
+    rank: usize,
+    /// Left child, every item in its subtree is at least the node's item
+    left: HalfTreeLink<I>,
+    /// Right child, unless node is a root then it is the next half tree
+    right: HalfTreeLink<I>,
+    /// Node whose left or right link owns this node, or None if node is a root
+    parent: Option<NonNull<HalfTreeNode<I>>>,
+    /// Previous half tree in the list if node is a root
+    prev: Option<NonNull<HalfTreeNode<I>>>,
+    /// Handle of the item, if it was inserted with one
+    handle: Option<Handle>,
+}
+type HalfTreeLink<I> = Option<NodeBox<HalfTreeNode<I>>>;
+
+impl<I> TreeNode for HalfTreeNode<I> {
+    fn take_links(&mut self) -> [Option<NodeBox<Self>>; 2] {
+        [self.right.take(), self.left.take()]
+    }
+}
+
+impl<I> HalfTreeNode<I> {
+    fn with_item(item: I) -> NodeBox<Self> {
+        count!(allocations);
+        NodeBox::new(Self {
+            item,
+            rank: 0,
+            left: None,
+            right: None,
+            parent: None,
+            prev: None,
+            handle: None,
+        })
+    }
+
+    fn rank(link: &HalfTreeLink<I>) -> isize {
+        link.as_ref().map_or(-1, |node| node.rank as isize)
+    }
+
+    /// Links two half trees of the same rank, making the root with the greater item the left
+    /// child of the other one
+    fn link(
+        mut tree: NodeBox<Self>,
+        mut other: NodeBox<Self>,
+        compare: &impl Compare<I>,
+    ) -> NodeBox<Self> {
+        debug_assert_eq!(tree.rank, other.rank);
+        count!(links);
+        if compare.gt(&tree.item, &other.item) {
+            mem::swap(&mut tree, &mut other);
+        }
+
+        let other_ptr = other.as_ptr();
+        if let Some(left) = tree.left.as_mut() {
+            left.parent = Some(other_ptr);
+        }
+        other.right = tree.left.take();
+        other.parent = Some(tree.as_ptr());
+        other.prev = None;
+        tree.left = Some(other);
+        tree.rank += 1;
+        tree
+    }
+
+    /// Links the half trees of both lists whose ranks match in one pass, like
+    /// `LeftTreeNode::merge_matches_one_pass` in the lazy binomial heap. The roots on the list
+    /// get their rank reset to one more than their left child's
+    fn merge_matches_one_pass(
+        mut head_a: HalfTreeLink<I>,
+        mut head_b: HalfTreeLink<I>,
+        compare: &impl Compare<I>,
+    ) -> HalfTreeLink<I> {
+        // Half tree waiting for a match at each rank
+        let mut matchings: Vec<HalfTreeLink<I>> = Vec::new();
+
+        if head_a.is_none() {
+            mem::swap(&mut head_a, &mut head_b);
+        }
+
+        let mut full_list_head = None;
+        let mut list_tail_cursor = &mut full_list_head;
+        let mut cursor = head_a;
+        while let Some(mut tree) = cursor.or_else(|| head_b.take()) {
+            cursor = tree.right.take();
+            // Nodes on the right spine of the extracted min's left child become roots here
+            tree.parent = None;
+            tree.prev = None;
+            tree.rank = (Self::rank(&tree.left) + 1) as usize;
+
+            let rank = tree.rank;
+            if matchings.len() <= rank {
+                matchings.resize_with(rank + 1, || None);
+            }
+            if let Some(matching_tree) = matchings[rank].take() {
+                *list_tail_cursor = Some(Self::link(tree, matching_tree, compare));
+                list_tail_cursor = &mut list_tail_cursor.as_mut().unwrap().right;
+            } else {
+                matchings[rank] = Some(tree);
+            }
+        }
+        // Add leftovers
+        for tree in matchings.into_iter().flatten() {
+            *list_tail_cursor = Some(tree);
+            list_tail_cursor = &mut list_tail_cursor.as_mut().unwrap().right;
+        }
+
+        full_list_head
+    }
+}
+
+/// Rank-pairing heap from "Rank-Pairing Heaps" by Haeupler, Sen and Tarjan. It keeps a list of
+/// half trees linked one pass at a time like the lazy binomial heap, and `decrease_key` cuts the
+/// node out of its half tree, then lowers the ranks of its ancestors with the rank rule `R`
+pub struct RankPairingHeap<I, R: RankRule = Type2, C = MinOrder> {
+    // Head of the list of half trees. "Owns" the list
+    head: HalfTreeLink<I>,
+    // Pointer to the last half tree in the list
+    tail: Option<NonNull<HalfTreeNode<I>>>,
+    // Pointer to the half tree immediately before the min one, or None if min is at head
+    prev_min: Option<NonNull<HalfTreeNode<I>>>,
+    // Pointer to the half tree with min value in the list
+    min: Option<NonNull<HalfTreeNode<I>>>,
+    // Node holding the item of each handle in the heap
+    handles: HashMap<Handle, NonNull<HalfTreeNode<I>>>,
+    // Number of items in the heap
+    len: usize,
+    // Order of the items
+    compare: C,
+    rule: PhantomData<R>,
+}
+
+impl<I, R: RankRule, C: Compare<I> + Default> Default for RankPairingHeap<I, R, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<I, R: RankRule, C: Compare<I>> RankPairingHeap<I, R, C> {
+    /// Heap ordered by `compare`, which the heaps melded with it should share, see
+    /// [`MinHeap::meld`]
+    pub fn with_comparator(compare: C) -> Self {
+        Self {
+            head: None,
+            tail: None,
+            prev_min: None,
+            min: None,
+            handles: HashMap::new(),
+            len: 0,
+            compare,
+            rule: PhantomData,
+        }
+    }
+
+    pub fn heapify_with_comparator(items: Vec<I>, compare: C) -> Self {
+        let mut heap = Self::with_comparator(compare);
+        for item in items.into_iter() {
+            heap.insert(item);
+        }
+        heap
+    }
+
+    /// Iterates over the items in arbitrary order
+    pub fn iter(&self) -> Iter<'_, I> {
+        Iter {
+            stack: self.head.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+
+    /// Removes all the items and iterates over them in arbitrary order
+    pub fn drain(&mut self) -> IntoIter<I> {
+        let iter = IntoIter {
+            stack: self.head.take().into_iter().collect(),
+            remaining: self.len,
+        };
+        self.clear();
+        iter
+    }
+
+    /// Appends a half tree to the end of the list, updating the min if needed
+    fn push_root(&mut self, mut tree: NodeBox<HalfTreeNode<I>>) {
+        debug_assert!(tree.right.is_none() && tree.parent.is_none());
+        tree.prev = self.tail;
+        let tree_ptr = tree.as_ptr();
+
+        // SAFETY: We only store valid pointers to half trees
+        // We hold a mutable reference to self, so no one can have any other reference
+        if self.min.is_none_or(|min_ptr| {
+            self.compare
+                .gt(unsafe { &min_ptr.as_ref().item }, &tree.item)
+        }) {
+            self.prev_min = self.tail;
+            self.min = Some(tree_ptr);
+        }
+        match self.tail {
+            Some(mut tail_ptr) => unsafe { tail_ptr.as_mut() }.right = Some(tree),
+            None => self.head = Some(tree),
+        }
+        self.tail = Some(tree_ptr);
+    }
+
+    /// Replaces the list of half trees, finding its tail and min
+    fn set_list(&mut self, list: HalfTreeLink<I>) {
+        let Some(mut min_tree) = list else {
+            self.head = None;
+            self.tail = None;
+            self.prev_min = None;
+            self.min = None;
+            return;
+        };
+
+        // Moving the min to front of list to simplify prev_min pointer
+        let mut remaining_list = min_tree.right.take();
+        let mut cursor = &mut remaining_list;
+        let mut tail_ptr = min_tree.as_ptr();
+
+        while let Some(mut curr_tree) = cursor.take() {
+            if self.compare.gt(&min_tree.item, &curr_tree.item) {
+                min_tree.right = curr_tree.right.take();
+                mem::swap(&mut min_tree, &mut curr_tree);
+            }
+
+            tail_ptr = curr_tree.as_ptr();
+            *cursor = Some(curr_tree);
+            cursor = &mut cursor.as_mut().unwrap().right;
+        }
+        min_tree.right = remaining_list;
+
+        let min_ptr = min_tree.as_ptr();
+
+        // Fix the prev pointers of the roots now that the order of the list is final
+        min_tree.prev = None;
+        let mut prev_ptr = min_ptr;
+        let mut cursor = &mut min_tree.right;
+        while let Some(curr_tree) = cursor {
+            curr_tree.prev = Some(prev_ptr);
+            prev_ptr = curr_tree.as_ptr();
+            cursor = &mut curr_tree.right;
+        }
+
+        self.head = Some(min_tree);
+        self.tail = Some(tail_ptr);
+        self.prev_min = None;
+        self.min = Some(min_ptr);
+    }
+
+    /// Detaches a node that isn't a root together with its left subtree, puts its right child in
+    /// its place and appends it to the list as a new half tree
+    fn cut(&mut self, node_ptr: NonNull<HalfTreeNode<I>>) {
+        // SAFETY: We only store pointers to nodes owned by the heap, and each reference is dropped
+        // before the next one to a different node is made.
+        // We hold a mutable reference to self, so no one can have any other reference
+        let (mut parent_ptr, mut right) = {
+            let node = unsafe { &mut *node_ptr.as_ptr() };
+            (node.parent.take().unwrap(), node.right.take())
+        };
+        if let Some(right) = right.as_mut() {
+            right.parent = Some(parent_ptr);
+        }
+        let mut tree = {
+            let parent = unsafe { parent_ptr.as_mut() };
+            let slot = if parent
+                .left
+                .as_ref()
+                .is_some_and(|left| left.as_ptr() == node_ptr)
+            {
+                &mut parent.left
+            } else {
+                &mut parent.right
+            };
+            mem::replace(slot, right).unwrap()
+        };
+        tree.rank = (HalfTreeNode::rank(&tree.left) + 1) as usize;
+        self.push_root(tree);
+        self.lower_ranks(parent_ptr);
+    }
+
+    /// Recomputes the ranks from `node` upwards after one of its subtrees lost a node, stopping
+    /// at the first rank that doesn't drop
+    fn lower_ranks(&mut self, mut node_ptr: NonNull<HalfTreeNode<I>>) {
+        loop {
+            // SAFETY: We only store pointers to nodes owned by the heap
+            // We hold a mutable reference to self, so no one can have any other reference
+            let node = unsafe { node_ptr.as_mut() };
+            let left_rank = HalfTreeNode::rank(&node.left);
+            let Some(parent_ptr) = node.parent else {
+                node.rank = (left_rank + 1) as usize;
+                return;
+            };
+            let rank = R::rank(left_rank, HalfTreeNode::rank(&node.right)) as usize;
+            if rank >= node.rank {
+                return;
+            }
+            node.rank = rank;
+            node_ptr = parent_ptr;
+        }
+    }
+}
+
+impl<I, R: RankRule, C: Compare<I>> MinHeap for RankPairingHeap<I, R, C> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&Self::Item> {
+        // SAFETY: We only store Some(ptr) from valid half trees
+        // Since we have &self borrowed, we know there are not mutable references
+        self.min.map(|ptr| unsafe { &ptr.as_ref().item })
+    }
+
+    fn extract_min(&mut self) -> Option<Self::Item> {
+        let min_ptr = self.min?;
+        let mut list = self.head.take();
+
+        let min = if let Some(mut prev_ptr) = self.prev_min {
+            // Extract min from middle of list
+            // SAFETY: We only ever store valid pointers to half trees
+            // We hold a mutable reference to self, so no one can have any other reference
+            let prev_tree = unsafe { prev_ptr.as_mut() };
+            let mut min = prev_tree.right.take().unwrap();
+            prev_tree.right = min.right.take();
+            min
+        } else {
+            // Extract min from start of list
+            let mut min = list.take().unwrap();
+            list = min.right.take();
+            min
+        };
+
+        debug_assert!(min.as_ptr() == min_ptr); // Sanity check
+
+        // The right spine of the left child of a root can be thought of as a list of half trees
+        let HalfTreeNode {
+            item: min_item,
+            left: min_tree_remaining,
+            handle: min_handle,
+            ..
+        } = min.into_inner();
+
+        if let Some(handle) = min_handle {
+            self.handles.remove(&handle);
+        }
+
+        let merged = HalfTreeNode::merge_matches_one_pass(list, min_tree_remaining, &self.compare);
+        self.set_list(merged);
+        self.len -= 1;
+
+        Some(min_item)
+    }
+
+    fn insert(&mut self, item: Self::Item) {
+        self.push_root(HalfTreeNode::with_item(item));
+        self.len += 1;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.set_list(None);
+        self.handles.clear();
+        self.len = 0;
+    }
+
+    fn heapify(items: Vec<Self::Item>) -> Self
+    where
+        Self: Default,
+    {
+        Self::heapify_with_comparator(items, Self::default().compare)
+    }
+
+    fn meld(mut heap_a: Self, mut heap_b: Self) -> Self {
+        if heap_a.is_empty() {
+            // The melded heap keeps the comparator of heap_a
+            mem::swap(&mut heap_a.compare, &mut heap_b.compare);
+            return heap_b;
+        }
+        if heap_b.is_empty() {
+            return heap_a;
+        }
+
+        let mut head_b = heap_b.head.take().unwrap();
+        let tail_ptr_b = heap_b.tail.unwrap();
+        let prev_min_b = heap_b.prev_min;
+        let min_ptr_b = heap_b.min.unwrap();
+
+        let mut tail_ptr_a = heap_a.tail.unwrap();
+        let prev_min_a = heap_a.prev_min;
+        let min_ptr_a = heap_a.min.unwrap();
+
+        let handles = meld_handle_maps(
+            mem::take(&mut heap_a.handles),
+            mem::take(&mut heap_b.handles),
+        );
+
+        // Append list b to the tail of list a
+        {
+            // SAFETY: We only store valid pointers to half trees
+            // We own heap_a so no-one else can have a reference
+            let tail_a = unsafe { tail_ptr_a.as_mut() };
+            head_b.prev = Some(tail_ptr_a);
+            tail_a.right = Some(head_b);
+        }
+        let (prev_min, min) = {
+            // SAFETY: We only store valid pointer to half trees
+            // We own both heap_a and heap_b so there can't be mutable references
+            let min_a = unsafe { min_ptr_a.as_ref() };
+            let min_b = unsafe { min_ptr_b.as_ref() };
+
+            if heap_a.compare.le(&min_a.item, &min_b.item) {
+                (prev_min_a, min_ptr_a)
+            } else if prev_min_b.is_some() {
+                (prev_min_b, min_ptr_b)
+            } else {
+                // min_b was the first element of list b
+                (Some(tail_ptr_a), min_ptr_b)
+            }
+        };
+
+        heap_a.tail = Some(tail_ptr_b);
+        heap_a.prev_min = prev_min;
+        heap_a.min = Some(min);
+        heap_a.handles = handles;
+        heap_a.len += heap_b.len;
+        heap_a
+    }
+}
+
+impl<I, R: RankRule, C: Compare<I>> AddressableHeap for RankPairingHeap<I, R, C> {
+    fn insert_with_handle(&mut self, item: Self::Item) -> Handle {
+        let handle = Handle::new();
+        let mut node = HalfTreeNode::with_item(item);
+        node.handle = Some(handle);
+        self.handles.insert(handle, node.as_ptr());
+        self.push_root(node);
+        self.len += 1;
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<&Self::Item> {
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // Since we have &self borrowed, we know there are not mutable references
+        self.handles
+            .get(&handle)
+            .map(|node| unsafe { &node.as_ref().item })
+    }
+
+    fn decrease_key(&mut self, handle: Handle, item: Self::Item) -> Result<(), HandleError> {
+        let mut node_ptr = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        // SAFETY: We only store pointers to nodes owned by the heap
+        // We hold a mutable reference to self, so no one can have any other reference
+        let node = unsafe { node_ptr.as_mut() };
+        if self.compare.gt(&item, &node.item) {
+            return Err(HandleError::KeyIncreased);
+        }
+        node.item = item;
+
+        if node.parent.is_some() {
+            // push_root checks whether the new half tree holds the min
+            self.cut(node_ptr);
+        } else {
+            // SAFETY: Both point to half trees owned by the heap, and we only read through them
+            let (node, min) = unsafe { (node_ptr.as_ref(), self.min.unwrap().as_ref()) };
+            if self.compare.lt(&node.item, &min.item) {
+                self.prev_min = node.prev;
+                self.min = Some(node_ptr);
+            }
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, handle: Handle) -> Result<Self::Item, HandleError> {
+        let node_ptr = *self
+            .handles
+            .get(&handle)
+            .ok_or(HandleError::InvalidHandle)?;
+        // SAFETY: We only store pointers to nodes owned by the heap, and we only read through it
+        if unsafe { node_ptr.as_ref() }.parent.is_some() {
+            self.cut(node_ptr);
+        }
+
+        // Make the half tree holding the item the min one so extract_min removes it
+        // SAFETY: The node is now a root owned by the heap, and we only read through it
+        self.prev_min = unsafe { node_ptr.as_ref() }.prev;
+        self.min = Some(node_ptr);
+        Ok(self.extract_min().unwrap())
+    }
+}
+
+/// Iterator over the items of a [`RankPairingHeap`] in arbitrary order
+pub struct Iter<'a, I> {
+    /// Nodes whose item, children and next half trees haven't been visited yet
+    stack: Vec<&'a HalfTreeNode<I>>,
+    remaining: usize,
+}
+
+impl<'a, I> Iterator for Iter<'a, I> {
+    type Item = &'a I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.remaining -= 1;
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for Iter<'_, I> {}
+impl<I> FusedIterator for Iter<'_, I> {}
+
+/// Owning iterator over the items of a [`RankPairingHeap`] in arbitrary order
+pub struct IntoIter<I> {
+    /// Half trees whose items haven't been returned yet
+    stack: Vec<NodeBox<HalfTreeNode<I>>>,
+    remaining: usize,
+}
+
+impl<I> Iterator for IntoIter<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?.into_inner();
+        self.stack.extend(node.right.take());
+        self.stack.extend(node.left.take());
+        self.remaining -= 1;
+        Some(node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> ExactSizeIterator for IntoIter<I> {}
+impl<I> FusedIterator for IntoIter<I> {}
+
+impl<I, R: RankRule, C: Compare<I>> IntoIterator for RankPairingHeap<I, R, C> {
+    type Item = I;
+    type IntoIter = IntoIter<I>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl<'a, I, R: RankRule, C: Compare<I>> IntoIterator for &'a RankPairingHeap<I, R, C> {
+    type Item = &'a I;
+    type IntoIter = Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<I, R: RankRule, C: Compare<I> + Default> FromIterator<I> for RankPairingHeap<I, R, C> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self::heapify_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<I, R: RankRule, C: Compare<I> + Default> From<Vec<I>> for RankPairingHeap<I, R, C> {
+    fn from(items: Vec<I>) -> Self {
+        Self::heapify_with_comparator(items, C::default())
+    }
+}
+
+impl<I, R: RankRule, C: Compare<I> + Default, const N: usize> From<[I; N]>
+    for RankPairingHeap<I, R, C>
+{
+    fn from(items: [I; N]) -> Self {
+        Self::from(Vec::from(items))
+    }
+}
+
+impl<I, R: RankRule, C: Compare<I>> Extend<I> for RankPairingHeap<I, R, C> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        // Inserting is O(1), which is also how heapify builds the heap
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<I: Clone, R: RankRule, C: Compare<I> + Clone> Clone for RankPairingHeap<I, R, C> {
+    /// The clone has the same items but none of the handles, which keep referring to the items in
+    /// the original heap
+    fn clone(&self) -> Self {
+        Self::heapify_with_comparator(self.iter().cloned().collect(), self.compare.clone())
+    }
+}
+
+impl<I: Debug, R: RankRule, C: Compare<I>> Debug for RankPairingHeap<I, R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Heaps are equal if they hold the same items, no matter how they are arranged
+impl<I: PartialEq, R: RankRule, C: Compare<I>> PartialEq for RankPairingHeap<I, R, C> {
+    fn eq(&self, other: &Self) -> bool {
+        same_items(self.iter(), other.iter(), &self.compare)
+    }
+}
+
+impl<I: Eq, R: RankRule, C: Compare<I>> Eq for RankPairingHeap<I, R, C> {}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, R: RankRule, C: Compare<I>> RankPairingHeap<I, R, C> {
+    /// Checks the half tree below the root, returning its number of nodes
+    fn check_half_tree(
+        &self,
+        root: &HalfTreeNode<I>,
+        handles_found: &mut usize,
+    ) -> Result<usize, InvariantError> {
+        check_handle(
+            &self.handles,
+            root.handle,
+            NonNull::from(root),
+            handles_found,
+        )?;
+        ensure!(
+            root.rank as isize == HalfTreeNode::rank(&root.left) + 1,
+            "rank rule",
+            "root of rank {} has a left child of rank {}",
+            root.rank,
+            HalfTreeNode::rank(&root.left)
+        );
+        let mut size = 1;
+        // Each node with the item of the nearest ancestor it is in the left subtree of, which
+        // it can't be smaller than
+        let mut stack: Vec<(&HalfTreeNode<I>, &I)> = root
+            .left
+            .as_deref()
+            .map(|left| (left, &root.item))
+            .into_iter()
+            .collect();
+        while let Some((node, bound)) = stack.pop() {
+            size += 1;
+            check_handle(
+                &self.handles,
+                node.handle,
+                NonNull::from(node),
+                handles_found,
+            )?;
+            ensure!(
+                self.compare.le(bound, &node.item),
+                "half tree order",
+                "a node is smaller than an ancestor whose left subtree holds it"
+            );
+            let left_rank = HalfTreeNode::rank(&node.left);
+            let right_rank = HalfTreeNode::rank(&node.right);
+            ensure!(
+                node.rank as isize == R::rank(left_rank, right_rank),
+                "rank rule",
+                "node of rank {} has children of ranks {left_rank} and {right_rank}",
+                node.rank
+            );
+            if let Some(left) = node.left.as_deref() {
+                stack.push((left, &node.item));
+            }
+            if let Some(right) = node.right.as_deref() {
+                stack.push((right, bound));
+            }
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                ensure!(
+                    child.parent == Some(NonNull::from(node)),
+                    "parent pointers",
+                    "a child of a node of rank {} doesn't point to it",
+                    node.rank
+                );
+            }
+        }
+        if let Some(left) = &root.left {
+            ensure!(
+                left.parent == Some(NonNull::from(root)),
+                "parent pointers",
+                "the left child of a root of rank {} doesn't point to it",
+                root.rank
+            );
+        }
+        Ok(size)
+    }
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<I, R: RankRule, C: Compare<I>> CheckInvariants for RankPairingHeap<I, R, C> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut len = 0;
+        let mut handles_found = 0;
+        let mut prev = None;
+        let mut min_found = false;
+        let mut smallest: Option<&HalfTreeNode<I>> = None;
+        let mut cursor = self.head.as_deref();
+        for i in 0.. {
+            let Some(root) = cursor else {
+                break;
+            };
+            ensure!(
+                root.parent.is_none(),
+                "parent pointers",
+                "root {i} has a parent"
+            );
+            ensure!(
+                root.prev == prev,
+                "prev pointers",
+                "root {i} doesn't point to the root before it"
+            );
+            if self.min == Some(NonNull::from(root)) {
+                ensure!(
+                    self.prev_min == prev,
+                    "prev_min pointer",
+                    "the min is root {i} but prev_min doesn't point to the root before it"
+                );
+                min_found = true;
+            }
+            if smallest.is_none_or(|smallest| self.compare.lt(&root.item, &smallest.item)) {
+                smallest = Some(root);
+            }
+            len += self.check_half_tree(root, &mut handles_found)?;
+            prev = Some(NonNull::from(root));
+            cursor = root.right.as_deref();
+        }
+        ensure!(
+            self.tail == prev,
+            "tail pointer",
+            "tail doesn't point to the last root"
+        );
+        match smallest {
+            Some(smallest) => {
+                ensure!(min_found, "min pointer", "min doesn't point to a root");
+                // SAFETY: min points to one of the roots walked above
+                let min = unsafe { self.min.unwrap().as_ref() };
+                ensure!(
+                    self.compare.le(&min.item, &smallest.item),
+                    "min pointer",
+                    "a root is smaller than the one min points to"
+                );
+            }
+            None => ensure!(
+                self.min.is_none() && self.prev_min.is_none(),
+                "min pointer",
+                "min or prev_min is set in an empty heap"
+            ),
+        }
+        check_len(self.len, len)?;
+        check_handle_count(&self.handles, handles_found)
+    }
+}
+
+/// Draws the list of half trees side by side, marking the ones `min`, `prev_min` and `tail`
+/// point to, with each node labelled with its rank and an edge to each of its children
+impl<I: Debug, R: RankRule, C> ToDot for RankPairingHeap<I, R, C> {
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut roots = Vec::new();
+        let mut stack = Vec::new();
+        let mut cursor = self.head.as_deref();
+        while let Some(root) = cursor {
+            let id = dot.node(&root.item, &format!("rank {}", root.rank), "");
+            let root_ptr = Some(NonNull::from(root));
+            for (name, ptr) in [
+                ("min", self.min),
+                ("prev_min", self.prev_min),
+                ("tail", self.tail),
+            ] {
+                if ptr == root_ptr {
+                    dot.marker(name, id);
+                }
+            }
+            roots.push(id);
+            // The right link of a root is the next half tree rather than a child
+            if let Some(left) = root.left.as_deref() {
+                stack.push((left, id, "label=left"));
+            }
+            cursor = root.right.as_deref();
+        }
+        dot.list(&roots);
+        while let Some((node, parent_id, attributes)) = stack.pop() {
+            let id = dot.node(&node.item, &format!("rank {}", node.rank), "");
+            dot.edge(parent_id, id, attributes);
+            if let Some(right) = node.right.as_deref() {
+                stack.push((right, id, "label=right"));
+            }
+            if let Some(left) = node.left.as_deref() {
+                stack.push((left, id, "label=left"));
+            }
+        }
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests, ByKey, FnCompare, MaxOrder};
+
+    type HeapU32 = RankPairingHeap<u32>;
+
+    #[test]
+    fn simple() {
+        tests::simple::<HeapU32>();
+    }
+    #[test]
+    fn differential() {
+        tests::differential::<HeapU32>();
+    }
+    #[test]
+    fn empty_heap() {
+        tests::empty_heap::<HeapU32>();
+    }
+    #[test]
+    fn insert_after_extract() {
+        tests::insert_after_extract::<HeapU32>();
+    }
+    #[test]
+    fn duplicate_items() {
+        tests::duplicate_items::<HeapU32>();
+    }
+    #[test]
+    fn mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<HeapU32>();
+    }
+    #[test]
+    fn meld() {
+        tests::meld::<HeapU32>();
+    }
+    #[test]
+    fn heapify() {
+        tests::heapify::<HeapU32>();
+    }
+    #[test]
+    fn heapify_duplicates() {
+        tests::heapify_duplicates::<HeapU32>();
+    }
+    #[test]
+    fn large_input() {
+        tests::large_input::<HeapU32>();
+    }
+    #[test]
+    fn meld_empty_and_non_empty() {
+        tests::meld_empty_and_non_empty::<HeapU32>();
+    }
+    #[test]
+    fn meld_two_empty_heaps() {
+        tests::meld_two_empty_heaps::<HeapU32>();
+    }
+    #[test]
+    fn len_and_clear() {
+        tests::len_and_clear::<HeapU32>();
+    }
+    #[test]
+    fn iter() {
+        tests::iter::<HeapU32>();
+    }
+    #[test]
+    fn drain() {
+        tests::drain(|heap: &mut HeapU32| heap.drain().collect());
+    }
+    #[test]
+    fn drain_sorted() {
+        tests::drain_sorted::<HeapU32>();
+    }
+    #[test]
+    fn std_traits() {
+        tests::std_traits::<HeapU32>();
+    }
+    #[test]
+    fn deep_heap() {
+        tests::deep_heap::<HeapU32>();
+    }
+    #[test]
+    fn drop_huge() {
+        tests::drop_huge::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key() {
+        tests::decrease_key::<HeapU32>();
+    }
+    #[test]
+    fn decrease_key_many() {
+        tests::decrease_key_many::<HeapU32>();
+    }
+    #[test]
+    fn delete() {
+        tests::delete::<HeapU32>();
+    }
+    #[test]
+    fn handles_after_meld() {
+        tests::handles_after_meld::<HeapU32>();
+    }
+    #[test]
+    fn stale_handles_rejected() {
+        tests::stale_handles_rejected::<HeapU32>();
+    }
+    #[test]
+    fn clone_handles() {
+        tests::clone_handles::<HeapU32>();
+    }
+    #[test]
+    fn max_order() {
+        tests::max_order::<RankPairingHeap<u32, Type2, MaxOrder>>();
+    }
+    #[test]
+    fn by_key() {
+        tests::by_key(|| {
+            RankPairingHeap::<_, Type2, _>::with_comparator(ByKey(|item: &(u32, char)| item.0))
+        });
+    }
+    #[test]
+    fn fn_compare_decrease_key() {
+        tests::fn_compare_decrease_key(RankPairingHeap::<_, Type2, _>::with_comparator(FnCompare(
+            |a: &u32, b: &u32| b.cmp(a),
+        )));
+    }
+
+    type Type1U32 = RankPairingHeap<u32, Type1>;
+
+    #[test]
+    fn type1_mixed_insertions_and_extractions() {
+        tests::mixed_insertions_and_extractions::<Type1U32>();
+    }
+    #[test]
+    fn type1_differential() {
+        tests::differential::<Type1U32>();
+    }
+    #[test]
+    fn type1_meld() {
+        tests::meld::<Type1U32>();
+    }
+    #[test]
+    fn type1_large_input() {
+        tests::large_input::<Type1U32>();
+    }
+    #[test]
+    fn type1_decrease_key_many() {
+        tests::decrease_key_many::<Type1U32>();
+    }
+    #[test]
+    fn type1_delete() {
+        tests::delete::<Type1U32>();
+    }
+    #[test]
+    fn type1_handles_after_meld() {
+        tests::handles_after_meld::<Type1U32>();
+    }
+
+    fn rank_rule_kept<R: RankRule>() {
+        let mut heap = RankPairingHeap::<u32, R>::default();
+        let handles: Vec<_> = (0..tests::sized(2000))
+            .map(|i| heap.insert_with_handle(10_000 + i))
+            .collect();
+        // Link the roots into half trees
+        heap.extract_min();
+        heap.check_invariants().unwrap();
+
+        for (i, &handle) in handles.iter().enumerate().skip(1) {
+            let item = (i as u32 * 7919) % 10_000;
+            if i % 5 == 0 {
+                assert_eq!(heap.delete(handle), Ok(10_000 + i as u32));
+            } else {
+                assert_eq!(heap.decrease_key(handle, item), Ok(()));
+            }
+            if i % 100 == 0 {
+                heap.check_invariants().unwrap();
+                heap.extract_min();
+                heap.check_invariants().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn type1_rank_rule_kept() {
+        rank_rule_kept::<Type1>();
+    }
+    #[test]
+    fn type2_rank_rule_kept() {
+        rank_rule_kept::<Type2>();
+    }
+}
@@ -0,0 +1,260 @@
+use std::{
+    cmp::Ordering,
+    sync::atomic::{self, AtomicU64},
+};
+
+use crate::{
+    dot::ToDot, AddressableHeap, BinaryHeap, BinomialHeap, Compare, FibonacciHeap, Handle,
+    HandleError, LazyBinomialHeap, MinHeap, MinOrder, PairingHeap, TwoPass,
+};
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+use crate::{CheckInvariants, InvariantError};
+
+/// Counter used to stamp every item with the time it was inserted, even across different heaps
+static NEXT_STAMP: AtomicU64 = AtomicU64::new(0);
+
+/// Item stored by a [`StableHeap`], along with when it was inserted
+#[derive(Debug)]
+pub struct Stamped<I> {
+    item: I,
+    stamp: u64,
+}
+
+impl<I> Stamped<I> {
+    fn new(item: I) -> Self {
+        Self {
+            item,
+            stamp: NEXT_STAMP.fetch_add(1, atomic::Ordering::Relaxed),
+        }
+    }
+}
+
+/// Orders [`Stamped`] items with `C`, and items that `C` finds equal by when they were inserted
+#[derive(Clone, Copy, Default, Debug)]
+pub struct StableOrder<C>(pub C);
+
+impl<I, C: Compare<I>> Compare<Stamped<I>> for StableOrder<C> {
+    fn compare(&self, a: &Stamped<I>, b: &Stamped<I>) -> Ordering {
+        self.0.compare(&a.item, &b.item).then(a.stamp.cmp(&b.stamp))
+    }
+}
+
+/// Heap that extracts equal items in the order they were inserted. Insertion times are shared by
+/// all heaps, so after a meld the items inserted first still come out first whichever heap they
+/// came from
+pub struct StableHeap<H> {
+    heap: H,
+}
+
+pub type StableBinaryHeap<I, C = MinOrder> = StableHeap<BinaryHeap<Stamped<I>, StableOrder<C>>>;
+pub type StableBinomialHeap<I, C = MinOrder> = StableHeap<BinomialHeap<Stamped<I>, StableOrder<C>>>;
+pub type StableLazyBinomialHeap<I, C = MinOrder> =
+    StableHeap<LazyBinomialHeap<Stamped<I>, StableOrder<C>>>;
+pub type StableFibonacciHeap<I, C = MinOrder> =
+    StableHeap<FibonacciHeap<Stamped<I>, StableOrder<C>>>;
+pub type StablePairingHeap<I, S = TwoPass, C = MinOrder> =
+    StableHeap<PairingHeap<Stamped<I>, S, StableOrder<C>>>;
+
+impl<H: Default> Default for StableHeap<H> {
+    fn default() -> Self {
+        Self { heap: H::default() }
+    }
+}
+
+impl<H> StableHeap<H> {
+    /// Wraps an empty heap, which is how to use a comparator that doesn't implement `Default`
+    pub fn from_heap(heap: H) -> Self {
+        Self { heap }
+    }
+}
+
+impl<I, H: MinHeap<Item = Stamped<I>>> MinHeap for StableHeap<H> {
+    type Item = I;
+
+    fn peek_min(&self) -> Option<&I> {
+        self.heap.peek_min().map(|stamped| &stamped.item)
+    }
+
+    fn extract_min(&mut self) -> Option<I> {
+        self.heap.extract_min().map(|stamped| stamped.item)
+    }
+
+    fn insert(&mut self, item: I) {
+        self.heap.insert(Stamped::new(item));
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    /// Inserts the items one at a time, so equal items come out in the order of the vector
+    fn heapify(items: Vec<I>) -> Self
+    where
+        Self: Default,
+    {
+        let mut heap = Self::default();
+        for item in items {
+            heap.insert(item);
+        }
+        heap
+    }
+
+    fn meld(heap_a: Self, heap_b: Self) -> Self {
+        Self {
+            heap: H::meld(heap_a.heap, heap_b.heap),
+        }
+    }
+}
+
+impl<I, H: AddressableHeap<Item = Stamped<I>>> AddressableHeap for StableHeap<H> {
+    fn insert_with_handle(&mut self, item: I) -> Handle {
+        self.heap.insert_with_handle(Stamped::new(item))
+    }
+
+    fn get(&self, handle: Handle) -> Option<&I> {
+        self.heap.get(handle).map(|stamped| &stamped.item)
+    }
+
+    /// The item keeps the insertion time of the one it replaces
+    fn decrease_key(&mut self, handle: Handle, item: I) -> Result<(), HandleError> {
+        let stamp = self
+            .heap
+            .get(handle)
+            .ok_or(HandleError::InvalidHandle)?
+            .stamp;
+        self.heap.decrease_key(handle, Stamped { item, stamp })
+    }
+
+    fn delete(&mut self, handle: Handle) -> Result<I, HandleError> {
+        self.heap.delete(handle).map(|stamped| stamped.item)
+    }
+}
+
+#[cfg(any(test, debug_assertions, feature = "validate"))]
+impl<H: CheckInvariants> CheckInvariants for StableHeap<H> {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        self.heap.check_invariants()
+    }
+}
+
+/// Draws the wrapped heap, whose items show when they were inserted
+impl<H: ToDot> ToDot for StableHeap<H> {
+    fn to_dot(&self) -> String {
+        self.heap.to_dot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests, ArenaBinomialHeap, ArenaLazyBinomialHeap, ByKey, DaryHeap, FrontToBack, LeftistHeap,
+        MaxOrder, MinMaxHeap, Multipass, RankPairingHeap, SkewHeap, Type2,
+    };
+
+    type Item = Stamped<(u32, u32)>;
+    /// Compares only the first half of the items, so the second half tells equal items apart
+    type Order = StableOrder<ByKey<fn(&(u32, u32)) -> u32>>;
+
+    fn order() -> Order {
+        StableOrder(ByKey(|item| item.0))
+    }
+
+    #[test]
+    fn binary() {
+        tests::stable(|| StableHeap::from_heap(BinaryHeap::<Item, _>::with_comparator(order())));
+    }
+    #[test]
+    fn dary() {
+        tests::stable(|| StableHeap::from_heap(DaryHeap::<Item, 4, _>::with_comparator(order())));
+    }
+    #[test]
+    fn binomial() {
+        tests::stable(|| StableHeap::from_heap(BinomialHeap::<Item, _>::with_comparator(order())));
+    }
+    #[test]
+    fn lazy_binomial() {
+        tests::stable(|| {
+            StableHeap::from_heap(LazyBinomialHeap::<Item, _>::with_comparator(order()))
+        });
+    }
+    #[test]
+    fn arena_binomial() {
+        tests::stable(|| {
+            StableHeap::from_heap(ArenaBinomialHeap::<Item, _>::with_comparator(order()))
+        });
+    }
+    #[test]
+    fn arena_lazy_binomial() {
+        tests::stable(|| {
+            StableHeap::from_heap(ArenaLazyBinomialHeap::<Item, _>::with_comparator(order()))
+        });
+    }
+    #[test]
+    fn fibonacci() {
+        tests::stable(|| StableHeap::from_heap(FibonacciHeap::<Item, _>::with_comparator(order())));
+    }
+    #[test]
+    fn pairing() {
+        tests::stable(|| {
+            StableHeap::from_heap(PairingHeap::<Item, TwoPass, _>::with_comparator(order()))
+        });
+        tests::stable(|| {
+            StableHeap::from_heap(PairingHeap::<Item, FrontToBack, _>::with_comparator(order()))
+        });
+        tests::stable(|| {
+            StableHeap::from_heap(PairingHeap::<Item, Multipass, _>::with_comparator(order()))
+        });
+    }
+    #[test]
+    fn leftist() {
+        tests::stable(|| StableHeap::from_heap(LeftistHeap::<Item, _>::with_comparator(order())));
+    }
+    #[test]
+    fn skew() {
+        tests::stable(|| StableHeap::from_heap(SkewHeap::<Item, _>::with_comparator(order())));
+    }
+    #[test]
+    fn rank_pairing() {
+        tests::stable(|| {
+            StableHeap::from_heap(RankPairingHeap::<Item, Type2, _>::with_comparator(order()))
+        });
+    }
+    #[test]
+    fn min_max() {
+        tests::stable(|| StableHeap::from_heap(MinMaxHeap::<Item, _>::with_comparator(order())));
+    }
+
+    #[test]
+    fn decrease_key_keeps_insertion_time() {
+        let mut heap = StableHeap::from_heap(FibonacciHeap::<Item, _>::with_comparator(order()));
+        let a = heap.insert_with_handle((5, 0));
+        heap.insert((2, 1));
+        let c = heap.insert_with_handle((4, 2));
+        assert_eq!(heap.decrease_key(a, (2, 0)), Ok(()));
+        assert_eq!(heap.decrease_key(c, (2, 2)), Ok(()));
+        assert_eq!(heap.decrease_key(c, (3, 2)), Err(HandleError::KeyIncreased));
+        assert_eq!(heap.get(c), Some(&(2, 2)));
+        assert_eq!(heap.extract_min(), Some((2, 0)));
+        assert_eq!(heap.delete(c), Ok((2, 2)));
+        assert_eq!(heap.delete(c), Err(HandleError::InvalidHandle));
+        assert_eq!(heap.extract_min(), Some((2, 1)));
+        assert_eq!(heap.extract_min(), None);
+    }
+
+    #[test]
+    fn shared_tests() {
+        tests::simple::<StableBinaryHeap<u32>>();
+        tests::differential::<StableBinaryHeap<u32>>();
+        tests::differential::<StableLazyBinomialHeap<u32>>();
+        tests::meld::<StableLazyBinomialHeap<u32>>();
+        tests::heapify_duplicates::<StableBinomialHeap<u32>>();
+        tests::max_order::<StablePairingHeap<u32, TwoPass, MaxOrder>>();
+        tests::decrease_key::<StableFibonacciHeap<u32>>();
+        tests::handles_after_meld::<StablePairingHeap<u32>>();
+    }
+}
@@ -0,0 +1,111 @@
+use std::{cell::Cell, cmp::Ordering};
+
+use crate::{Compare, MinOrder};
+
+/// Work the heaps did on the current thread, to compare their algorithmic cost apart from how long
+/// they take. Comparisons are counted by heaps using a [`CountingOrder`], and the rest only with
+/// the `stats` feature
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Stats {
+    /// Items compared through a [`CountingOrder`]
+    pub comparisons: u64,
+    /// Trees made a child of another tree, or nodes added to a merged right spine
+    pub links: u64,
+    /// Items swapped between two places of a heap
+    pub swaps: u64,
+    /// Nodes allocated on their own, so the arena and array-based heaps never count any
+    pub allocations: u64,
+}
+
+thread_local! {
+    static STATS: Cell<Stats> = const {
+        Cell::new(Stats {
+            comparisons: 0,
+            links: 0,
+            swaps: 0,
+            allocations: 0,
+        })
+    };
+}
+
+impl Stats {
+    /// Returns the work counted on the current thread since the last call, and starts counting
+    /// from zero again
+    pub fn take() -> Self {
+        STATS.with(Cell::take)
+    }
+}
+
+pub(crate) fn add(update: impl FnOnce(&mut Stats)) {
+    STATS.with(|cell| {
+        let mut stats = cell.get();
+        update(&mut stats);
+        cell.set(stats);
+    });
+}
+
+/// Counts one more of the event in [`Stats`], only with the `stats` feature so other builds don't
+/// pay for it
+macro_rules! count {
+    ($counter:ident) => {
+        #[cfg(feature = "stats")]
+        $crate::stats::add(|stats| stats.$counter += 1);
+    };
+}
+pub(crate) use count;
+
+/// Orders items like the comparator it wraps, counting every comparison in
+/// [`Stats::comparisons`]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CountingOrder<C = MinOrder>(pub C);
+
+impl<T, C: Compare<T>> Compare<T> for CountingOrder<C> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        add(|stats| stats.comparisons += 1);
+        self.0.compare(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BinaryHeap, MinHeap};
+
+    #[test]
+    fn comparisons_counted() {
+        Stats::take();
+        let mut heap = BinaryHeap::<u32, CountingOrder>::default();
+        heap.insert(2);
+        assert_eq!(Stats::take().comparisons, 0);
+        heap.insert(1);
+        heap.insert(3);
+        assert_eq!(Stats::take().comparisons, 2);
+        // 3 moves to the root and is compared with its only child 2, and then with itself once it
+        // has no children
+        assert_eq!(heap.extract_min(), Some(1));
+        assert_eq!(Stats::take().comparisons, 2);
+        assert_eq!(Stats::take(), Stats::default());
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn work_counted() {
+        Stats::take();
+        let mut heap = BinaryHeap::<u32>::default();
+        for item in [2, 1, 3] {
+            heap.insert(item);
+        }
+        heap.extract_min();
+        let stats = Stats::take();
+        assert_eq!((stats.comparisons, stats.swaps), (0, 3));
+
+        // Inserting into a binomial heap adds one like a binary counter, where each carry links
+        // two trees
+        let mut heap = crate::BinomialHeap::<u32>::default();
+        for item in 0..4 {
+            heap.insert(item);
+        }
+        let stats = Stats::take();
+        assert_eq!((stats.links, stats.allocations), (3, 4));
+    }
+}
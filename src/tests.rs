@@ -1,173 +1,941 @@
-use crate::MinHeap;
+use std::{cmp::Ordering, fmt::Debug};
 
-pub fn simple<H: MinHeap<Item = u32>>() {
+use crate::{
+    AddressableHeap, CheckInvariants, DoubleEndedHeap, FnCompare, HandleError, KeyValue,
+    KeyValueHeap, MinHeap,
+};
+
+mod differential;
+
+pub use differential::{differential, differential_with, Config};
+
+/// Panics with the first broken invariant of the heap, called after every operation
+pub fn check(heap: &impl CheckInvariants) {
+    if let Err(err) = heap.check_invariants() {
+        panic!("{err}");
+    }
+}
+
+/// Length of the larger tests, cut down under Miri which runs them thousands of times slower
+pub(crate) const fn sized(len: u32) -> u32 {
+    if cfg!(miri) {
+        len / 10
+    } else {
+        len
+    }
+}
+
+pub fn simple<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap = H::make_heap();
     heap.insert(3);
+    check(&heap);
     heap.insert(2);
+    check(&heap);
     heap.insert(1);
+    check(&heap);
 
     assert_eq!(heap.peek_min(), Some(&1));
 
     assert_eq!(heap.extract_min(), Some(1));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(2));
+    check(&heap);
     assert_eq!(heap.peek_min(), Some(&3));
     assert_eq!(heap.extract_min(), Some(3));
+    check(&heap);
     assert_eq!(heap.peek_min(), None);
     assert_eq!(heap.extract_min(), None);
+    check(&heap);
 }
 
-pub fn empty_heap<H: MinHeap<Item = u32>>() {
+pub fn empty_heap<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap = H::make_heap();
 
     assert_eq!(heap.peek_min(), None);
     assert_eq!(heap.extract_min(), None);
+    check(&heap);
 }
 
-pub fn insert_after_extract<H: MinHeap<Item = u32>>() {
+pub fn insert_after_extract<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap = H::make_heap();
 
     heap.insert(5);
+    check(&heap);
     heap.insert(3);
+    check(&heap);
     heap.insert(8);
+    check(&heap);
 
     assert_eq!(heap.extract_min(), Some(3));
+    check(&heap);
 
     heap.insert(2);
+    check(&heap);
 
     assert_eq!(heap.extract_min(), Some(2));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(8));
+    check(&heap);
     assert_eq!(heap.extract_min(), None);
+    check(&heap);
 }
 
-pub fn duplicate_items<H: MinHeap<Item = u32>>() {
+pub fn duplicate_items<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap = H::make_heap();
 
     heap.insert(5);
+    check(&heap);
     heap.insert(5);
+    check(&heap);
     heap.insert(5);
+    check(&heap);
 
     assert_eq!(heap.peek_min(), Some(&5));
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
     assert_eq!(heap.extract_min(), None);
+    check(&heap);
 }
 
-pub fn mixed_insertions_and_extractions<H: MinHeap<Item = u32>>() {
+pub fn mixed_insertions_and_extractions<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap = H::make_heap();
 
     heap.insert(10);
+    check(&heap);
     heap.insert(20);
+    check(&heap);
     heap.insert(5);
+    check(&heap);
 
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
 
     heap.insert(15);
+    check(&heap);
     heap.insert(30);
+    check(&heap);
 
     assert_eq!(heap.extract_min(), Some(10));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(15));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(20));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(30));
+    check(&heap);
     assert_eq!(heap.extract_min(), None);
+    check(&heap);
 }
 
-pub fn meld<H: MinHeap<Item = u32>>() {
+pub fn meld<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap_a = H::make_heap();
     heap_a.insert(0);
+    check(&heap_a);
     heap_a.insert(3);
+    check(&heap_a);
     heap_a.insert(6);
+    check(&heap_a);
 
     let mut heap_b = H::make_heap();
     heap_b.insert(1);
+    check(&heap_b);
     heap_b.insert(4);
+    check(&heap_b);
     heap_b.insert(7);
+    check(&heap_b);
 
     let mut heap_c = H::make_heap();
     heap_c.insert(2);
+    check(&heap_c);
     heap_c.insert(5);
+    check(&heap_c);
     heap_c.insert(8);
+    check(&heap_c);
 
     let heap_ac = H::meld(heap_a, heap_c);
+    check(&heap_ac);
     let mut heap_abc = H::meld(heap_ac, heap_b);
+    check(&heap_abc);
 
     assert_eq!(heap_abc.extract_min(), Some(0));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), Some(1));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), Some(2));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), Some(3));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), Some(4));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), Some(5));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), Some(6));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), Some(7));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), Some(8));
+    check(&heap_abc);
     assert_eq!(heap_abc.extract_min(), None);
+    check(&heap_abc);
 
     heap_a = H::make_heap();
     heap_b = H::make_heap();
     let mut heap_ab = H::meld(heap_a, heap_b);
+    check(&heap_ab);
     assert_eq!(heap_ab.extract_min(), None);
+    check(&heap_ab);
 }
 
-pub fn heapify<H: MinHeap<Item = u32>>() {
+pub fn heapify<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap = H::heapify(vec![3, 2, 1]);
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(1));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(2));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(3));
+    check(&heap);
     assert_eq!(heap.extract_min(), None);
+    check(&heap);
 
     heap = H::heapify(vec![]);
+    check(&heap);
     assert_eq!(heap.extract_min(), None);
+    check(&heap);
 }
 
-pub fn heapify_duplicates<H: MinHeap<Item = u32>>() {
+pub fn heapify_duplicates<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap = H::heapify(vec![5, 1, 5, 2, 5, 3, 5]);
+    check(&heap);
 
     assert_eq!(heap.extract_min(), Some(1));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(2));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(3));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
     assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
     assert_eq!(heap.extract_min(), None);
+    check(&heap);
 }
 
-pub fn large_input<H: MinHeap<Item = u32>>() {
-    let mut heap = H::heapify((1..=10000).rev().collect::<Vec<u32>>());
+pub fn large_input<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
+    let len = sized(10000);
+    let mut heap = H::heapify((1..=len).rev().collect::<Vec<u32>>());
+    check(&heap);
 
-    for i in 1..=10000 {
+    for i in 1..=len {
         assert_eq!(heap.extract_min(), Some(i));
     }
 
     assert_eq!(heap.extract_min(), None);
 }
 
-pub fn meld_empty_and_non_empty<H: MinHeap<Item = u32>>() {
+pub fn meld_empty_and_non_empty<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let mut heap_a = H::make_heap();
     heap_a.insert(3);
+    check(&heap_a);
     heap_a.insert(7);
+    check(&heap_a);
 
     let mut heap_b = H::make_heap();
     heap_b.insert(1);
+    check(&heap_b);
     heap_b.insert(5);
+    check(&heap_b);
 
     let mut heap_a_and_empty = H::meld(heap_a, H::make_heap());
+    check(&heap_a_and_empty);
     let mut heap_empty_and_b = H::meld(H::make_heap(), heap_b);
+    check(&heap_empty_and_b);
 
     assert_eq!(heap_a_and_empty.extract_min(), Some(3));
+    check(&heap_a_and_empty);
     assert_eq!(heap_a_and_empty.extract_min(), Some(7));
+    check(&heap_a_and_empty);
     assert_eq!(heap_a_and_empty.extract_min(), None);
+    check(&heap_a_and_empty);
     assert_eq!(heap_empty_and_b.extract_min(), Some(1));
+    check(&heap_empty_and_b);
     assert_eq!(heap_empty_and_b.extract_min(), Some(5));
+    check(&heap_empty_and_b);
     assert_eq!(heap_empty_and_b.extract_min(), None);
+    check(&heap_empty_and_b);
 }
 
-pub fn meld_two_empty_heaps<H: MinHeap<Item = u32>>() {
+pub fn meld_two_empty_heaps<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
     let heap_a = H::make_heap();
     let heap_b = H::make_heap();
 
     let mut heap_ab = H::meld(heap_a, heap_b);
+    check(&heap_ab);
 
     assert_eq!(heap_ab.extract_min(), None);
+    check(&heap_ab);
+}
+
+pub fn len_and_clear<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap = H::make_heap();
+    assert_eq!(heap.len(), 0);
+    assert!(heap.is_empty());
+
+    for i in 0..10 {
+        heap.insert(i);
+        check(&heap);
+        assert_eq!(heap.len(), i as usize + 1);
+    }
+    assert!(!heap.is_empty());
+    for i in 0..4 {
+        heap.extract_min();
+        check(&heap);
+        assert_eq!(heap.len(), 9 - i);
+    }
+
+    let other = H::heapify((20..25).collect());
+    check(&other);
+    assert_eq!(other.len(), 5);
+    heap = H::meld(heap, other);
+    check(&heap);
+    assert_eq!(heap.len(), 11);
+    heap = H::meld(heap, H::make_heap());
+    check(&heap);
+    assert_eq!(heap.len(), 11);
+    heap = H::meld(H::make_heap(), heap);
+    check(&heap);
+    assert_eq!(heap.len(), 11);
+
+    heap.clear();
+    check(&heap);
+    assert_eq!(heap.len(), 0);
+    assert!(heap.is_empty());
+    assert_eq!(heap.peek_min(), None);
+    assert_eq!(heap.extract_min(), None);
+    check(&heap);
+    assert_eq!(heap.len(), 0);
+
+    heap.insert(3);
+    check(&heap);
+    heap.insert(1);
+    check(&heap);
+    assert_eq!(heap.len(), 2);
+    assert_eq!(heap.extract_min(), Some(1));
+    check(&heap);
+    assert_eq!(heap.len(), 1);
+}
+
+pub fn iter<H>()
+where
+    H: MinHeap<Item = u32> + CheckInvariants + Default + IntoIterator<Item = u32>,
+    for<'a> &'a H: IntoIterator<Item = &'a u32>,
+{
+    let mut heap = H::heapify((0..100).rev().collect());
+    check(&heap);
+    for _ in 0..10 {
+        heap.extract_min();
+        check(&heap);
+    }
+    let other = H::heapify((100..150).collect());
+    check(&other);
+    heap = H::meld(heap, other);
+    check(&heap);
+    heap.insert(5);
+    check(&heap);
+
+    let expected: Vec<u32> = (5..150).filter(|&i| i >= 10 || i == 5).collect();
+    let iter = (&heap).into_iter();
+    assert_eq!(iter.size_hint(), (expected.len(), Some(expected.len())));
+    let mut items: Vec<u32> = iter.copied().collect();
+    items.sort();
+    assert_eq!(items, expected);
+
+    // Iterating doesn't change the heap
+    assert_eq!(heap.len(), expected.len());
+    assert_eq!(heap.peek_min(), Some(&5));
+
+    let mut items: Vec<u32> = heap.into_iter().collect();
+    items.sort();
+    assert_eq!(items, expected);
+
+    assert_eq!((&H::make_heap()).into_iter().next(), None);
+    assert_eq!(H::make_heap().into_iter().next(), None);
+}
+
+pub fn drain<H: MinHeap<Item = u32> + CheckInvariants + Default>(
+    drain: impl Fn(&mut H) -> Vec<u32>,
+) {
+    let mut heap = H::heapify(vec![4, 1, 3, 2]);
+    check(&heap);
+    heap.insert(0);
+    check(&heap);
+
+    let mut items = drain(&mut heap);
+    check(&heap);
+    items.sort();
+    assert_eq!(items, vec![0, 1, 2, 3, 4]);
+    assert_eq!(heap.len(), 0);
+    assert_eq!(heap.extract_min(), None);
+    check(&heap);
+
+    heap.insert(7);
+    check(&heap);
+    heap.insert(6);
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(6));
+    check(&heap);
+    assert_eq!(drain(&mut heap), vec![7]);
+    check(&heap);
+    assert_eq!(drain(&mut heap), vec![]);
+    check(&heap);
+}
+
+pub fn drain_sorted<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap = H::heapify(vec![5, 9, 1, 7, 3]);
+    check(&heap);
+    let mut drain = heap.drain_sorted();
+    assert_eq!(drain.len(), 5);
+    assert_eq!(drain.next(), Some(1));
+    assert_eq!(drain.next(), Some(3));
+    assert_eq!(drain.len(), 3);
+    drop(drain);
+    check(&heap);
+    // Items not taken from the iterator are removed too
+    assert!(heap.is_empty());
+
+    heap.insert(4);
+    check(&heap);
+    heap.insert(2);
+    check(&heap);
+    heap.insert(8);
+    check(&heap);
+    assert_eq!(heap.drain_sorted().collect::<Vec<_>>(), vec![2, 4, 8]);
+    check(&heap);
+
+    let heap = H::heapify((0..1000).rev().collect());
+    check(&heap);
+    assert_eq!(heap.into_sorted_vec(), (0..1000).collect::<Vec<_>>());
+    assert_eq!(H::make_heap().into_sorted_vec(), vec![]);
+}
+
+pub fn std_traits<H>()
+where
+    H: MinHeap<Item = u32>
+        + CheckInvariants
+        + Default
+        + FromIterator<u32>
+        + Extend<u32>
+        + From<Vec<u32>>
+        + From<[u32; 3]>
+        + Clone
+        + Debug
+        + PartialEq,
+{
+    let mut heap: H = (0..10).rev().collect();
+    check(&heap);
+    assert_eq!(heap.len(), 10);
+    assert_eq!(heap.peek_min(), Some(&0));
+
+    // Small and large batches compared to the size of the heap
+    heap.extend([20, 3]);
+    check(&heap);
+    heap.extend((30..1000).rev());
+    check(&heap);
+    heap.extend(None);
+    check(&heap);
+    assert_eq!(heap.len(), 982);
+
+    let copy = heap.clone();
+    check(&copy);
+    assert_eq!(copy, heap);
+    let mut expected: Vec<u32> = (0..10).chain([20, 3]).chain(30..1000).collect();
+    expected.sort();
+    assert_eq!(heap.into_sorted_vec(), expected);
+    assert_eq!(copy.len(), 982);
+
+    assert_eq!(H::from(vec![2, 3, 1]), H::from([3, 1, 2]));
+    assert_ne!(H::from([1, 1, 2]), H::from([1, 2, 2]));
+    assert_ne!(H::from(vec![1, 2]), H::from([1, 2, 3]));
+    assert_eq!(H::make_heap(), H::from(vec![]));
+
+    let heap = H::from([1, 1, 1]);
+    check(&heap);
+    assert_eq!(format!("{heap:?}"), "[1, 1, 1]");
+    assert_eq!(format!("{:?}", H::make_heap()), "[]");
+}
+
+pub fn clone_handles<H: AddressableHeap<Item = u32> + CheckInvariants + Default + Clone>() {
+    let mut heap = H::make_heap();
+    let a = heap.insert_with_handle(5);
+    check(&heap);
+    heap.insert(3);
+    check(&heap);
+
+    // The handles only refer to items of the original heap
+    let mut copy = heap.clone();
+    check(&copy);
+    assert_eq!(copy.get(a), None);
+    assert_eq!(copy.decrease_key(a, 0), Err(HandleError::InvalidHandle));
+    check(&copy);
+    assert_eq!(heap.decrease_key(a, 1), Ok(()));
+    check(&heap);
+    let b = copy.insert_with_handle(4);
+    check(&copy);
+
+    let mut heap = H::meld(heap, copy);
+    check(&heap);
+    assert_eq!(heap.delete(b), Ok(4));
+    check(&heap);
+    for i in [1, 3, 3, 5] {
+        assert_eq!(heap.extract_min(), Some(i));
+        check(&heap);
+    }
+}
+
+/// Long enough that recursing once per item would overflow the stack
+#[cfg(not(miri))]
+pub(crate) const DEEP_LEN: u32 = 1_000_000;
+/// Miri only runs the deep paths to check their pointer accesses, too slowly to reach the depth
+/// that would overflow
+#[cfg(miri)]
+pub(crate) const DEEP_LEN: u32 = 500;
+
+pub fn deep_heap<H>()
+where
+    H: MinHeap<Item = u32> + Default + Clone + IntoIterator<Item = u32>,
+    for<'a> &'a H: IntoIterator<Item = &'a u32>,
+{
+    let mut heap = H::make_heap();
+    for i in 0..DEEP_LEN {
+        heap.insert(i);
+    }
+
+    assert_eq!((&heap).into_iter().count(), DEEP_LEN as usize);
+    let copy = heap.clone();
+    assert_eq!(copy.len(), DEEP_LEN as usize);
+    drop(copy);
+
+    let mut iter = heap.clone().into_iter();
+    assert!(iter.next().is_some());
+    drop(iter);
+
+    heap.clear();
+    for i in (0..DEEP_LEN).rev() {
+        heap.insert(i);
+    }
+    drop(heap);
+}
+
+pub fn drop_huge<H: MinHeap<Item = u32> + Default>() {
+    let mut heap = H::make_heap();
+    for i in 0..20 * DEEP_LEN {
+        heap.insert(i);
+    }
+    assert_eq!(heap.len(), 20 * DEEP_LEN as usize);
+    drop(heap);
+}
+
+pub fn decrease_key<H: AddressableHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap = H::make_heap();
+    let a = heap.insert_with_handle(10);
+    check(&heap);
+    let b = heap.insert_with_handle(20);
+    check(&heap);
+    let c = heap.insert_with_handle(30);
+    check(&heap);
+    heap.insert(15);
+    check(&heap);
+
+    assert_eq!(heap.decrease_key(c, 5), Ok(()));
+    check(&heap);
+    assert_eq!(heap.get(c), Some(&5));
+    assert_eq!(heap.peek_min(), Some(&5));
+    assert_eq!(heap.decrease_key(b, 12), Ok(()));
+    check(&heap);
+    assert_eq!(heap.decrease_key(a, 10), Ok(()));
+    check(&heap);
+    assert_eq!(heap.decrease_key(a, 11), Err(HandleError::KeyIncreased));
+    check(&heap);
+    assert_eq!(heap.get(a), Some(&10));
+
+    assert_eq!(heap.extract_min(), Some(5));
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(10));
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(12));
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(15));
+    check(&heap);
+    assert_eq!(heap.extract_min(), None);
+    check(&heap);
+}
+
+pub fn decrease_key_many<H: AddressableHeap<Item = u32> + CheckInvariants + Default>() {
+    let len = sized(1000);
+    let mut heap = H::make_heap();
+    let handles: Vec<_> = (0..len).map(|i| heap.insert_with_handle(len + i)).collect();
+    check(&heap);
+
+    // Decrease every item to a permutation of 0..len
+    for (i, &handle) in handles.iter().enumerate() {
+        let new_item = (i as u32 * 7919) % len;
+        assert_eq!(heap.decrease_key(handle, new_item), Ok(()));
+        check(&heap);
+        if i % 3 == 0 {
+            heap.insert(2 * len + i as u32);
+            check(&heap);
+        }
+    }
+
+    for i in 0..len {
+        assert_eq!(heap.extract_min(), Some(i));
+        check(&heap);
+    }
+}
+
+pub fn delete<H: AddressableHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap = H::make_heap();
+    let handles: Vec<_> = (0..10).map(|i| heap.insert_with_handle(i)).collect();
+    check(&heap);
+
+    assert_eq!(heap.delete(handles[0]), Ok(0));
+    check(&heap);
+    assert_eq!(heap.delete(handles[5]), Ok(5));
+    check(&heap);
+    assert_eq!(heap.delete(handles[9]), Ok(9));
+    check(&heap);
+    assert_eq!(heap.delete(handles[5]), Err(HandleError::InvalidHandle));
+    check(&heap);
+    assert_eq!(heap.peek_min(), Some(&1));
+    assert_eq!(heap.len(), 7);
+
+    for i in [1, 2, 3, 4, 6, 7, 8] {
+        assert_eq!(heap.extract_min(), Some(i));
+        check(&heap);
+    }
+    assert_eq!(heap.extract_min(), None);
+    check(&heap);
+}
+
+pub fn handles_after_meld<H: AddressableHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap_a = H::make_heap();
+    let mut heap_b = H::make_heap();
+    let handles_a: Vec<_> = (0..20)
+        .map(|i| heap_a.insert_with_handle(100 + i))
+        .collect();
+    check(&heap_a);
+    let handles_b: Vec<_> = (0..20)
+        .map(|i| heap_b.insert_with_handle(200 + i))
+        .collect();
+    check(&heap_b);
+    heap_a.insert(50);
+    check(&heap_a);
+    heap_b.insert(60);
+    check(&heap_b);
+
+    let mut heap = H::meld(heap_a, heap_b);
+    check(&heap);
+    assert_eq!(heap.len(), 42);
+    assert_eq!(heap.get(handles_a[3]), Some(&103));
+    assert_eq!(heap.get(handles_b[3]), Some(&203));
+
+    assert_eq!(heap.decrease_key(handles_b[7], 1), Ok(()));
+    check(&heap);
+    assert_eq!(heap.decrease_key(handles_a[7], 2), Ok(()));
+    check(&heap);
+    assert_eq!(heap.delete(handles_a[0]), Ok(100));
+    check(&heap);
+    assert_eq!(heap.delete(handles_b[19]), Ok(219));
+    check(&heap);
+    assert_eq!(heap.len(), 40);
+
+    assert_eq!(heap.extract_min(), Some(1));
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(2));
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(50));
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(60));
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(101));
+    check(&heap);
+}
+
+pub fn stale_handles_rejected<H: AddressableHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap = H::make_heap();
+    let a = heap.insert_with_handle(1);
+    check(&heap);
+    let b = heap.insert_with_handle(2);
+    check(&heap);
+
+    assert_eq!(heap.extract_min(), Some(1));
+    check(&heap);
+    assert_eq!(heap.get(a), None);
+    assert_eq!(heap.decrease_key(a, 0), Err(HandleError::InvalidHandle));
+    check(&heap);
+    assert_eq!(heap.delete(a), Err(HandleError::InvalidHandle));
+    check(&heap);
+
+    let mut other = H::make_heap();
+    let c = other.insert_with_handle(3);
+    check(&other);
+    assert_eq!(heap.get(c), None);
+    assert_eq!(heap.decrease_key(c, 0), Err(HandleError::InvalidHandle));
+    check(&heap);
+    assert_eq!(heap.delete(c), Err(HandleError::InvalidHandle));
+    check(&heap);
+
+    assert_eq!(heap.get(b), Some(&2));
+    assert_eq!(other.get(b), None);
+
+    heap.clear();
+    check(&heap);
+    assert_eq!(heap.get(b), None);
+    assert_eq!(heap.delete(b), Err(HandleError::InvalidHandle));
+    check(&heap);
+}
+
+pub fn double_ended<H: DoubleEndedHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap = H::make_heap();
+    assert_eq!(heap.peek_max(), None);
+    assert_eq!(heap.extract_max(), None);
+    check(&heap);
+    heap.insert(4);
+    check(&heap);
+    assert_eq!(heap.peek_max(), Some(&4));
+    assert_eq!(heap.peek_min(), Some(&4));
+
+    // Items still in the heap, kept sorted
+    let mut expected = vec![4];
+    for i in 0..sized(2000) {
+        let item = (i * 7919) % 1000;
+        heap.insert(item);
+        check(&heap);
+        let at = expected.partition_point(|&x| x <= item);
+        expected.insert(at, item);
+        match i % 5 {
+            1 => assert_eq!(heap.extract_max(), expected.pop()),
+            3 => assert_eq!(heap.extract_min(), Some(expected.remove(0))),
+            _ => {}
+        }
+        check(&heap);
+        assert_eq!(heap.peek_min(), expected.first());
+        assert_eq!(heap.peek_max(), expected.last());
+    }
+    assert_eq!(heap.len(), expected.len());
+    while let Some(max) = expected.pop() {
+        assert_eq!(heap.extract_max(), Some(max));
+        check(&heap);
+    }
+    assert_eq!(heap.extract_max(), None);
+    check(&heap);
+    assert!(heap.is_empty());
+}
+
+pub fn push_pop_max<H: DoubleEndedHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap = H::make_heap();
+    assert_eq!(heap.push_pop_max(7), 7);
+    check(&heap);
+    assert!(heap.is_empty());
+
+    heap.insert(5);
+    check(&heap);
+    assert_eq!(heap.push_pop_max(3), 5);
+    check(&heap);
+    assert_eq!(heap.push_pop_max(8), 8);
+    check(&heap);
+    for item in [10, 1, 6] {
+        heap.insert(item);
+        check(&heap);
+    }
+    assert_eq!(heap.push_pop_max(0), 10);
+    check(&heap);
+    assert_eq!(heap.push_pop_max(4), 6);
+    check(&heap);
+    assert_eq!(heap.len(), 4);
+    assert_eq!(heap.peek_min(), Some(&0));
+    assert_eq!(heap.into_sorted_vec(), vec![0, 1, 3, 4]);
+}
+
+pub fn max_order<H: MinHeap<Item = u32> + CheckInvariants + Default>() {
+    let mut heap = H::heapify(vec![5, 1, 9, 3]);
+    check(&heap);
+    heap.insert(7);
+    check(&heap);
+    heap.insert(0);
+    check(&heap);
+    let mut other = H::make_heap();
+    other.insert(8);
+    check(&other);
+    other.insert(2);
+    check(&other);
+
+    let mut heap = H::meld(heap, other);
+    check(&heap);
+    assert_eq!(heap.peek_min(), Some(&9));
+    for i in [9, 8, 7, 5, 3, 2, 1, 0] {
+        assert_eq!(heap.extract_min(), Some(i));
+        check(&heap);
+    }
+    assert_eq!(heap.extract_min(), None);
+    check(&heap);
+}
+
+pub fn by_key<H: MinHeap<Item = (u32, char)> + CheckInvariants>(make_heap: impl Fn() -> H) {
+    let mut heap_a = make_heap();
+    heap_a.insert((3, 'c'));
+    check(&heap_a);
+    heap_a.insert((1, 'a'));
+    check(&heap_a);
+    heap_a.insert((5, 'e'));
+    check(&heap_a);
+    let mut heap_b = make_heap();
+    heap_b.insert((4, 'd'));
+    check(&heap_b);
+    heap_b.insert((2, 'b'));
+    check(&heap_b);
+
+    let mut heap = H::meld(heap_a, heap_b);
+    check(&heap);
+    assert_eq!(heap.peek_min(), Some(&(1, 'a')));
+    for item in [(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd'), (5, 'e')] {
+        assert_eq!(heap.extract_min(), Some(item));
+        check(&heap);
+    }
+    assert_eq!(heap.extract_min(), None);
+    check(&heap);
+}
+
+/// Comparison function for `FnCompare`, so heaps ordered in different ways have the same type
+pub type CompareFn = fn(&u32, &u32) -> Ordering;
+
+/// Melds a heap ordered from smallest to largest with one ordered from largest to smallest, and
+/// returns the items in the order the melded heap extracts them. Only the comparator of `heap_a`
+/// is kept, so the result depends on whether the heap reorders the items of `heap_b` with it
+pub fn meld_different_comparators<H: MinHeap<Item = u32>>(
+    with_comparator: impl Fn(FnCompare<CompareFn>) -> H,
+) -> Vec<u32> {
+    let mut heap_a = with_comparator(FnCompare(|a, b| a.cmp(b)));
+    for item in [1, 3, 5] {
+        heap_a.insert(item);
+    }
+    let mut heap_b = with_comparator(FnCompare(|a, b| b.cmp(a)));
+    for item in [2, 4, 6] {
+        heap_b.insert(item);
+    }
+    H::meld(heap_a, heap_b).into_sorted_vec()
+}
+
+pub fn fn_compare_decrease_key<H: AddressableHeap<Item = u32> + CheckInvariants>(mut heap: H) {
+    // The heap is expected to order items from largest to smallest
+    let a = heap.insert_with_handle(10);
+    check(&heap);
+    let b = heap.insert_with_handle(20);
+    check(&heap);
+    heap.insert(15);
+    check(&heap);
+
+    assert_eq!(heap.peek_min(), Some(&20));
+    assert_eq!(heap.decrease_key(a, 5), Err(HandleError::KeyIncreased));
+    check(&heap);
+    assert_eq!(heap.decrease_key(a, 30), Ok(()));
+    check(&heap);
+    assert_eq!(heap.peek_min(), Some(&30));
+    assert_eq!(heap.delete(b), Ok(20));
+    check(&heap);
+
+    assert_eq!(heap.extract_min(), Some(30));
+    check(&heap);
+    assert_eq!(heap.extract_min(), Some(15));
+    check(&heap);
+    assert_eq!(heap.extract_min(), None);
+    check(&heap);
+}
+
+/// Payload that can't be compared, so key-value heaps can only order items by their keys
+#[derive(Debug, PartialEq, Eq)]
+pub struct Payload(pub Vec<u32>);
+
+pub fn key_value<H: MinHeap<Item = KeyValue<u32, Payload>> + CheckInvariants + Default>() {
+    let mut heap_a = KeyValueHeap::<H>::make_heap();
+    heap_a.insert(3, Payload(vec![3; 100]));
+    check(&heap_a);
+    heap_a.insert(1, Payload(vec![1; 100]));
+    check(&heap_a);
+    heap_a.insert(1, Payload(vec![1; 100]));
+    check(&heap_a);
+    let heap_b = KeyValueHeap::<H>::heapify(vec![
+        (4, Payload(vec![4; 100])),
+        (0, Payload(vec![0; 100])),
+        (2, Payload(vec![2; 100])),
+    ]);
+    check(&heap_b);
+
+    let mut heap = KeyValueHeap::meld(heap_a, heap_b);
+    check(&heap);
+    assert_eq!(heap.peek_min(), Some((&0, &Payload(vec![0; 100]))));
+    for key in [0, 1, 1, 2, 3, 4] {
+        assert_eq!(heap.extract_min(), Some((key, Payload(vec![key; 100]))));
+        check(&heap);
+    }
+    assert_eq!(heap.extract_min(), None);
+    check(&heap);
+    assert_eq!(heap.peek_min(), None);
+}
+
+/// The heaps are expected to compare only the first half of the items, and to extract items with
+/// equal first halves in the order they were inserted
+pub fn stable<H: MinHeap<Item = (u32, u32)> + CheckInvariants>(make_heap: impl Fn() -> H) {
+    let mut heap = make_heap();
+    for (i, key) in [3, 1, 3, 2, 1, 3].into_iter().enumerate() {
+        heap.insert((key, i as u32));
+        check(&heap);
+    }
+    assert_eq!(heap.peek_min(), Some(&(1, 1)));
+    assert_eq!(heap.extract_min(), Some((1, 1)));
+    check(&heap);
+    heap.insert((1, 6));
+    check(&heap);
+    heap.insert((3, 7));
+    check(&heap);
+    assert_eq!(
+        heap.drain_sorted().collect::<Vec<_>>(),
+        vec![(1, 4), (1, 6), (2, 3), (3, 0), (3, 2), (3, 5), (3, 7)]
+    );
+    check(&heap);
+
+    // Insertions alternate between the heaps, and the earlier one wins on either side
+    let mut heap_a = make_heap();
+    let mut heap_b = make_heap();
+    for i in 0..40 {
+        let heap = if i % 3 == 0 { &mut heap_a } else { &mut heap_b };
+        heap.insert((i % 4, i));
+        check(heap);
+    }
+    let heap = H::meld(heap_b, heap_a);
+    check(&heap);
+    let mut expected: Vec<_> = (0..40).map(|i| (i % 4, i)).collect();
+    expected.sort_unstable();
+    assert_eq!(heap.into_sorted_vec(), expected);
+
+    let mut heap = make_heap();
+    for i in (0..50).rev() {
+        heap.insert((0, 49 - i));
+        check(&heap);
+    }
+    for i in 0..50 {
+        assert_eq!(heap.extract_min(), Some((0, i)));
+        check(&heap);
+    }
 }
@@ -0,0 +1,517 @@
+//! Runs random sequences of operations on a heap and on `std::collections::BinaryHeap` as a
+//! reference model, checking that they agree after every operation. Failing sequences are shrunk
+//! to a minimal one before being reported
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap as ModelHeap,
+    fmt::Debug,
+    mem,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{CheckInvariants, MinHeap};
+
+/// Items the tester can build from a key and compare with the model by that key
+pub trait ModelItem: Debug {
+    fn from_key(key: u32) -> Self;
+    fn key(&self) -> u32;
+}
+
+impl ModelItem for u32 {
+    fn from_key(key: u32) -> Self {
+        key
+    }
+    fn key(&self) -> u32 {
+        *self
+    }
+}
+
+/// Items of a [`RadixHeap`](crate::RadixHeap) without values
+impl ModelItem for (u32, ()) {
+    fn from_key(key: u32) -> Self {
+        (key, ())
+    }
+    fn key(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Items of a [`BucketQueue`](crate::BucketQueue) without values
+impl ModelItem for (usize, ()) {
+    fn from_key(key: u32) -> Self {
+        (key as usize, ())
+    }
+    fn key(&self) -> u32 {
+        self.0 as u32
+    }
+}
+
+/// What the sequences may contain, for heaps that don't take every `u32`
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Keys are below this
+    pub max_key: u32,
+    /// Keys inserted are raised to the last key extracted, for monotone heaps
+    pub monotone: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_key: u32::MAX,
+            monotone: false,
+        }
+    }
+}
+
+/// Heap built on its own and then melded into the heap under test
+#[derive(Clone, Debug, Default)]
+pub struct SubHeap {
+    heapify: Vec<u32>,
+    inserts: Vec<u32>,
+    extracts: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum Op {
+    Insert(u32),
+    ExtractMin,
+    PeekMin,
+    /// Melds a sub-heap in, as the first heap passed to `meld` or as the second
+    Meld {
+        sub_heap: SubHeap,
+        sub_heap_first: bool,
+    },
+    /// Replaces the heap with a heapified one
+    Heapify(Vec<u32>),
+    Clear,
+}
+
+/// Heap under test next to the model, checking they agree
+struct Pair<H> {
+    heap: H,
+    model: ModelHeap<Reverse<u32>>,
+}
+
+impl<H: MinHeap<Item: ModelItem> + CheckInvariants + Default> Pair<H> {
+    fn heapify(keys: &[u32]) -> Self {
+        Self {
+            heap: H::heapify(keys.iter().map(|&key| H::Item::from_key(key)).collect()),
+            model: keys.iter().map(|&key| Reverse(key)).collect(),
+        }
+    }
+
+    fn insert(&mut self, key: u32) {
+        self.heap.insert(H::Item::from_key(key));
+        self.model.push(Reverse(key));
+    }
+
+    fn extract_min(&mut self) -> Result<Option<u32>, String> {
+        let key = self.heap.extract_min().map(|item| item.key());
+        let expected = self.model.pop().map(|Reverse(key)| key);
+        if key != expected {
+            return Err(format!("extract_min gave {key:?} instead of {expected:?}"));
+        }
+        Ok(key)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let key = self.heap.peek_min().map(ModelItem::key);
+        let expected = self.model.peek().map(|Reverse(key)| *key);
+        if key != expected {
+            return Err(format!("peek_min gave {key:?} instead of {expected:?}"));
+        }
+        if self.heap.len() != self.model.len() {
+            return Err(format!(
+                "len is {} instead of {}",
+                self.heap.len(),
+                self.model.len()
+            ));
+        }
+        self.heap.check_invariants().map_err(|err| err.to_string())
+    }
+}
+
+/// Runs the operations, returning a description of the first disagreement with the model
+fn run<H: MinHeap<Item: ModelItem> + CheckInvariants + Default>(
+    ops: &[Op],
+    config: Config,
+) -> Result<(), String> {
+    let mut pair = Pair::<H>::heapify(&[]);
+    // Lowest key a monotone heap accepts
+    let mut floor = 0;
+    let raise = |key: u32, floor: u32| if config.monotone { key.max(floor) } else { key };
+
+    for (i, op) in ops.iter().enumerate() {
+        let result = match op {
+            Op::Insert(key) => {
+                pair.insert(raise(*key, floor));
+                pair.check()
+            }
+            Op::ExtractMin => pair.extract_min().and_then(|key| {
+                floor = floor.max(key.unwrap_or(0));
+                pair.check()
+            }),
+            Op::PeekMin => pair.check(),
+            Op::Meld {
+                sub_heap,
+                sub_heap_first,
+            } => {
+                let heapify: Vec<u32> = sub_heap.heapify.iter().map(|&k| raise(k, floor)).collect();
+                let mut sub = Pair::<H>::heapify(&heapify);
+                for &key in &sub_heap.inserts {
+                    sub.insert(raise(key, floor));
+                }
+                let extracted = (0..sub_heap.extracts).try_for_each(|_| {
+                    sub.extract_min()
+                        .map(drop)
+                        .map_err(|err| format!("in sub-heap, {err}"))
+                });
+                extracted.and_then(|()| {
+                    let heap = mem::take(&mut pair.heap);
+                    let (heap_a, heap_b) = if *sub_heap_first {
+                        (sub.heap, heap)
+                    } else {
+                        (heap, sub.heap)
+                    };
+                    pair.heap = H::meld(heap_a, heap_b);
+                    pair.model.extend(sub.model);
+                    pair.check()
+                })
+            }
+            Op::Heapify(keys) => {
+                let keys: Vec<u32> = keys.iter().map(|&key| raise(key, floor)).collect();
+                pair = Pair::heapify(&keys);
+                pair.check()
+            }
+            Op::Clear => {
+                pair.heap.clear();
+                pair.model.clear();
+                pair.check()
+            }
+        };
+        result.map_err(|err| format!("after operation {i} ({op:?}): {err}"))?;
+    }
+
+    // Whatever is left must come out in order
+    while pair.extract_min()?.is_some() {}
+    pair.check()
+}
+
+/// Whether the operations fail, by disagreeing with the model or by panicking
+fn fails<H: MinHeap<Item: ModelItem> + CheckInvariants + Default>(
+    ops: &[Op],
+    config: Config,
+) -> Option<String> {
+    match panic::catch_unwind(AssertUnwindSafe(|| run::<H>(ops, config))) {
+        Ok(result) => result.err(),
+        Err(payload) => Some(
+            payload
+                .downcast_ref::<&str>()
+                .map(|msg| msg.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .map_or("panicked".to_string(), |msg| format!("panicked: {msg}")),
+        ),
+    }
+}
+
+/// Xorshift generator, so every sequence can be rebuilt from its seed
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // Spread out small seeds, and never start at 0 which xorshift can't leave
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    /// Mostly small keys so there are many duplicates, and now and then any key
+    fn key(&mut self, config: Config) -> u32 {
+        let bound = if self.below(4) == 0 {
+            config.max_key
+        } else {
+            config.max_key.min(32)
+        };
+        self.below(bound.into()) as u32
+    }
+
+    fn keys(&mut self, config: Config, max_len: u64) -> Vec<u32> {
+        let len = self.below(max_len + 1);
+        (0..len).map(|_| self.key(config)).collect()
+    }
+
+    fn op(&mut self, config: Config) -> Op {
+        match self.below(20) {
+            0..=7 => Op::Insert(self.key(config)),
+            8..=13 => Op::ExtractMin,
+            14 => Op::PeekMin,
+            15..=17 => Op::Meld {
+                sub_heap: SubHeap {
+                    heapify: self.keys(config, 20),
+                    inserts: self.keys(config, 10),
+                    extracts: self.below(4) as usize,
+                },
+                sub_heap_first: self.below(2) == 0,
+            },
+            18 => Op::Heapify(self.keys(config, 40)),
+            _ => Op::Clear,
+        }
+    }
+}
+
+/// Smaller versions of a list of keys: without halves or single keys, or with smaller keys
+fn simpler_keys(keys: &[u32]) -> Vec<Vec<u32>> {
+    let mut candidates = Vec::new();
+    if keys.len() > 1 {
+        candidates.push(keys[..keys.len() / 2].to_vec());
+        candidates.push(keys[keys.len() / 2..].to_vec());
+    }
+    for i in 0..keys.len() {
+        let mut candidate = keys.to_vec();
+        candidate.remove(i);
+        candidates.push(candidate);
+    }
+    for i in 0..keys.len() {
+        if keys[i] > 0 {
+            let mut candidate = keys.to_vec();
+            candidate[i] /= 2;
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+impl Op {
+    /// Simpler operations to try in place of this one while shrinking
+    fn simpler(&self) -> Vec<Op> {
+        match self {
+            Op::Insert(0) | Op::ExtractMin | Op::PeekMin | Op::Clear => vec![],
+            Op::Insert(key) => vec![Op::Insert(0), Op::Insert(key / 2), Op::Insert(key - 1)],
+            Op::Heapify(keys) => {
+                let mut candidates = vec![Op::Clear];
+                candidates.extend(simpler_keys(keys).into_iter().map(Op::Heapify));
+                candidates
+            }
+            Op::Meld {
+                sub_heap,
+                sub_heap_first,
+            } => {
+                let meld = |sub_heap: SubHeap| Op::Meld {
+                    sub_heap,
+                    sub_heap_first: *sub_heap_first,
+                };
+                let mut candidates = Vec::new();
+                if *sub_heap_first {
+                    candidates.push(Op::Meld {
+                        sub_heap: sub_heap.clone(),
+                        sub_heap_first: false,
+                    });
+                }
+                if sub_heap.extracts > 0 {
+                    candidates.push(meld(SubHeap {
+                        extracts: sub_heap.extracts - 1,
+                        ..sub_heap.clone()
+                    }));
+                }
+                for heapify in simpler_keys(&sub_heap.heapify) {
+                    candidates.push(meld(SubHeap {
+                        heapify,
+                        ..sub_heap.clone()
+                    }));
+                }
+                for inserts in simpler_keys(&sub_heap.inserts) {
+                    candidates.push(meld(SubHeap {
+                        inserts,
+                        ..sub_heap.clone()
+                    }));
+                }
+                candidates
+            }
+        }
+    }
+}
+
+/// Shrinks a failing sequence by removing runs of operations and simplifying single ones, for as
+/// long as the result still fails
+fn shrink<H: MinHeap<Item: ModelItem> + CheckInvariants + Default>(
+    mut ops: Vec<Op>,
+    config: Config,
+) -> Vec<Op> {
+    loop {
+        let mut shrunk = false;
+
+        let mut chunk = ops.len().div_ceil(2);
+        while chunk > 0 {
+            let mut start = 0;
+            while start < ops.len() {
+                let mut candidate = ops.clone();
+                candidate.drain(start..(start + chunk).min(ops.len()));
+                if fails::<H>(&candidate, config).is_some() {
+                    ops = candidate;
+                    shrunk = true;
+                } else {
+                    start += chunk;
+                }
+            }
+            chunk /= 2;
+        }
+
+        for i in 0..ops.len() {
+            for op in ops[i].simpler() {
+                let mut candidate = ops.clone();
+                candidate[i] = op;
+                if fails::<H>(&candidate, config).is_some() {
+                    ops = candidate;
+                    shrunk = true;
+                    break;
+                }
+            }
+        }
+
+        if !shrunk {
+            return ops;
+        }
+    }
+}
+
+/// Number of random sequences run on each heap
+const CASES: u64 = if cfg!(miri) { 20 } else { 300 };
+/// Longest random sequence
+const MAX_OPS: u64 = if cfg!(miri) { 40 } else { 80 };
+
+/// Runs random sequences on the heap and panics with a shrunk failing sequence if it ever
+/// disagrees with the model
+pub fn differential<H: MinHeap<Item: ModelItem> + CheckInvariants + Default>() {
+    differential_with::<H>(Config::default());
+}
+
+pub fn differential_with<H: MinHeap<Item: ModelItem> + CheckInvariants + Default>(config: Config) {
+    if let Err(report) = find_failure::<H>(config) {
+        panic!("{report}");
+    }
+}
+
+fn find_failure<H: MinHeap<Item: ModelItem> + CheckInvariants + Default>(
+    config: Config,
+) -> Result<(), String> {
+    for seed in 0..CASES {
+        let mut rng = Rng::new(seed);
+        let len = rng.below(MAX_OPS + 1);
+        let ops: Vec<Op> = (0..len).map(|_| rng.op(config)).collect();
+        if fails::<H>(&ops, config).is_some() {
+            let ops = shrink::<H>(ops, config);
+            let error = fails::<H>(&ops, config).unwrap();
+            return Err(format!(
+                "{} disagrees with the model on seed {seed}: {error}\nshrunk sequence: {ops:#?}",
+                std::any::type_name::<H>()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BinaryHeap;
+
+    /// Binary heap that loses the min of the second heap when melding
+    #[derive(Default)]
+    struct LossyMeldHeap(BinaryHeap<u32>);
+
+    impl MinHeap for LossyMeldHeap {
+        type Item = u32;
+
+        fn peek_min(&self) -> Option<&u32> {
+            self.0.peek_min()
+        }
+        fn extract_min(&mut self) -> Option<u32> {
+            self.0.extract_min()
+        }
+        fn insert(&mut self, item: u32) {
+            self.0.insert(item);
+        }
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+        fn clear(&mut self) {
+            self.0.clear();
+        }
+        fn heapify(items: Vec<u32>) -> Self {
+            Self(BinaryHeap::heapify(items))
+        }
+        fn meld(heap_a: Self, mut heap_b: Self) -> Self {
+            heap_b.0.extract_min();
+            Self(BinaryHeap::meld(heap_a.0, heap_b.0))
+        }
+    }
+
+    impl CheckInvariants for LossyMeldHeap {
+        fn check_invariants(&self) -> Result<(), crate::InvariantError> {
+            self.0.check_invariants()
+        }
+    }
+
+    #[test]
+    fn model_agrees_with_correct_heap() {
+        assert_eq!(find_failure::<BinaryHeap<u32>>(Config::default()), Ok(()));
+    }
+
+    #[test]
+    fn failure_is_shrunk() {
+        let report = find_failure::<LossyMeldHeap>(Config::default()).unwrap_err();
+        let ops = shrink::<LossyMeldHeap>(vec![Op::ExtractMin; 3], Config::default());
+        assert_eq!(ops.len(), 3, "a passing sequence isn't shrunk");
+        assert!(report.contains("disagrees with the model"), "{report}");
+
+        let mut rng = Rng::new(7);
+        let ops: Vec<Op> = (0..MAX_OPS)
+            .map(|_| rng.op(Config::default()))
+            .chain([Op::Meld {
+                sub_heap: SubHeap {
+                    heapify: vec![5, 9],
+                    inserts: vec![3],
+                    extracts: 0,
+                },
+                sub_heap_first: true,
+            }])
+            .collect();
+        // The smallest failing sequence melds in a sub-heap with a single key
+        let ops = shrink::<LossyMeldHeap>(ops, Config::default());
+        assert_eq!(ops.len(), 1, "{ops:?}");
+        let Op::Meld { sub_heap, .. } = &ops[0] else {
+            panic!("{ops:?}")
+        };
+        assert_eq!(
+            sub_heap.heapify.len() + sub_heap.inserts.len(),
+            1,
+            "{ops:?}"
+        );
+    }
+
+    #[test]
+    fn monotone_keys_raised() {
+        let config = Config {
+            max_key: 100,
+            monotone: true,
+        };
+        let ops = [
+            Op::Insert(50),
+            Op::ExtractMin,
+            Op::Insert(10),
+            Op::Heapify(vec![20, 70]),
+        ];
+        assert_eq!(run::<crate::RadixHeap<u32>>(&ops, config), Ok(()));
+    }
+}